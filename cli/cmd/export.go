@@ -32,7 +32,17 @@ var exportProfileCmd = &cobra.Command{
 	Short: "Regenerate writing profile and guard hook",
 	Run: func(cmd *cobra.Command, args []string) {
 		dbPath := resolveDBPath()
-		if err := profile.ExportProfile(dbPath); err != nil {
+		list, _ := cmd.Flags().GetString("targets")
+		targets, err := profile.ParseTargets(list)
+		if err != nil {
+			output.ErrorE(err)
+		}
+		severity, _ := cmd.Flags().GetString("min-severity")
+		minSeverity, err := profile.ParseMinSeverity(severity)
+		if err != nil {
+			output.ErrorE(err)
+		}
+		if err := profile.ExportProfileTargets(dbPath, targets, minSeverity); err != nil {
 			output.ErrorE(err)
 		}
 		output.JSON(map[string]bool{"success": true}, pretty)
@@ -41,6 +51,8 @@ var exportProfileCmd = &cobra.Command{
 
 func init() {
 	exportWaitCmd.Flags().Int("timeout", 300, "timeout in seconds (max 600)")
+	exportProfileCmd.Flags().String("targets", "", "comma-separated files to write: markdown, hook (default both)")
+	exportProfileCmd.Flags().String("min-severity", "", "only guard against rules at or above this severity; must-fix violations then deny (default: the app's last choice)")
 
 	exportCmd.AddCommand(exportWaitCmd, exportProfileCmd)
 	rootCmd.AddCommand(exportCmd)
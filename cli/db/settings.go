@@ -0,0 +1,20 @@
+package db
+
+import "database/sql"
+
+// GuardMinSeveritySetting holds the minimum severity the guard hook was last
+// exported with. Matches GUARD_MIN_SEVERITY_KEY in the app.
+const GuardMinSeveritySetting = "guard_min_severity"
+
+// GetSetting reads a value from the app's settings table, or "" when unset.
+func GetSetting(d *sql.DB, key string) (string, error) {
+	var value string
+	err := d.QueryRow("SELECT value FROM settings WHERE key = ?", key).Scan(&value)
+	if err == sql.ErrNoRows {
+		return "", nil
+	}
+	if err != nil {
+		return "", err
+	}
+	return value, nil
+}
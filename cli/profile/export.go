@@ -205,35 +205,75 @@ func formatRulesSection(rules []db.WritingRule) []string {
 	return lines
 }
 
+// severityRank orders severities from least to most strict; unknown values rank 0.
+func severityRank(severity string) int {
+	switch severity {
+	case "must-fix":
+		return 3
+	case "should-fix":
+		return 2
+	case "nice-to-fix":
+		return 1
+	}
+	return 0
+}
+
+// ParseMinSeverity validates a --min-severity value. Empty keeps the default guard.
+func ParseMinSeverity(value string) (string, error) {
+	value = strings.TrimSpace(value)
+	if value != "" && severityRank(value) == 0 {
+		return "", fmt.Errorf("unknown severity %q (expected must-fix, should-fix, or nice-to-fix)", value)
+	}
+	return value, nil
+}
+
 // GenerateWritingGuardPy generates the Python hook script.
 // This is the canonical generator — Rust and MCP delegate to the CLI.
-func GenerateWritingGuardPy(rules []db.WritingRule) string {
-	var killWords []string
-	var slopPatterns [][2]string
-	var headingPatterns [][2]string
-	var autoCorrections [][2]string
+//
+// With an empty minSeverity the guard checks must-fix kill words, heading
+// patterns, and auto-corrections plus every slop pattern, and only asks.
+// Otherwise every category is limited to rules at or above minSeverity, and
+// must-fix violations deny the write while the rest ask.
+func GenerateWritingGuardPy(rules []db.WritingRule, minSeverity string) string {
+	var killWords [][2]string
+	var slopPatterns [][3]string
+	var headingPatterns [][3]string
+	var autoCorrections [][3]string
+
+	include := func(r db.WritingRule, mustFixByDefault bool) bool {
+		if minSeverity == "" {
+			return !mustFixByDefault || r.Severity == "must-fix"
+		}
+		return severityRank(r.Severity) >= severityRank(minSeverity)
+	}
 
 	for _, r := range rules {
-		if r.Severity == "must-fix" && r.Category == "kill-words" {
-			killWords = append(killWords, r.RuleText)
+		if r.Category == "kill-words" && include(r, true) {
+			killWords = append(killWords, [2]string{r.RuleText, r.Severity})
 		}
-		if r.Category == "ai-slop" && r.ExampleBefore != nil {
-			slopPatterns = append(slopPatterns, [2]string{*r.ExampleBefore, r.RuleText})
+		if r.Category == "ai-slop" && r.ExampleBefore != nil && include(r, false) {
+			slopPatterns = append(slopPatterns, [3]string{*r.ExampleBefore, r.RuleText, r.Severity})
 		}
-		if r.Category == "heading-patterns" && r.Severity == "must-fix" && r.ExampleBefore != nil {
-			headingPatterns = append(headingPatterns, [2]string{*r.ExampleBefore, r.RuleText})
+		if r.Category == "heading-patterns" && r.ExampleBefore != nil && include(r, true) {
+			headingPatterns = append(headingPatterns, [3]string{*r.ExampleBefore, r.RuleText, r.Severity})
 		}
-		if r.Category == "auto-synthesized" && r.Severity == "must-fix" && r.ExampleBefore != nil {
+		if r.Category == "auto-synthesized" && r.ExampleBefore != nil && include(r, true) {
 			if utf8.RuneCountInString(*r.ExampleBefore) <= 80 {
-				autoCorrections = append(autoCorrections, [2]string{*r.ExampleBefore, r.RuleText})
+				autoCorrections = append(autoCorrections, [3]string{*r.ExampleBefore, r.RuleText, r.Severity})
 			}
 		}
 	}
 
+	blockingSeverities := []string{}
+	if minSeverity != "" {
+		blockingSeverities = append(blockingSeverities, "must-fix")
+	}
+
 	killWordsJSON, _ := json.Marshal(killWords)
 	slopPatternsJSON, _ := json.Marshal(slopPatterns)
 	headingPatternsJSON, _ := json.Marshal(headingPatterns)
 	autoCorrectionsJSON, _ := json.Marshal(autoCorrections)
+	blockingJSON, _ := json.Marshal(blockingSeverities)
 
 	for _, blob := range [][]byte{killWordsJSON, slopPatternsJSON, headingPatternsJSON, autoCorrectionsJSON} {
 		if strings.Contains(string(blob), `"""`) {
@@ -258,18 +298,21 @@ import json, sys, re
 # Only check prose file extensions
 PROSE_EXTENSIONS = {".md", ".mdx", ".txt", ".html", ".htm"}
 
-# Kill words — loaded from JSON for codegen safety.
+# Kill words — [word, severity], loaded from JSON for codegen safety.
 KILL_WORDS = json.loads(r"""%s""")
 
-# AI-slop sentence patterns — [pattern, explanation]
+# AI-slop sentence patterns — [pattern, explanation, severity]
 SLOP_PATTERNS = json.loads(r"""%s""")
 
-# Heading patterns — [regex, explanation] applied per heading line
+# Heading patterns — [regex, explanation, severity] applied per heading line
 HEADING_PATTERNS = json.loads(r"""%s""")
 
-# Auto-synthesized corrections — [original_text, explanation] substring match
+# Auto-synthesized corrections — [original_text, explanation, severity] substring match
 AUTO_CORRECTIONS = json.loads(r"""%s""")
 
+# Severities that deny the write outright; any other violation asks
+BLOCKING_SEVERITIES = json.loads(r"""%s""")
+
 def get_extension(path):
     if not path:
         return ""
@@ -299,14 +342,14 @@ def main():
 
         # Check kill words
         lower = text.lower()
-        for word in KILL_WORDS:
+        for word, severity in KILL_WORDS:
             if word in lower:
-                violations.append(f'Kill word: "{word}"')
+                violations.append((f'Kill word: "{word}"', severity))
 
         # Check slop patterns
-        for pattern, explanation in SLOP_PATTERNS:
+        for pattern, explanation, severity in SLOP_PATTERNS:
             if re.search(pattern, text):
-                violations.append(explanation)
+                violations.append((explanation, severity))
 
         # Check heading patterns
         if HEADING_PATTERNS:
@@ -317,26 +360,27 @@ def main():
                 heading_text = stripped.lstrip('#').strip()
                 if not heading_text:
                     continue
-                for pattern, explanation in HEADING_PATTERNS:
+                for pattern, explanation, severity in HEADING_PATTERNS:
                     if re.search(pattern, heading_text):
-                        violations.append(f'{explanation}: "{stripped}"')
+                        violations.append((f'{explanation}: "{stripped}"', severity))
                         break
 
         # Check auto-synthesized corrections (substring match)
-        for original_text, explanation in AUTO_CORRECTIONS:
+        for original_text, explanation, severity in AUTO_CORRECTIONS:
             if original_text.lower() in lower:
-                violations.append(f'Auto-correction: "{original_text}" — {explanation}')
+                violations.append((f'Auto-correction: "{original_text}" — {explanation}', severity))
 
         if violations:
             msg = "WRITING GUARD: Writing rule violations detected:\n"
-            for v in violations:
+            for v, _ in violations:
                 msg += f"  - {v}\n"
             msg += "Fix violations. See ~/.margin/writing-rules.md for rules."
+            blocked = any(severity in BLOCKING_SEVERITIES for _, severity in violations)
 
             print(json.dumps({
                 "hookSpecificOutput": {
                     "hookEventName": "PreToolUse",
-                    "permissionDecision": "ask",
+                    "permissionDecision": "deny" if blocked else "ask",
                     "permissionDecisionReason": msg
                 }
             }))
@@ -349,17 +393,62 @@ def main():
 
 if __name__ == "__main__":
     main()
-`, string(killWordsJSON), string(slopPatternsJSON), string(headingPatternsJSON), string(autoCorrectionsJSON))
+`, string(killWordsJSON), string(slopPatternsJSON), string(headingPatternsJSON), string(autoCorrectionsJSON), string(blockingJSON))
+}
+
+// Targets selects which profile files ExportProfile writes.
+type Targets struct {
+	Markdown bool
+	Hook     bool
+}
+
+// AllTargets writes both the rules markdown and the guard hook.
+var AllTargets = Targets{Markdown: true, Hook: true}
+
+// ParseTargets reads a comma-separated list of "markdown" and "hook".
+// An empty list means both.
+func ParseTargets(list string) (Targets, error) {
+	if strings.TrimSpace(list) == "" {
+		return AllTargets, nil
+	}
+	var t Targets
+	for _, name := range strings.Split(list, ",") {
+		switch strings.TrimSpace(name) {
+		case "markdown":
+			t.Markdown = true
+		case "hook":
+			t.Hook = true
+		default:
+			return Targets{}, fmt.Errorf("unknown export target %q (expected markdown or hook)", name)
+		}
+	}
+	return t, nil
 }
 
 // ExportProfile writes ~/.margin/writing-rules.md and ~/.claude/hooks/writing_guard.py.
 func ExportProfile(dbPath string) error {
+	return ExportProfileTargets(dbPath, AllTargets, "")
+}
+
+// ExportProfileTargets writes only the profile files selected by targets,
+// building the guard hook from rules at or above minSeverity. An empty
+// minSeverity uses the one the app last exported with, so re-exports after
+// rule or correction changes keep a narrowed guard; unset means the default.
+func ExportProfileTargets(dbPath string, targets Targets, minSeverity string) error {
 	d, err := db.OpenRead(dbPath)
 	if err != nil {
 		return err
 	}
 	defer d.Close()
 
+	if minSeverity == "" {
+		// Databases from before the settings table just get the default guard.
+		stored, _ := db.GetSetting(d, db.GuardMinSeveritySetting)
+		if parsed, err := ParseMinSeverity(stored); err == nil {
+			minSeverity = parsed
+		}
+	}
+
 	rules, err := db.GetWritingRules(d, nil)
 	if err != nil {
 		return err
@@ -371,21 +460,28 @@ func ExportProfile(dbPath string) error {
 	}
 
 	home, _ := os.UserHomeDir()
+	return writeProfileFiles(home, rules, corrections, targets, minSeverity)
+}
 
+func writeProfileFiles(home string, rules []db.WritingRule, corrections []db.CorrectionRecord, targets Targets, minSeverity string) error {
 	// Write writing-rules.md
-	profileMD := FormatProfileMarkdown(rules, corrections)
-	rulesPath := filepath.Join(home, ".margin", "writing-rules.md")
-	if err := os.WriteFile(rulesPath, []byte(profileMD), 0644); err != nil {
-		return fmt.Errorf("failed to write %s: %w", rulesPath, err)
+	if targets.Markdown {
+		profileMD := FormatProfileMarkdown(rules, corrections)
+		rulesPath := filepath.Join(home, ".margin", "writing-rules.md")
+		if err := os.WriteFile(rulesPath, []byte(profileMD), 0644); err != nil {
+			return fmt.Errorf("failed to write %s: %w", rulesPath, err)
+		}
 	}
 
 	// Write writing_guard.py
-	guardPy := GenerateWritingGuardPy(rules)
-	hooksDir := filepath.Join(home, ".claude", "hooks")
-	os.MkdirAll(hooksDir, 0755)
-	guardPath := filepath.Join(hooksDir, "writing_guard.py")
-	if err := os.WriteFile(guardPath, []byte(guardPy), 0755); err != nil {
-		return fmt.Errorf("failed to write %s: %w", guardPath, err)
+	if targets.Hook {
+		guardPy := GenerateWritingGuardPy(rules, minSeverity)
+		hooksDir := filepath.Join(home, ".claude", "hooks")
+		os.MkdirAll(hooksDir, 0755)
+		guardPath := filepath.Join(hooksDir, "writing_guard.py")
+		if err := os.WriteFile(guardPath, []byte(guardPy), 0755); err != nil {
+			return fmt.Errorf("failed to write %s: %w", guardPath, err)
+		}
 	}
 
 	return nil
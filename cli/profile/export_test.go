@@ -1,6 +1,8 @@
 package profile
 
 import (
+	"os"
+	"path/filepath"
 	"strings"
 	"testing"
 
@@ -125,7 +127,7 @@ func TestGenerateWritingGuardPy(t *testing.T) {
 		{Category: "ai-slop", Severity: "should-fix", RuleText: "Don't start with In today's", ExampleBefore: ptr(`(?i)^in today'?s`)},
 	}
 
-	py := GenerateWritingGuardPy(rules)
+	py := GenerateWritingGuardPy(rules, "")
 
 	checks := []string{
 		"#!/usr/bin/env python3",
@@ -140,6 +142,48 @@ func TestGenerateWritingGuardPy(t *testing.T) {
 			t.Errorf("GenerateWritingGuardPy missing %q", check)
 		}
 	}
+	if !strings.Contains(py, `BLOCKING_SEVERITIES = json.loads(r"""[]""")`) {
+		t.Error("default guard should never deny")
+	}
+}
+
+func TestGenerateWritingGuardPyMinSeverity(t *testing.T) {
+	rules := []db.WritingRule{
+		{Category: "kill-words", Severity: "must-fix", RuleText: "leverage"},
+		{Category: "kill-words", Severity: "should-fix", RuleText: "utilize"},
+		{Category: "kill-words", Severity: "nice-to-fix", RuleText: "very"},
+		{Category: "ai-slop", Severity: "nice-to-fix", RuleText: "Hedging", ExampleBefore: ptr(`(?i)it seems`)},
+	}
+
+	py := GenerateWritingGuardPy(rules, "should-fix")
+	for _, want := range []string{`["leverage","must-fix"]`, `["utilize","should-fix"]`, `BLOCKING_SEVERITIES = json.loads(r"""["must-fix"]""")`} {
+		if !strings.Contains(py, want) {
+			t.Errorf("GenerateWritingGuardPy(should-fix) missing %q", want)
+		}
+	}
+	for _, unwanted := range []string{`"very"`, "Hedging"} {
+		if strings.Contains(py, unwanted) {
+			t.Errorf("GenerateWritingGuardPy(should-fix) should drop %q", unwanted)
+		}
+	}
+
+	// Default keeps today's filter: only must-fix kill words, every slop pattern
+	py = GenerateWritingGuardPy(rules, "")
+	if strings.Contains(py, `"utilize"`) || !strings.Contains(py, "Hedging") {
+		t.Error("default guard filter changed")
+	}
+}
+
+func TestParseMinSeverity(t *testing.T) {
+	if got, err := ParseMinSeverity(""); got != "" || err != nil {
+		t.Errorf("ParseMinSeverity(\"\") = %q, %v", got, err)
+	}
+	if got, _ := ParseMinSeverity(" must-fix "); got != "must-fix" {
+		t.Errorf("ParseMinSeverity(must-fix) = %q", got)
+	}
+	if _, err := ParseMinSeverity("urgent"); err == nil {
+		t.Error("ParseMinSeverity(\"urgent\") should fail")
+	}
 }
 
 func TestCategoryLabel(t *testing.T) {
@@ -159,3 +203,38 @@ func TestCategoryLabel(t *testing.T) {
 		}
 	}
 }
+
+func TestParseTargets(t *testing.T) {
+	if got, _ := ParseTargets(""); got != AllTargets {
+		t.Errorf("ParseTargets(\"\") = %+v, want both", got)
+	}
+	if got, _ := ParseTargets("markdown"); got != (Targets{Markdown: true}) {
+		t.Errorf("ParseTargets(\"markdown\") = %+v", got)
+	}
+	if got, _ := ParseTargets("hook, markdown"); got != AllTargets {
+		t.Errorf("ParseTargets(\"hook, markdown\") = %+v", got)
+	}
+	if _, err := ParseTargets("pdf"); err == nil {
+		t.Error("ParseTargets(\"pdf\") should fail")
+	}
+}
+
+func TestWriteProfileFilesMarkdownOnly(t *testing.T) {
+	home := t.TempDir()
+	if err := os.MkdirAll(filepath.Join(home, ".margin"), 0755); err != nil {
+		t.Fatal(err)
+	}
+	rules := []db.WritingRule{
+		{ID: "1", WritingType: "general", Category: "editorial", RuleText: "Keep it short", Severity: "must-fix", Source: "manual"},
+	}
+
+	if err := writeProfileFiles(home, rules, nil, Targets{Markdown: true}, ""); err != nil {
+		t.Fatal(err)
+	}
+	if _, err := os.Stat(filepath.Join(home, ".margin", "writing-rules.md")); err != nil {
+		t.Errorf("writing-rules.md not written: %v", err)
+	}
+	if _, err := os.Stat(filepath.Join(home, ".claude", "hooks", "writing_guard.py")); !os.IsNotExist(err) {
+		t.Errorf("writing_guard.py should not be written, stat err = %v", err)
+	}
+}
@@ -0,0 +1,154 @@
+//! Re-anchoring of highlight text in content that may have changed since the
+//! highlight was made. Mirrors `resolveAnchor` in `src/lib/text-anchoring.ts`;
+//! offsets are in UTF-16 code units, like the JS string indices stored in
+//! `from_pos`/`to_pos`.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnchorConfidence {
+    Exact,
+    Fuzzy,
+    Orphaned,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnchorMatch {
+    pub from: usize,
+    pub to: usize,
+    pub confidence: AnchorConfidence,
+}
+
+pub struct TextAnchor<'a> {
+    pub text: &'a str,
+    pub prefix: &'a str,
+    pub suffix: &'a str,
+    pub from: usize,
+}
+
+fn find_from(haystack: &[u16], needle: &[u16], start: usize) -> Option<usize> {
+    if needle.is_empty() || needle.len() > haystack.len() {
+        return None;
+    }
+    (start..=haystack.len() - needle.len()).find(|&i| haystack[i..i + needle.len()] == *needle)
+}
+
+/// Counts matching chars walking outward from the candidate's boundaries.
+fn context_score(full: &[u16], idx: usize, text_len: usize, prefix: &[u16], suffix: &[u16]) -> usize {
+    let actual_prefix = &full[idx.saturating_sub(prefix.len())..idx];
+    let suffix_start = idx + text_len;
+    let actual_suffix = &full[suffix_start..(suffix_start + suffix.len()).min(full.len())];
+
+    let prefix_score = prefix
+        .iter()
+        .rev()
+        .zip(actual_prefix.iter().rev())
+        .filter(|(a, b)| a == b)
+        .count();
+    let suffix_score = suffix
+        .iter()
+        .zip(actual_suffix.iter())
+        .filter(|(a, b)| a == b)
+        .count();
+    prefix_score + suffix_score
+}
+
+/// Tries, in order: the original position, text with its full context, then
+/// every occurrence of the text ranked by context similarity.
+pub fn resolve_anchor(full_text: &str, anchor: &TextAnchor) -> AnchorMatch {
+    let full: Vec<u16> = full_text.encode_utf16().collect();
+    let text: Vec<u16> = anchor.text.encode_utf16().collect();
+    let prefix: Vec<u16> = anchor.prefix.encode_utf16().collect();
+    let suffix: Vec<u16> = anchor.suffix.encode_utf16().collect();
+    let at = |from: usize, confidence| AnchorMatch {
+        from,
+        to: from + text.len(),
+        confidence,
+    };
+
+    if text.is_empty() {
+        return at(anchor.from, AnchorConfidence::Orphaned);
+    }
+
+    // 1. Original position still holds the text
+    if full.get(anchor.from..anchor.from + text.len()) == Some(&text[..]) {
+        return at(anchor.from, AnchorConfidence::Exact);
+    }
+
+    // 2. Text with its surrounding context moved intact
+    let context: Vec<u16> = prefix.iter().chain(&text).chain(&suffix).copied().collect();
+    if let Some(idx) = find_from(&full, &context, 0) {
+        return at(idx + prefix.len(), AnchorConfidence::Exact);
+    }
+
+    // 3. Text alone; first occurrence with the best context score wins
+    let mut best: Option<(usize, usize)> = None;
+    let mut start = 0;
+    while let Some(idx) = find_from(&full, &text, start) {
+        let score = context_score(&full, idx, text.len(), &prefix, &suffix);
+        if best.is_none_or(|(_, best_score)| score > best_score) {
+            best = Some((idx, score));
+        }
+        start = idx + 1;
+    }
+
+    match best {
+        Some((idx, _)) => at(idx, AnchorConfidence::Fuzzy),
+        None => at(anchor.from, AnchorConfidence::Orphaned),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anchor<'a>(text: &'a str, prefix: &'a str, suffix: &'a str, from: usize) -> TextAnchor<'a> {
+        TextAnchor { text, prefix, suffix, from }
+    }
+
+    #[test]
+    fn exact_position_match() {
+        let result = resolve_anchor("Hello world, how are you?", &anchor("world", "Hello ", ", how", 6));
+        assert_eq!(result, AnchorMatch { from: 6, to: 11, confidence: AnchorConfidence::Exact });
+    }
+
+    #[test]
+    fn shifted_text_found_by_context() {
+        let result = resolve_anchor("Intro. Hello world, how are you?", &anchor("world", "Hello ", ", how", 6));
+        assert_eq!(result, AnchorMatch { from: 13, to: 18, confidence: AnchorConfidence::Exact });
+    }
+
+    #[test]
+    fn repeated_text_disambiguated_by_context() {
+        let full = "the cat sat. the dog ran. the cat ran.";
+        // Suffix was edited, so only the second "the cat" scores on both sides
+        let result = resolve_anchor(full, &anchor("the cat", "ran. ", " ran!", 100));
+        assert_eq!(result.from, 26);
+        assert_eq!(result.confidence, AnchorConfidence::Fuzzy);
+    }
+
+    #[test]
+    fn missing_text_is_orphaned() {
+        let result = resolve_anchor("Nothing to see here", &anchor("world", "", "", 3));
+        assert_eq!(result.confidence, AnchorConfidence::Orphaned);
+        assert_eq!(result.from, 3);
+    }
+
+    #[test]
+    fn offsets_are_not_bytes() {
+        let result = resolve_anchor("café — naïve text", &anchor("naïve", "— ", " text", 0));
+        assert_eq!(result, AnchorMatch { from: 7, to: 12, confidence: AnchorConfidence::Exact });
+    }
+
+    #[test]
+    fn offsets_are_in_utf16_units() {
+        // The emoji is one char but two UTF-16 units, as in the editor's JS offsets
+        let full = "🎉 Hello world";
+        let exact = resolve_anchor(full, &anchor("world", "Hello ", "", 9));
+        assert_eq!(exact, AnchorMatch { from: 9, to: 14, confidence: AnchorConfidence::Exact });
+
+        let shifted = resolve_anchor(full, &anchor("world", "Hello ", "", 6));
+        assert_eq!(shifted, AnchorMatch { from: 9, to: 14, confidence: AnchorConfidence::Exact });
+
+        let emoji = resolve_anchor("a 🎉 b", &anchor("🎉 b", "", "", 0));
+        assert_eq!(emoji, AnchorMatch { from: 2, to: 6, confidence: AnchorConfidence::Exact });
+    }
+}
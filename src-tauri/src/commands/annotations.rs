@@ -1,6 +1,8 @@
+use crate::anchoring::{resolve_anchor, AnchorConfidence, TextAnchor};
 use crate::commands::now_millis;
+use crate::commands::settings::{default_highlight_color, note_template_for};
 use crate::db::migrations::DbPool;
-use crate::db::models::{Highlight, MarginNote};
+use crate::db::models::{Highlight, HighlightWithNotes, MarginNote};
 use rusqlite::Connection;
 use uuid::Uuid;
 
@@ -57,13 +59,17 @@ fn insert_highlight(
     Ok(())
 }
 
+fn resolve_highlight_color(conn: &Connection, color: Option<String>) -> String {
+    color.unwrap_or_else(|| default_highlight_color(conn))
+}
+
 fn fetch_highlights(conn: &Connection, document_id: &str) -> Result<Vec<Highlight>, String> {
     let mut stmt = conn
         .prepare(
             "SELECT id, document_id, color, text_content, from_pos, to_pos,
                     prefix_context, suffix_context, created_at, updated_at
              FROM highlights
-             WHERE document_id = ?1
+             WHERE document_id = ?1 AND deleted_at IS NULL
              ORDER BY from_pos",
         )
         .map_err(|e| e.to_string())?;
@@ -76,21 +82,281 @@ fn fetch_highlights(conn: &Connection, document_id: &str) -> Result<Vec<Highligh
     results
 }
 
-fn set_highlight_color(conn: &Connection, id: &str, color: &str, now: i64) -> Result<(), String> {
+fn fetch_highlights_by_color(conn: &Connection, document_id: &str, color: &str) -> Result<Vec<Highlight>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, document_id, color, text_content, from_pos, to_pos,
+                    prefix_context, suffix_context, created_at, updated_at
+             FROM highlights
+             WHERE document_id = ?1 AND color = ?2 AND deleted_at IS NULL
+             ORDER BY from_pos",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let results = stmt
+        .query_map(rusqlite::params![document_id, color], Highlight::from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string());
+    results
+}
+
+/// Highlight tags are stored trimmed and lowercased, like document tags.
+fn normalize_highlight_tag(tag: &str) -> Result<String, String> {
+    let tag = tag.trim().to_lowercase();
+    if tag.is_empty() {
+        return Err("Tag cannot be empty".to_string());
+    }
+    Ok(tag)
+}
+
+/// Returns `false` when the highlight already had the tag.
+fn add_highlight_tag_inner(conn: &Connection, highlight_id: &str, tag: &str, now: i64) -> Result<bool, String> {
+    let tag = normalize_highlight_tag(tag)?;
+    let inserted = conn
+        .execute(
+            "INSERT OR IGNORE INTO highlight_tags (highlight_id, tag, created_at) VALUES (?1, ?2, ?3)",
+            rusqlite::params![highlight_id, tag, now],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(inserted > 0)
+}
+
+/// Returns `false` when the highlight didn't have the tag.
+fn remove_highlight_tag_inner(conn: &Connection, highlight_id: &str, tag: &str) -> Result<bool, String> {
+    let tag = normalize_highlight_tag(tag)?;
+    let removed = conn
+        .execute(
+            "DELETE FROM highlight_tags WHERE highlight_id = ?1 AND tag = ?2",
+            rusqlite::params![highlight_id, tag],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(removed > 0)
+}
+
+fn fetch_highlights_by_tag(conn: &Connection, document_id: &str, tag: &str) -> Result<Vec<Highlight>, String> {
+    let tag = normalize_highlight_tag(tag)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT h.id, h.document_id, h.color, h.text_content, h.from_pos, h.to_pos,
+                    h.prefix_context, h.suffix_context, h.created_at, h.updated_at
+             FROM highlights h
+             JOIN highlight_tags t ON t.highlight_id = h.id
+             WHERE h.document_id = ?1 AND t.tag = ?2 AND h.deleted_at IS NULL
+             ORDER BY h.from_pos",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let results = stmt
+        .query_map(rusqlite::params![document_id, tag], Highlight::from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string());
+    results
+}
+
+/// Fills in `tags` (alphabetical, possibly empty) on each of a document's highlights.
+fn attach_highlight_tags(conn: &Connection, document_id: &str, highlights: &mut [Highlight]) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.highlight_id, t.tag
+             FROM highlight_tags t
+             JOIN highlights h ON h.id = t.highlight_id
+             WHERE h.document_id = ?1
+             ORDER BY t.tag",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([document_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| e.to_string())?;
+
+    let mut by_highlight: std::collections::HashMap<String, Vec<String>> = std::collections::HashMap::new();
+    for row in rows {
+        let (highlight_id, tag) = row.map_err(|e| e.to_string())?;
+        by_highlight.entry(highlight_id).or_default().push(tag);
+    }
+    for highlight in highlights.iter_mut() {
+        highlight.tags = Some(by_highlight.remove(&highlight.id).unwrap_or_default());
+    }
+    Ok(())
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnotationCounts {
+    pub highlight_count: i64,
+    pub note_count: i64,
+}
+
+/// Highlight and note counts for each requested document; documents without
+/// annotations map to zero counts.
+fn fetch_annotation_counts(
+    conn: &Connection,
+    document_ids: &[String],
+) -> Result<std::collections::HashMap<String, AnnotationCounts>, String> {
+    let mut counts: std::collections::HashMap<String, AnnotationCounts> =
+        document_ids.iter().map(|id| (id.clone(), AnnotationCounts::default())).collect();
+
+    // Stay well under SQLite's bound-parameter limit
+    for chunk in document_ids.chunks(900) {
+        let placeholders: Vec<String> = (1..=chunk.len()).map(|i| format!("?{i}")).collect();
+        let sql = format!(
+            "SELECT h.document_id, COUNT(DISTINCT h.id), COUNT(mn.id)
+             FROM highlights h
+             LEFT JOIN margin_notes mn ON mn.highlight_id = h.id
+             WHERE h.document_id IN ({}) AND h.deleted_at IS NULL
+             GROUP BY h.document_id",
+            placeholders.join(",")
+        );
+        let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(chunk), |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    AnnotationCounts { highlight_count: row.get(1)?, note_count: row.get(2)? },
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+        for row in rows {
+            let (document_id, c) = row.map_err(|e| e.to_string())?;
+            counts.insert(document_id, c);
+        }
+    }
+    Ok(counts)
+}
+
+/// A highlight with enough of its document to link back to it.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HighlightFeedItem {
+    pub highlight: Highlight,
+    pub document_title: Option<String>,
+    pub document_path: Option<String>,
+    /// Margin note text on the highlight, oldest first.
+    pub notes: Vec<String>,
+}
+
+/// Library-wide highlights, newest first, for the review feed.
+fn fetch_all_highlights(conn: &Connection, limit: i64, offset: i64) -> Result<Vec<HighlightFeedItem>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT h.id, h.document_id, h.color, h.text_content, h.from_pos, h.to_pos,
+                    h.prefix_context, h.suffix_context, h.created_at, h.updated_at,
+                    d.title AS document_title, d.file_path AS document_path
+             FROM highlights h
+             JOIN documents d ON d.id = h.document_id
+             WHERE h.deleted_at IS NULL
+             ORDER BY h.created_at DESC, h.id
+             LIMIT ?1 OFFSET ?2",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut items = stmt
+        .query_map(rusqlite::params![limit, offset], |row| {
+            Ok(HighlightFeedItem {
+                highlight: Highlight::from_row(row)?,
+                document_title: row.get("document_title")?,
+                document_path: row.get("document_path")?,
+                notes: Vec::new(),
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let ids: Vec<String> = items.iter().map(|i| i.highlight.id.clone()).collect();
+    let mut by_highlight: std::collections::HashMap<String, Vec<String>> = std::collections::HashMap::new();
+    for chunk in ids.chunks(900) {
+        let placeholders: Vec<String> = (1..=chunk.len()).map(|i| format!("?{i}")).collect();
+        let sql = format!(
+            "SELECT highlight_id, content FROM margin_notes
+             WHERE highlight_id IN ({})
+             ORDER BY created_at, id",
+            placeholders.join(",")
+        );
+        let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(chunk), |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+            .map_err(|e| e.to_string())?;
+        for row in rows {
+            let (highlight_id, content) = row.map_err(|e| e.to_string())?;
+            by_highlight.entry(highlight_id).or_default().push(content);
+        }
+    }
+    for item in items.iter_mut() {
+        item.notes = by_highlight.remove(&item.highlight.id).unwrap_or_default();
+    }
+    Ok(items)
+}
+
+/// Returns `false` (and leaves `updated_at` alone) when the color is already `color`.
+fn set_highlight_color(conn: &Connection, id: &str, color: &str, now: i64) -> Result<bool, String> {
+    let changed = conn
+        .execute(
+            "UPDATE highlights SET color = ?1, updated_at = ?2 WHERE id = ?3 AND color != ?1",
+            rusqlite::params![color, now, id],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(changed > 0)
+}
+
+/// Recolors every `from_color` highlight in one document; returns how many changed.
+fn recolor_highlights_inner(
+    conn: &Connection,
+    document_id: &str,
+    from_color: &str,
+    to_color: &str,
+    now: i64,
+) -> Result<usize, String> {
+    if from_color == to_color {
+        return Ok(0);
+    }
     conn.execute(
-        "UPDATE highlights SET color = ?1, updated_at = ?2 WHERE id = ?3",
-        rusqlite::params![color, now, id],
+        "UPDATE highlights SET color = ?1, updated_at = ?2
+         WHERE document_id = ?3 AND color = ?4 AND deleted_at IS NULL",
+        rusqlite::params![to_color, now, document_id, from_color],
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Soft-deletes a highlight. Its notes and tags stay until it is purged, so
+/// `restore_highlight_inner` can bring everything back.
+fn remove_highlight(conn: &Connection, id: &str, now: i64) -> Result<(), String> {
+    conn.execute(
+        "UPDATE highlights SET deleted_at = ?1 WHERE id = ?2 AND deleted_at IS NULL",
+        rusqlite::params![now, id],
     )
     .map_err(|e| e.to_string())?;
     Ok(())
 }
 
-fn remove_highlight(conn: &Connection, id: &str) -> Result<(), String> {
+/// Permanently deletes a highlight; notes and tags cascade.
+fn purge_highlight(conn: &Connection, id: &str) -> Result<(), String> {
     conn.execute("DELETE FROM highlights WHERE id = ?1", rusqlite::params![id])
         .map_err(|e| e.to_string())?;
     Ok(())
 }
 
+/// Clears `deleted_at`. Returns whether the highlight was soft-deleted.
+fn restore_highlight_inner(conn: &Connection, id: &str) -> Result<bool, String> {
+    let changed = conn
+        .execute(
+            "UPDATE highlights SET deleted_at = NULL WHERE id = ?1 AND deleted_at IS NOT NULL",
+            rusqlite::params![id],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(changed > 0)
+}
+
+/// Permanently deletes highlights soft-deleted before `cutoff`.
+fn purge_deleted_highlights_inner(conn: &Connection, cutoff: i64) -> Result<usize, String> {
+    conn.execute(
+        "DELETE FROM highlights WHERE deleted_at IS NOT NULL AND deleted_at < ?1",
+        rusqlite::params![cutoff],
+    )
+    .map_err(|e| e.to_string())
+}
+
 fn insert_margin_note(
     conn: &Connection,
     id: &str,
@@ -107,14 +373,81 @@ fn insert_margin_note(
     Ok(())
 }
 
+/// Inserts a reply to `parent_note_id`, which must be a note on the same highlight.
+fn insert_margin_note_reply(
+    conn: &Connection,
+    id: &str,
+    highlight_id: &str,
+    parent_note_id: &str,
+    content: &str,
+    now: i64,
+) -> Result<(), String> {
+    let parent_highlight: String = match conn.query_row(
+        "SELECT highlight_id FROM margin_notes WHERE id = ?1",
+        [parent_note_id],
+        |row| row.get(0),
+    ) {
+        Ok(h) => h,
+        Err(rusqlite::Error::QueryReturnedNoRows) => return Err(format!("Parent note not found: {}", parent_note_id)),
+        Err(e) => return Err(e.to_string()),
+    };
+    if parent_highlight != highlight_id {
+        return Err(format!("Parent note {} belongs to a different highlight", parent_note_id));
+    }
+
+    conn.execute(
+        "INSERT INTO margin_notes (id, highlight_id, parent_note_id, content, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        rusqlite::params![id, highlight_id, parent_note_id, content, now, now],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Creates a note prefilled with the template for the highlight's color, or empty.
+fn create_note_from_template_inner(conn: &Connection, highlight_id: &str, now: i64) -> Result<MarginNote, String> {
+    let color: String = conn
+        .query_row(
+            "SELECT color FROM highlights WHERE id = ?1",
+            rusqlite::params![highlight_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    let content = note_template_for(conn, &color)?.unwrap_or_default();
+
+    let id = Uuid::new_v4().to_string();
+    insert_margin_note(conn, &id, highlight_id, &content, now)?;
+    Ok(MarginNote {
+        id,
+        highlight_id: highlight_id.to_string(),
+        parent_note_id: None,
+        content,
+        created_at: now,
+        updated_at: now,
+    })
+}
+
+/// Notes for a document in highlight order. Within a highlight each thread is
+/// depth-first: a note comes before its replies, and siblings are ordered by
+/// `created_at`, so the frontend can nest them in a single pass.
 fn fetch_margin_notes(conn: &Connection, document_id: &str) -> Result<Vec<MarginNote>, String> {
     let mut stmt = conn
         .prepare(
-            "SELECT mn.id, mn.highlight_id, mn.content, mn.created_at, mn.updated_at
+            "WITH RECURSIVE thread(id, path) AS (
+                 SELECT mn.id, printf('%020d:%s', mn.created_at, mn.id)
+                 FROM margin_notes mn
+                 JOIN highlights h ON mn.highlight_id = h.id
+                 WHERE h.document_id = ?1 AND h.deleted_at IS NULL AND mn.parent_note_id IS NULL
+                 UNION ALL
+                 SELECT mn.id, thread.path || '/' || printf('%020d:%s', mn.created_at, mn.id)
+                 FROM margin_notes mn
+                 JOIN thread ON mn.parent_note_id = thread.id
+             )
+             SELECT mn.id, mn.highlight_id, mn.parent_note_id, mn.content, mn.created_at, mn.updated_at
              FROM margin_notes mn
+             JOIN thread ON thread.id = mn.id
              JOIN highlights h ON mn.highlight_id = h.id
-             WHERE h.document_id = ?1
-             ORDER BY h.from_pos",
+             ORDER BY h.from_pos, h.id, thread.path",
         )
         .map_err(|e| e.to_string())?;
 
@@ -126,6 +459,40 @@ fn fetch_margin_notes(conn: &Connection, document_id: &str) -> Result<Vec<Margin
     results
 }
 
+/// `None` for soft-deleted highlights too; `restore_highlight` clears
+/// `deleted_at` before fetching, so it still gets the restored one back.
+fn fetch_highlight_with_notes(conn: &Connection, highlight_id: &str) -> Result<Option<HighlightWithNotes>, String> {
+    let highlight = match conn.query_row(
+        "SELECT id, document_id, color, text_content, from_pos, to_pos,
+                prefix_context, suffix_context, created_at, updated_at
+         FROM highlights
+         WHERE id = ?1 AND deleted_at IS NULL",
+        [highlight_id],
+        Highlight::from_row,
+    ) {
+        Ok(h) => h,
+        Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+        Err(e) => return Err(e.to_string()),
+    };
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, highlight_id, parent_note_id, content, created_at, updated_at
+             FROM margin_notes
+             WHERE highlight_id = ?1
+             ORDER BY created_at",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let notes = stmt
+        .query_map([highlight_id], MarginNote::from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(Some(HighlightWithNotes { highlight, notes }))
+}
+
 fn set_margin_note_content(conn: &Connection, id: &str, content: &str, now: i64) -> Result<(), String> {
     conn.execute(
         "UPDATE margin_notes SET content = ?1, updated_at = ?2 WHERE id = ?3",
@@ -157,6 +524,221 @@ fn bulk_update_highlight_positions(conn: &Connection, updates: &[(String, i64, i
     Ok(())
 }
 
+/// Text of `first` extended by whatever of `next` runs past it. Offsets are in
+/// UTF-16 units (see `anchoring`), so the overlap is `first.to_pos -
+/// next.from_pos` units of `next`.
+fn merged_text(first: &Highlight, next: &Highlight) -> String {
+    if next.to_pos <= first.to_pos {
+        return first.text_content.clone();
+    }
+    let overlap = (first.to_pos - next.from_pos).max(0) as usize;
+    let next_units: Vec<u16> = next.text_content.encode_utf16().collect();
+    let mut text = first.text_content.clone();
+    text.push_str(&String::from_utf16_lossy(next_units.get(overlap..).unwrap_or_default()));
+    text
+}
+
+/// Folds `absorbed` into `survivor`: its notes (minus ones with identical
+/// content) and tags move over, then the absorbed row is deleted.
+fn absorb_highlight(conn: &Connection, survivor: &str, absorbed: &str) -> Result<(), String> {
+    // Replies to a duplicate note move to the survivor's copy instead of
+    // cascading away with it.
+    conn.execute(
+        "UPDATE margin_notes
+         SET parent_note_id = (
+             SELECT s.id FROM margin_notes s
+             JOIN margin_notes d ON d.content = s.content
+             WHERE s.highlight_id = ?1 AND d.id = margin_notes.parent_note_id
+             LIMIT 1
+         )
+         WHERE parent_note_id IN (
+             SELECT id FROM margin_notes
+             WHERE highlight_id = ?2
+               AND content IN (SELECT content FROM margin_notes WHERE highlight_id = ?1)
+         )",
+        rusqlite::params![survivor, absorbed],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM margin_notes
+         WHERE highlight_id = ?2
+           AND content IN (SELECT content FROM margin_notes WHERE highlight_id = ?1)",
+        rusqlite::params![survivor, absorbed],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE margin_notes SET highlight_id = ?1 WHERE highlight_id = ?2",
+        rusqlite::params![survivor, absorbed],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT OR IGNORE INTO highlight_tags (highlight_id, tag, created_at)
+         SELECT ?1, tag, created_at FROM highlight_tags WHERE highlight_id = ?2",
+        rusqlite::params![survivor, absorbed],
+    )
+    .map_err(|e| e.to_string())?;
+    purge_highlight(conn, absorbed)
+}
+
+/// Collapses same-color highlights whose ranges touch or overlap into the
+/// leftmost one, spanning all of them and keeping the earliest `created_at`.
+/// Returns how many highlights were merged away.
+fn merge_overlapping_highlights_inner(conn: &Connection, document_id: &str, now: i64) -> Result<usize, String> {
+    let mut highlights = fetch_highlights(conn, document_id)?;
+    highlights.sort_by(|a, b| a.color.cmp(&b.color).then(a.from_pos.cmp(&b.from_pos)).then(b.to_pos.cmp(&a.to_pos)));
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let mut merged = 0;
+    let mut current: Option<(Highlight, bool)> = None;
+
+    let save = |tx: &Connection, (h, changed): &(Highlight, bool)| -> Result<(), String> {
+        if !changed {
+            return Ok(());
+        }
+        tx.execute(
+            "UPDATE highlights
+             SET from_pos = ?1, to_pos = ?2, text_content = ?3, suffix_context = ?4, created_at = ?5, updated_at = ?6
+             WHERE id = ?7",
+            rusqlite::params![h.from_pos, h.to_pos, h.text_content, h.suffix_context, h.created_at, now, h.id],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    };
+
+    for h in highlights {
+        match current.as_mut() {
+            Some((cur, changed)) if cur.color == h.color && h.from_pos <= cur.to_pos => {
+                cur.text_content = merged_text(cur, &h);
+                if h.to_pos > cur.to_pos {
+                    cur.to_pos = h.to_pos;
+                    cur.suffix_context = h.suffix_context.clone();
+                }
+                cur.created_at = cur.created_at.min(h.created_at);
+                *changed = true;
+                absorb_highlight(&tx, &cur.id, &h.id)?;
+                merged += 1;
+            }
+            _ => {
+                if let Some(done) = current.take() {
+                    save(&tx, &done)?;
+                }
+                current = Some((h, false));
+            }
+        }
+    }
+    if let Some(done) = current {
+        save(&tx, &done)?;
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(merged)
+}
+
+/// Deep-copies a document's highlights, their tags and their notes onto
+/// another document, giving every copied row a fresh id. Returns
+/// `(highlights, notes)` copied.
+pub(crate) fn copy_annotations(conn: &Connection, from_document_id: &str, to_document_id: &str) -> Result<(usize, usize), String> {
+    let highlight_ids: Vec<String> = {
+        let mut stmt = conn
+            .prepare("SELECT id FROM highlights WHERE document_id = ?1 AND deleted_at IS NULL ORDER BY from_pos")
+            .map_err(|e| e.to_string())?;
+        let ids = stmt
+            .query_map([from_document_id], |row| row.get(0))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        ids
+    };
+
+    // Old note id -> (new note id, old parent id)
+    let mut new_notes: std::collections::HashMap<String, (String, Option<String>)> = std::collections::HashMap::new();
+    for old_id in &highlight_ids {
+        let new_id = Uuid::new_v4().to_string();
+        conn.execute(
+            "INSERT INTO highlights
+                (id, document_id, color, text_content, from_pos, to_pos,
+                 prefix_context, suffix_context, created_at, updated_at)
+             SELECT ?1, ?2, color, text_content, from_pos, to_pos,
+                    prefix_context, suffix_context, created_at, updated_at
+             FROM highlights WHERE id = ?3",
+            rusqlite::params![new_id, to_document_id, old_id],
+        )
+        .map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO highlight_tags (highlight_id, tag, created_at)
+             SELECT ?1, tag, created_at FROM highlight_tags WHERE highlight_id = ?2",
+            rusqlite::params![new_id, old_id],
+        )
+        .map_err(|e| e.to_string())?;
+
+        let notes: Vec<(String, Option<String>)> = {
+            let mut stmt = conn
+                .prepare("SELECT id, parent_note_id FROM margin_notes WHERE highlight_id = ?1")
+                .map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map([old_id], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            rows
+        };
+        for (old_note_id, old_parent_id) in notes {
+            let new_note_id = Uuid::new_v4().to_string();
+            conn.execute(
+                "INSERT INTO margin_notes (id, highlight_id, content, created_at, updated_at)
+                 SELECT ?1, ?2, content, created_at, updated_at
+                 FROM margin_notes WHERE id = ?3",
+                rusqlite::params![new_note_id, new_id, old_note_id],
+            )
+            .map_err(|e| e.to_string())?;
+            new_notes.insert(old_note_id, (new_note_id, old_parent_id));
+        }
+    }
+
+    // Every note exists by now, so replies link up whatever order their
+    // parents were created or copied in.
+    for (new_note_id, old_parent_id) in new_notes.values() {
+        let Some(new_parent_id) = old_parent_id.as_ref().and_then(|p| new_notes.get(p)).map(|(id, _)| id) else {
+            continue;
+        };
+        conn.execute(
+            "UPDATE margin_notes SET parent_note_id = ?1 WHERE id = ?2",
+            rusqlite::params![new_parent_id, new_note_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    Ok((highlight_ids.len(), new_notes.len()))
+}
+
+/// Notes whose highlight is gone. FK cascades prevent this in normal use, but
+/// backfilled or hand-edited databases can still contain them.
+fn fetch_orphaned_notes(conn: &Connection) -> Result<Vec<MarginNote>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, highlight_id, parent_note_id, content, created_at, updated_at
+             FROM margin_notes
+             WHERE highlight_id NOT IN (SELECT id FROM highlights)
+             ORDER BY created_at",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let results = stmt
+        .query_map([], MarginNote::from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string());
+    results
+}
+
+fn remove_orphaned_notes(conn: &Connection) -> Result<usize, String> {
+    conn.execute(
+        "DELETE FROM margin_notes WHERE highlight_id NOT IN (SELECT id FROM highlights)",
+        [],
+    )
+    .map_err(|e| e.to_string())
+}
+
 fn remove_all_highlights_for_document(conn: &Connection, document_id: &str) -> Result<usize, String> {
     conn.execute(
         "DELETE FROM highlights WHERE document_id = ?1",
@@ -165,6 +747,216 @@ fn remove_all_highlights_for_document(conn: &Connection, document_id: &str) -> R
     .map_err(|e| e.to_string())
 }
 
+/// A highlight as found in an exported JSON file. Positions are only a hint;
+/// the text and its context decide where it lands.
+#[derive(serde::Deserialize)]
+struct ImportedHighlight {
+    text_content: String,
+    /// Falls back to the configured default highlight color.
+    #[serde(default)]
+    color: Option<String>,
+    #[serde(default)]
+    from_pos: i64,
+    #[serde(default)]
+    prefix_context: Option<String>,
+    #[serde(default)]
+    suffix_context: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HighlightImportResult {
+    pub anchored: usize,
+    pub unmatched: usize,
+}
+
+fn import_highlights_inner(
+    conn: &Connection,
+    document_id: &str,
+    content: &str,
+    imported: &[ImportedHighlight],
+) -> Result<HighlightImportResult, String> {
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let now = now_millis();
+    let mut anchored = 0;
+    let mut unmatched = 0;
+
+    for h in imported {
+        let anchor = TextAnchor {
+            text: &h.text_content,
+            prefix: h.prefix_context.as_deref().unwrap_or(""),
+            suffix: h.suffix_context.as_deref().unwrap_or(""),
+            from: h.from_pos.max(0) as usize,
+        };
+        let found = resolve_anchor(content, &anchor);
+        if found.confidence == AnchorConfidence::Orphaned {
+            unmatched += 1;
+            continue;
+        }
+
+        insert_highlight(
+            &tx,
+            &Uuid::new_v4().to_string(),
+            document_id,
+            &resolve_highlight_color(&tx, h.color.clone()),
+            &h.text_content,
+            found.from as i64,
+            found.to as i64,
+            h.prefix_context.as_deref(),
+            h.suffix_context.as_deref(),
+            now,
+        )?;
+        anchored += 1;
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(HighlightImportResult { anchored, unmatched })
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReanchorResult {
+    pub relocated: usize,
+    pub unchanged: usize,
+    pub orphaned: usize,
+    /// Highlights whose text no longer appears; left at their old offsets.
+    pub orphaned_ids: Vec<String>,
+}
+
+/// Moves each of a document's highlights to where its text (and context) now
+/// sits in `new_content`. Highlights that can't be found are reported, not deleted.
+fn reanchor_highlights_inner(conn: &Connection, document_id: &str, new_content: &str) -> Result<ReanchorResult, String> {
+    let highlights = fetch_highlights(conn, document_id)?;
+    let mut moves = Vec::new();
+    let mut result = ReanchorResult { relocated: 0, unchanged: 0, orphaned: 0, orphaned_ids: Vec::new() };
+
+    for h in &highlights {
+        let anchor = TextAnchor {
+            text: &h.text_content,
+            prefix: h.prefix_context.as_deref().unwrap_or(""),
+            suffix: h.suffix_context.as_deref().unwrap_or(""),
+            from: h.from_pos.max(0) as usize,
+        };
+        let found = resolve_anchor(new_content, &anchor);
+        if found.confidence == AnchorConfidence::Orphaned {
+            result.orphaned += 1;
+            result.orphaned_ids.push(h.id.clone());
+        } else if found.from as i64 == h.from_pos && found.to as i64 == h.to_pos {
+            result.unchanged += 1;
+        } else {
+            moves.push((h.id.clone(), found.from as i64, found.to as i64));
+            result.relocated += 1;
+        }
+    }
+
+    bulk_update_highlight_positions(conn, &moves)?;
+    Ok(result)
+}
+
+/// Prefixes every line of `text` with `prefix`, keeping blank lines inside the callout.
+fn callout_lines(out: &mut String, prefix: &str, text: &str) {
+    for line in text.lines() {
+        if line.is_empty() {
+            out.push_str(prefix.trim_end());
+        } else {
+            out.push_str(prefix);
+            out.push_str(line);
+        }
+        out.push('\n');
+    }
+}
+
+/// Renders highlights as Obsidian `[!quote]` callouts with their notes nested
+/// as `[!note]` callouts. Expects `highlights` and `notes` in position order.
+fn render_obsidian_annotations(
+    title: &str,
+    source_link: Option<&str>,
+    highlights: &[Highlight],
+    notes: &[MarginNote],
+) -> String {
+    let mut out = format!("# Annotations: {title}\n\n");
+    if let Some(link) = source_link {
+        out.push_str(&format!("Source: {link}\n\n"));
+    }
+    if highlights.is_empty() {
+        out.push_str("_No annotations to export._\n");
+        return out;
+    }
+
+    for (i, h) in highlights.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        out.push_str(&format!("> [!quote] Highlight ({})\n", h.color));
+        callout_lines(&mut out, "> ", &h.text_content);
+        for note in notes.iter().filter(|n| n.highlight_id == h.id) {
+            out.push_str(">\n> > [!note]\n");
+            callout_lines(&mut out, "> > ", &note.content);
+        }
+    }
+    out
+}
+
+/// Title (defaulting to "Untitled"), file path, and URL for export headers.
+fn document_export_info(conn: &Connection, document_id: &str) -> Result<(String, Option<String>, Option<String>), String> {
+    let (title, file_path, url): (Option<String>, Option<String>, Option<String>) = conn
+        .query_row(
+            "SELECT title, file_path, url FROM documents WHERE id = ?1",
+            [document_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|e| e.to_string())?;
+    Ok((title.unwrap_or_else(|| "Untitled".to_string()), file_path, url))
+}
+
+/// Plain markdown in the same shape as the frontend's annotation export:
+/// quoted highlight text followed by `**Note:**` lines.
+fn render_markdown_annotations(heading: &str, highlights: &[Highlight], notes: &[MarginNote]) -> String {
+    let mut out = format!("# {heading}\n\n");
+    if highlights.is_empty() {
+        out.push_str("_No annotations to export._\n");
+        return out;
+    }
+    out.push_str(&format!("_Exported from Margin — {} annotations_\n", highlights.len()));
+
+    for h in highlights {
+        out.push_str("\n---\n\n");
+        callout_lines(&mut out, "> ", &h.text_content);
+        let mut notes = notes.iter().filter(|n| n.highlight_id == h.id).peekable();
+        if notes.peek().is_some() {
+            out.push('\n');
+        }
+        for note in notes {
+            out.push_str(&format!("**Note:** {}\n", note.content));
+        }
+    }
+    out
+}
+
+fn build_color_export(conn: &Connection, document_id: &str, color: &str) -> Result<(String, usize), String> {
+    let (title, _, _) = document_export_info(conn, document_id)?;
+    let highlights = fetch_highlights_by_color(conn, document_id, color)?;
+    let notes = fetch_margin_notes(conn, document_id)?;
+    let heading = format!("Annotations: \"{title}\" — {color} highlights");
+    Ok((render_markdown_annotations(&heading, &highlights, &notes), highlights.len()))
+}
+
+fn build_obsidian_export(conn: &Connection, document_id: &str) -> Result<(String, usize), String> {
+    let (title, file_path, url) = document_export_info(conn, document_id)?;
+
+    // Angle brackets keep paths with spaces intact as Obsidian link targets
+    let source_link = match (file_path, url) {
+        (Some(path), _) => Some(format!("[{title}](<file://{path}>)")),
+        (None, Some(url)) => Some(format!("[{title}](<{url}>)")),
+        (None, None) => None,
+    };
+
+    let highlights = fetch_highlights(conn, document_id)?;
+    let notes = fetch_margin_notes(conn, document_id)?;
+    let markdown = render_obsidian_annotations(&title, source_link.as_deref(), &highlights, &notes);
+    Ok((markdown, highlights.len()))
+}
+
 // === Tauri command handlers ===
 
 #[allow(clippy::too_many_arguments)]
@@ -172,7 +964,7 @@ fn remove_all_highlights_for_document(conn: &Connection, document_id: &str) -> R
 pub async fn create_highlight(
     state: tauri::State<'_, DbPool>,
     document_id: String,
-    color: String,
+    color: Option<String>,
     text_content: String,
     from_pos: i64,
     to_pos: i64,
@@ -180,6 +972,7 @@ pub async fn create_highlight(
     suffix_context: Option<String>,
 ) -> Result<Highlight, String> {
     let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    let color = resolve_highlight_color(&conn, color);
     let id = Uuid::new_v4().to_string();
     let now = now_millis();
 
@@ -203,37 +996,129 @@ pub async fn create_highlight(
         suffix_context,
         created_at: now,
         updated_at: now,
+        tags: None,
     })
 }
 
 #[tauri::command]
-pub async fn get_highlights(state: tauri::State<'_, DbPool>, document_id: String) -> Result<Vec<Highlight>, String> {
+pub async fn get_highlights(
+    state: tauri::State<'_, DbPool>,
+    document_id: String,
+    include_tags: Option<bool>,
+) -> Result<Vec<Highlight>, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    let mut highlights = fetch_highlights(&conn, &document_id)?;
+    if include_tags.unwrap_or(false) {
+        attach_highlight_tags(&conn, &document_id, &mut highlights)?;
+    }
+    Ok(highlights)
+}
+
+#[tauri::command]
+pub async fn add_highlight_tag(state: tauri::State<'_, DbPool>, highlight_id: String, tag: String) -> Result<bool, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    let doc_id = document_id_for_highlight(&conn, &highlight_id)?;
+    let added = add_highlight_tag_inner(&conn, &highlight_id, &tag, now_millis())?;
+    if added {
+        touch_document(&conn, &doc_id)?;
+    }
+    Ok(added)
+}
+
+#[tauri::command]
+pub async fn remove_highlight_tag(state: tauri::State<'_, DbPool>, highlight_id: String, tag: String) -> Result<bool, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    remove_highlight_tag_inner(&conn, &highlight_id, &tag)
+}
+
+#[tauri::command]
+pub async fn get_highlights_by_tag(
+    state: tauri::State<'_, DbPool>,
+    document_id: String,
+    tag: String,
+) -> Result<Vec<Highlight>, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    fetch_highlights_by_tag(&conn, &document_id, &tag)
+}
+
+#[tauri::command]
+pub async fn get_all_highlights(
+    state: tauri::State<'_, DbPool>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Vec<HighlightFeedItem>, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    fetch_all_highlights(&conn, limit.unwrap_or(50), offset.unwrap_or(0))
+}
+
+#[tauri::command]
+pub async fn get_document_annotation_counts(
+    state: tauri::State<'_, DbPool>,
+    document_ids: Vec<String>,
+) -> Result<std::collections::HashMap<String, AnnotationCounts>, String> {
     let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
-    fetch_highlights(&conn, &document_id)
+    fetch_annotation_counts(&conn, &document_ids)
 }
 
 #[tauri::command]
-pub async fn update_highlight_color(state: tauri::State<'_, DbPool>, id: String, color: String) -> Result<(), String> {
+pub async fn update_highlight_color(state: tauri::State<'_, DbPool>, id: String, color: String) -> Result<bool, String> {
     let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
     let now = now_millis();
 
-    set_highlight_color(&conn, &id, &color, now)?;
+    let doc_id = document_id_for_highlight(&conn, &id)?;
+    if !set_highlight_color(&conn, &id, &color, now)? {
+        return Ok(false);
+    }
+    touch_document(&conn, &doc_id)?;
+
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn recolor_highlights(
+    state: tauri::State<'_, DbPool>,
+    document_id: String,
+    from_color: String,
+    to_color: String,
+) -> Result<usize, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    let changed = recolor_highlights_inner(&conn, &document_id, &from_color, &to_color, now_millis())?;
+    if changed > 0 {
+        touch_document(&conn, &document_id)?;
+    }
+    Ok(changed)
+}
+
+#[tauri::command]
+pub async fn delete_highlight(state: tauri::State<'_, DbPool>, id: String) -> Result<(), String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
 
     let doc_id = document_id_for_highlight(&conn, &id)?;
+    remove_highlight(&conn, &id, now_millis())?;
     touch_document(&conn, &doc_id)?;
 
     Ok(())
 }
 
+/// Undoes `delete_highlight`, returning the highlight with its notes.
 #[tauri::command]
-pub async fn delete_highlight(state: tauri::State<'_, DbPool>, id: String) -> Result<(), String> {
+pub async fn restore_highlight(state: tauri::State<'_, DbPool>, id: String) -> Result<HighlightWithNotes, String> {
     let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
 
+    if !restore_highlight_inner(&conn, &id)? {
+        return Err(format!("Highlight is not deleted: {}", id));
+    }
     let doc_id = document_id_for_highlight(&conn, &id)?;
-    remove_highlight(&conn, &id)?;
     touch_document(&conn, &doc_id)?;
 
-    Ok(())
+    fetch_highlight_with_notes(&conn, &id)?.ok_or_else(|| format!("Highlight not found: {}", id))
+}
+
+/// Permanently removes highlights deleted more than `older_than_ms` ago.
+#[tauri::command]
+pub async fn purge_deleted_highlights(state: tauri::State<'_, DbPool>, older_than_ms: i64) -> Result<usize, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    purge_deleted_highlights_inner(&conn, now_millis() - older_than_ms)
 }
 
 #[tauri::command]
@@ -241,12 +1126,16 @@ pub async fn create_margin_note(
     state: tauri::State<'_, DbPool>,
     highlight_id: String,
     content: String,
+    parent_note_id: Option<String>,
 ) -> Result<MarginNote, String> {
     let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
     let id = Uuid::new_v4().to_string();
     let now = now_millis();
 
-    insert_margin_note(&conn, &id, &highlight_id, &content, now)?;
+    match &parent_note_id {
+        Some(parent) => insert_margin_note_reply(&conn, &id, &highlight_id, parent, &content, now)?,
+        None => insert_margin_note(&conn, &id, &highlight_id, &content, now)?,
+    }
 
     let doc_id = document_id_for_highlight(&conn, &highlight_id)?;
     touch_document(&conn, &doc_id)?;
@@ -254,18 +1143,42 @@ pub async fn create_margin_note(
     Ok(MarginNote {
         id,
         highlight_id,
+        parent_note_id,
         content,
         created_at: now,
         updated_at: now,
     })
 }
 
+#[tauri::command]
+pub async fn create_margin_note_from_template(
+    state: tauri::State<'_, DbPool>,
+    highlight_id: String,
+) -> Result<MarginNote, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    let note = create_note_from_template_inner(&conn, &highlight_id, now_millis())?;
+
+    let doc_id = document_id_for_highlight(&conn, &highlight_id)?;
+    touch_document(&conn, &doc_id)?;
+
+    Ok(note)
+}
+
 #[tauri::command]
 pub async fn get_margin_notes(state: tauri::State<'_, DbPool>, document_id: String) -> Result<Vec<MarginNote>, String> {
     let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
     fetch_margin_notes(&conn, &document_id)
 }
 
+#[tauri::command]
+pub async fn get_highlight_with_notes(
+    state: tauri::State<'_, DbPool>,
+    highlight_id: String,
+) -> Result<Option<HighlightWithNotes>, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    fetch_highlight_with_notes(&conn, &highlight_id)
+}
+
 #[tauri::command]
 pub async fn update_margin_note(state: tauri::State<'_, DbPool>, id: String, content: String) -> Result<(), String> {
     let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
@@ -298,6 +1211,110 @@ pub async fn update_highlight_positions(
     bulk_update_highlight_positions(&conn, &updates)
 }
 
+#[tauri::command]
+pub async fn merge_overlapping_highlights(state: tauri::State<'_, DbPool>, document_id: String) -> Result<usize, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    let merged = merge_overlapping_highlights_inner(&conn, &document_id, now_millis())?;
+    if merged > 0 {
+        touch_document(&conn, &document_id)?;
+    }
+    Ok(merged)
+}
+
+/// Re-anchors a document's highlights after its text changed outside the editor.
+#[tauri::command]
+pub async fn reanchor_highlights(
+    state: tauri::State<'_, DbPool>,
+    document_id: String,
+    new_content: String,
+) -> Result<ReanchorResult, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    reanchor_highlights_inner(&conn, &document_id, &new_content)
+}
+
+/// Imports highlights exported as JSON, re-anchoring each against `content`
+/// (the editor's current text) or, when omitted, the document's file on disk.
+#[tauri::command]
+pub async fn import_highlights_json(
+    state: tauri::State<'_, DbPool>,
+    document_id: String,
+    path: String,
+    content: Option<String>,
+) -> Result<HighlightImportResult, String> {
+    let json = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read file '{}': {}", path, e))?;
+    let imported: Vec<ImportedHighlight> =
+        serde_json::from_str(&json).map_err(|e| format!("Invalid highlights JSON: {e}"))?;
+
+    let content = match content {
+        Some(c) => c,
+        None => {
+            let file_path: Option<String> = {
+                let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+                conn.query_row(
+                    "SELECT file_path FROM documents WHERE id = ?1",
+                    [&document_id],
+                    |row| row.get(0),
+                )
+                .map_err(|e| e.to_string())?
+            };
+            let file_path = file_path.ok_or("Document has no file on disk; pass its content")?;
+            std::fs::read_to_string(&file_path)
+                .map_err(|e| format!("Failed to read file '{}': {}", file_path, e))?
+        }
+    };
+
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    let result = import_highlights_inner(&conn, &document_id, &content, &imported)?;
+    if result.anchored > 0 {
+        touch_document(&conn, &document_id)?;
+    }
+    Ok(result)
+}
+
+/// Writes only the highlights of `color` (with their notes) as markdown; returns the highlight count.
+#[tauri::command]
+pub async fn export_annotations_by_color(
+    state: tauri::State<'_, DbPool>,
+    document_id: String,
+    color: String,
+    path: String,
+) -> Result<usize, String> {
+    let (markdown, count) = {
+        let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+        build_color_export(&conn, &document_id, &color)?
+    };
+    std::fs::write(&path, markdown).map_err(|e| format!("Failed to write file '{}': {}", path, e))?;
+    Ok(count)
+}
+
+/// Writes the document's annotations as Obsidian callouts; returns the highlight count.
+#[tauri::command]
+pub async fn export_annotations_obsidian(
+    state: tauri::State<'_, DbPool>,
+    document_id: String,
+    path: String,
+) -> Result<usize, String> {
+    let (markdown, count) = {
+        let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+        build_obsidian_export(&conn, &document_id)?
+    };
+    std::fs::write(&path, markdown).map_err(|e| format!("Failed to write file '{}': {}", path, e))?;
+    Ok(count)
+}
+
+#[tauri::command]
+pub async fn find_orphaned_notes(state: tauri::State<'_, DbPool>) -> Result<Vec<MarginNote>, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    fetch_orphaned_notes(&conn)
+}
+
+/// Returns the number of notes deleted.
+#[tauri::command]
+pub async fn prune_orphaned_notes(state: tauri::State<'_, DbPool>) -> Result<usize, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    remove_orphaned_notes(&conn)
+}
+
 #[tauri::command]
 pub async fn delete_all_highlights_for_document(
     state: tauri::State<'_, DbPool>,
@@ -337,7 +1354,8 @@ mod tests {
              prefix_context TEXT,
              suffix_context TEXT,
              created_at INTEGER NOT NULL,
-             updated_at INTEGER NOT NULL
+             updated_at INTEGER NOT NULL,
+             deleted_at INTEGER
          );
          CREATE INDEX idx_highlights_document ON highlights(document_id);
          CREATE TABLE margin_notes (
@@ -345,9 +1363,16 @@ mod tests {
              highlight_id TEXT NOT NULL REFERENCES highlights(id) ON DELETE CASCADE,
              content TEXT NOT NULL,
              created_at INTEGER NOT NULL,
-             updated_at INTEGER NOT NULL
+             updated_at INTEGER NOT NULL,
+             parent_note_id TEXT REFERENCES margin_notes(id) ON DELETE CASCADE
          );
-         CREATE INDEX idx_margin_notes_highlight ON margin_notes(highlight_id);"
+         CREATE INDEX idx_margin_notes_highlight ON margin_notes(highlight_id);
+         CREATE TABLE highlight_tags (
+             highlight_id TEXT NOT NULL REFERENCES highlights(id) ON DELETE CASCADE,
+             tag TEXT NOT NULL,
+             created_at INTEGER NOT NULL,
+             PRIMARY KEY (highlight_id, tag)
+         );"
     }
 
     fn setup_db() -> Connection {
@@ -366,7 +1391,7 @@ mod tests {
     }
 
     fn highlight_count(conn: &Connection) -> i64 {
-        conn.query_row("SELECT COUNT(*) FROM highlights", [], |r| r.get(0)).unwrap()
+        conn.query_row("SELECT COUNT(*) FROM highlights WHERE deleted_at IS NULL", [], |r| r.get(0)).unwrap()
     }
 
     fn note_count(conn: &Connection) -> i64 {
@@ -426,7 +1451,7 @@ mod tests {
         insert_doc(&conn, "doc1");
         insert_highlight(&conn, "h1", "doc1", "yellow", "text", 0, 4, None, None, 1000).unwrap();
 
-        set_highlight_color(&conn, "h1", "green", 2000).unwrap();
+        assert!(set_highlight_color(&conn, "h1", "green", 2000).unwrap());
 
         let highlights = fetch_highlights(&conn, "doc1").unwrap();
         assert_eq!(highlights[0].color, "green");
@@ -434,6 +1459,30 @@ mod tests {
         assert_eq!(highlights[0].created_at, 1000); // unchanged
     }
 
+    #[test]
+    fn update_highlight_color_to_same_color_is_noop() {
+        let conn = setup_db();
+        insert_doc(&conn, "doc1");
+        insert_highlight(&conn, "h1", "doc1", "yellow", "text", 0, 4, None, None, 1000).unwrap();
+
+        assert!(!set_highlight_color(&conn, "h1", "yellow", 2000).unwrap());
+
+        let highlights = fetch_highlights(&conn, "doc1").unwrap();
+        assert_eq!(highlights[0].color, "yellow");
+        assert_eq!(highlights[0].updated_at, 1000);
+    }
+
+    #[test]
+    fn highlight_color_falls_back_to_configured_default() {
+        let conn = setup_db();
+        crate::db::migrations::migrate_add_settings_table(&conn).unwrap();
+        assert_eq!(resolve_highlight_color(&conn, None), "yellow");
+
+        crate::commands::settings::set_setting(&conn, "default_highlight_color", "green").unwrap();
+        assert_eq!(resolve_highlight_color(&conn, None), "green");
+        assert_eq!(resolve_highlight_color(&conn, Some("pink".to_string())), "pink");
+    }
+
     #[test]
     fn delete_highlight_removes_it() {
         let conn = setup_db();
@@ -441,8 +1490,85 @@ mod tests {
         insert_highlight(&conn, "h1", "doc1", "yellow", "text", 0, 4, None, None, 1000).unwrap();
         assert_eq!(highlight_count(&conn), 1);
 
-        remove_highlight(&conn, "h1").unwrap();
+        remove_highlight(&conn, "h1", 2000).unwrap();
         assert_eq!(highlight_count(&conn), 0);
+        assert!(fetch_highlights(&conn, "doc1").unwrap().is_empty());
+    }
+
+    #[test]
+    fn restore_highlight_brings_back_notes() {
+        let conn = setup_db();
+        insert_doc(&conn, "doc1");
+        insert_highlight(&conn, "h1", "doc1", "yellow", "text", 0, 4, None, None, 1000).unwrap();
+        insert_margin_note(&conn, "n1", "h1", "my note", 1000).unwrap();
+
+        remove_highlight(&conn, "h1", 2000).unwrap();
+        assert!(fetch_margin_notes(&conn, "doc1").unwrap().is_empty());
+
+        assert!(restore_highlight_inner(&conn, "h1").unwrap());
+        assert!(!restore_highlight_inner(&conn, "h1").unwrap());
+        assert_eq!(fetch_highlights(&conn, "doc1").unwrap().len(), 1);
+        assert_eq!(fetch_margin_notes(&conn, "doc1").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn purge_deleted_highlights_respects_cutoff() {
+        let conn = setup_db();
+        insert_doc(&conn, "doc1");
+        insert_highlight(&conn, "h1", "doc1", "yellow", "old", 0, 3, None, None, 1000).unwrap();
+        insert_highlight(&conn, "h2", "doc1", "yellow", "new", 5, 8, None, None, 1000).unwrap();
+        insert_highlight(&conn, "h3", "doc1", "yellow", "live", 10, 14, None, None, 1000).unwrap();
+        insert_margin_note(&conn, "n1", "h1", "note", 1000).unwrap();
+        remove_highlight(&conn, "h1", 2000).unwrap();
+        remove_highlight(&conn, "h2", 5000).unwrap();
+
+        assert_eq!(purge_deleted_highlights_inner(&conn, 3000).unwrap(), 1);
+        let remaining: i64 = conn.query_row("SELECT COUNT(*) FROM highlights", [], |r| r.get(0)).unwrap();
+        assert_eq!(remaining, 2);
+        assert_eq!(note_count(&conn), 0); // cascade on purge
+        assert!(!restore_highlight_inner(&conn, "h1").unwrap());
+        assert!(restore_highlight_inner(&conn, "h2").unwrap());
+    }
+
+    #[test]
+    fn highlight_tags_add_remove_and_filter() {
+        let conn = setup_db();
+        insert_doc(&conn, "doc1");
+        insert_doc(&conn, "doc2");
+        insert_highlight(&conn, "h1", "doc1", "yellow", "first", 0, 5, None, None, 1000).unwrap();
+        insert_highlight(&conn, "h2", "doc1", "yellow", "second", 10, 16, None, None, 1000).unwrap();
+        insert_highlight(&conn, "h3", "doc2", "yellow", "other", 0, 5, None, None, 1000).unwrap();
+
+        assert!(add_highlight_tag_inner(&conn, "h1", " Quote ", 1000).unwrap());
+        assert!(!add_highlight_tag_inner(&conn, "h1", "quote", 1000).unwrap());
+        assert!(add_highlight_tag_inner(&conn, "h1", "todo", 1000).unwrap());
+        assert!(add_highlight_tag_inner(&conn, "h3", "quote", 1000).unwrap());
+        assert!(add_highlight_tag_inner(&conn, "h1", "  ", 1000).is_err());
+        assert!(add_highlight_tag_inner(&conn, "missing", "quote", 1000).is_err());
+
+        let quoted = fetch_highlights_by_tag(&conn, "doc1", "QUOTE").unwrap();
+        assert_eq!(quoted.len(), 1);
+        assert_eq!(quoted[0].id, "h1");
+
+        let mut highlights = fetch_highlights(&conn, "doc1").unwrap();
+        attach_highlight_tags(&conn, "doc1", &mut highlights).unwrap();
+        assert_eq!(highlights[0].tags, Some(vec!["quote".to_string(), "todo".to_string()]));
+        assert_eq!(highlights[1].tags, Some(vec![]));
+
+        assert!(remove_highlight_tag_inner(&conn, "h1", "todo").unwrap());
+        assert!(!remove_highlight_tag_inner(&conn, "h1", "todo").unwrap());
+    }
+
+    #[test]
+    fn deleting_highlight_cascades_to_its_tags() {
+        let conn = setup_db();
+        insert_doc(&conn, "doc1");
+        insert_highlight(&conn, "h1", "doc1", "yellow", "text", 0, 4, None, None, 1000).unwrap();
+        add_highlight_tag_inner(&conn, "h1", "quote", 1000).unwrap();
+
+        purge_highlight(&conn, "h1").unwrap();
+        let tags: i64 = conn.query_row("SELECT COUNT(*) FROM highlight_tags", [], |r| r.get(0)).unwrap();
+        assert_eq!(tags, 0);
     }
 
     #[test]
@@ -453,7 +1579,10 @@ mod tests {
         insert_margin_note(&conn, "n1", "h1", "my note", 1000).unwrap();
         assert_eq!(note_count(&conn), 1);
 
-        remove_highlight(&conn, "h1").unwrap();
+        remove_highlight(&conn, "h1", 2000).unwrap();
+        assert_eq!(note_count(&conn), 1); // kept for restore
+
+        purge_highlight(&conn, "h1").unwrap();
         assert_eq!(note_count(&conn), 0); // cascade
     }
 
@@ -528,6 +1657,41 @@ mod tests {
         assert_eq!(note_count(&conn), 0);
     }
 
+    #[test]
+    fn replies_follow_their_parent_and_cascade_on_delete() {
+        let conn = setup_db();
+        insert_doc(&conn, "doc1");
+        insert_highlight(&conn, "h1", "doc1", "yellow", "text", 0, 4, None, None, 1000).unwrap();
+        insert_margin_note(&conn, "n1", "h1", "first", 1000).unwrap();
+        insert_margin_note(&conn, "n2", "h1", "second", 1001).unwrap();
+        insert_margin_note_reply(&conn, "r2", "h1", "n1", "later reply", 1003).unwrap();
+        insert_margin_note_reply(&conn, "r1", "h1", "n1", "reply", 1002).unwrap();
+        insert_margin_note_reply(&conn, "r1a", "h1", "r1", "nested", 1004).unwrap();
+
+        let notes = fetch_margin_notes(&conn, "doc1").unwrap();
+        let ids: Vec<&str> = notes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["n1", "r1", "r1a", "r2", "n2"]);
+        assert_eq!(notes[1].parent_note_id.as_deref(), Some("n1"));
+        assert_eq!(notes[0].parent_note_id, None);
+
+        remove_margin_note(&conn, "n1").unwrap();
+        let ids: Vec<String> = fetch_margin_notes(&conn, "doc1").unwrap().into_iter().map(|n| n.id).collect();
+        assert_eq!(ids, vec!["n2"]);
+    }
+
+    #[test]
+    fn reply_parent_must_be_on_same_highlight() {
+        let conn = setup_db();
+        insert_doc(&conn, "doc1");
+        insert_highlight(&conn, "h1", "doc1", "yellow", "one", 0, 3, None, None, 1000).unwrap();
+        insert_highlight(&conn, "h2", "doc1", "yellow", "two", 5, 8, None, None, 1000).unwrap();
+        insert_margin_note(&conn, "n1", "h1", "note", 1000).unwrap();
+
+        assert!(insert_margin_note_reply(&conn, "r1", "h2", "n1", "reply", 1001).is_err());
+        assert!(insert_margin_note_reply(&conn, "r1", "h1", "missing", "reply", 1001).is_err());
+        assert_eq!(note_count(&conn), 1);
+    }
+
     #[test]
     fn document_id_for_highlight_returns_correct_doc() {
         let conn = setup_db();
@@ -625,5 +1789,388 @@ mod tests {
         let deleted = remove_all_highlights_for_document(&conn, "doc1").unwrap();
         assert_eq!(deleted, 0);
     }
-}
 
+    // === Highlight with notes tests ===
+
+    #[test]
+    fn highlight_with_notes_returns_nested_notes() {
+        let conn = setup_db();
+        insert_doc(&conn, "doc1");
+        insert_highlight(&conn, "h1", "doc1", "yellow", "noted", 0, 5, None, None, 1000).unwrap();
+        insert_highlight(&conn, "h2", "doc1", "green", "bare", 10, 14, None, None, 1000).unwrap();
+        insert_margin_note(&conn, "n2", "h1", "second", 1002).unwrap();
+        insert_margin_note(&conn, "n1", "h1", "first", 1001).unwrap();
+
+        let noted = fetch_highlight_with_notes(&conn, "h1").unwrap().unwrap();
+        assert_eq!(noted.highlight.text_content, "noted");
+        assert_eq!(noted.notes.len(), 2);
+        assert_eq!(noted.notes[0].content, "first");
+        assert_eq!(noted.notes[1].content, "second");
+
+        let bare = fetch_highlight_with_notes(&conn, "h2").unwrap().unwrap();
+        assert_eq!(bare.highlight.color, "green");
+        assert!(bare.notes.is_empty());
+    }
+
+    #[test]
+    fn highlight_with_notes_missing_returns_none() {
+        let conn = setup_db();
+        assert!(fetch_highlight_with_notes(&conn, "nope").unwrap().is_none());
+    }
+
+    #[test]
+    fn highlight_with_notes_hides_soft_deleted_until_restored() {
+        let conn = setup_db();
+        insert_doc(&conn, "doc1");
+        insert_highlight(&conn, "h1", "doc1", "yellow", "gone", 0, 4, None, None, 1000).unwrap();
+        insert_margin_note(&conn, "n1", "h1", "kept", 1001).unwrap();
+
+        remove_highlight(&conn, "h1", 2000).unwrap();
+        assert!(fetch_highlight_with_notes(&conn, "h1").unwrap().is_none());
+
+        assert!(restore_highlight_inner(&conn, "h1").unwrap());
+        let restored = fetch_highlight_with_notes(&conn, "h1").unwrap().unwrap();
+        assert_eq!(restored.notes.len(), 1);
+    }
+
+    // === Obsidian export tests ===
+
+    #[test]
+    fn obsidian_export_uses_quote_and_nested_note_callouts() {
+        let conn = setup_db();
+        insert_doc(&conn, "doc1");
+        conn.execute("UPDATE documents SET file_path = '/notes/My Doc.md' WHERE id = 'doc1'", []).unwrap();
+        insert_highlight(&conn, "h2", "doc1", "green", "later line", 20, 30, None, None, 1000).unwrap();
+        insert_highlight(&conn, "h1", "doc1", "yellow", "first line\nsecond line", 0, 10, None, None, 1000).unwrap();
+        insert_margin_note(&conn, "n1", "h1", "tighten this", 1001).unwrap();
+
+        let (md, count) = build_obsidian_export(&conn, "doc1").unwrap();
+        assert_eq!(count, 2);
+        assert!(md.starts_with("# Annotations: Test Doc\n"));
+        assert!(md.contains("Source: [Test Doc](<file:///notes/My Doc.md>)"));
+        assert!(md.contains(
+            "> [!quote] Highlight (yellow)\n> first line\n> second line\n>\n> > [!note]\n> > tighten this\n"
+        ));
+        assert!(md.contains("> [!quote] Highlight (green)\n> later line\n"));
+        // Highlights follow document order, not insertion order
+        assert!(md.find("(yellow)").unwrap() < md.find("(green)").unwrap());
+    }
+
+    #[test]
+    fn obsidian_export_without_highlights_says_so() {
+        let conn = setup_db();
+        insert_doc(&conn, "doc1");
+        let (md, count) = build_obsidian_export(&conn, "doc1").unwrap();
+        assert_eq!(count, 0);
+        assert!(md.contains("_No annotations to export._"));
+    }
+
+    // === Recolor tests ===
+
+    #[test]
+    fn recolor_only_touches_matching_color_in_document() {
+        let conn = setup_db();
+        insert_doc(&conn, "doc1");
+        insert_doc(&conn, "doc2");
+        insert_highlight(&conn, "h1", "doc1", "yellow", "a", 0, 1, None, None, 1000).unwrap();
+        insert_highlight(&conn, "h2", "doc1", "yellow", "b", 2, 3, None, None, 1000).unwrap();
+        insert_highlight(&conn, "h3", "doc1", "blue", "c", 4, 5, None, None, 1000).unwrap();
+        insert_highlight(&conn, "h4", "doc2", "yellow", "d", 0, 1, None, None, 1000).unwrap();
+
+        assert_eq!(recolor_highlights_inner(&conn, "doc1", "yellow", "green", 2000).unwrap(), 2);
+
+        let colors = |doc: &str| -> Vec<(String, String, i64)> {
+            fetch_highlights(&conn, doc).unwrap().into_iter().map(|h| (h.id, h.color, h.updated_at)).collect()
+        };
+        assert_eq!(
+            colors("doc1"),
+            vec![
+                ("h1".to_string(), "green".to_string(), 2000),
+                ("h2".to_string(), "green".to_string(), 2000),
+                ("h3".to_string(), "blue".to_string(), 1000),
+            ]
+        );
+        assert_eq!(colors("doc2"), vec![("h4".to_string(), "yellow".to_string(), 1000)]);
+        assert_eq!(recolor_highlights_inner(&conn, "doc1", "green", "green", 3000).unwrap(), 0);
+    }
+
+    // === Annotation count tests ===
+
+    fn insert_note(conn: &Connection, id: &str, highlight_id: &str, content: &str) {
+        insert_margin_note(conn, id, highlight_id, content, 1000).unwrap();
+    }
+
+    #[test]
+    fn annotation_counts_per_document() {
+        let conn = setup_db();
+        insert_doc(&conn, "doc1");
+        insert_doc(&conn, "doc2");
+        insert_doc(&conn, "doc3");
+        insert_highlight(&conn, "h1", "doc1", "yellow", "a", 0, 1, None, None, 1000).unwrap();
+        insert_highlight(&conn, "h2", "doc1", "yellow", "b", 2, 3, None, None, 1000).unwrap();
+        insert_highlight(&conn, "h3", "doc2", "yellow", "c", 0, 1, None, None, 1000).unwrap();
+        insert_note(&conn, "n1", "h1", "one");
+        insert_note(&conn, "n2", "h1", "two");
+        insert_note(&conn, "n3", "h3", "three");
+
+        let ids: Vec<String> = ["doc1", "doc2", "doc3"].iter().map(|s| s.to_string()).collect();
+        let counts = fetch_annotation_counts(&conn, &ids).unwrap();
+        assert_eq!(counts["doc1"], AnnotationCounts { highlight_count: 2, note_count: 2 });
+        assert_eq!(counts["doc2"], AnnotationCounts { highlight_count: 1, note_count: 1 });
+        assert_eq!(counts["doc3"], AnnotationCounts::default());
+        assert!(fetch_annotation_counts(&conn, &[]).unwrap().is_empty());
+    }
+
+    // === Merge tests ===
+
+    #[test]
+    fn merge_collapses_overlapping_and_adjacent_same_color() {
+        let conn = setup_db();
+        insert_doc(&conn, "doc1");
+        // "The quick brown fox jumps"
+        insert_highlight(&conn, "h1", "doc1", "yellow", "quick brown", 4, 15, Some("The "), Some(" fox"), 3000).unwrap();
+        insert_highlight(&conn, "h2", "doc1", "yellow", "brown fox", 10, 19, Some("quick "), Some(" jumps"), 1000).unwrap();
+        // Adjacent: starts exactly where the merged span ends
+        insert_highlight(&conn, "h3", "doc1", "yellow", " jumps", 19, 25, Some("fox"), None, 2000).unwrap();
+        insert_note(&conn, "n1", "h1", "speed");
+        insert_note(&conn, "n2", "h2", "color");
+        insert_note(&conn, "n3", "h2", "speed");
+
+        assert_eq!(merge_overlapping_highlights_inner(&conn, "doc1", 5000).unwrap(), 2);
+
+        let highlights = fetch_highlights(&conn, "doc1").unwrap();
+        assert_eq!(highlights.len(), 1);
+        let h = &highlights[0];
+        assert_eq!(h.id, "h1");
+        assert_eq!((h.from_pos, h.to_pos), (4, 25));
+        assert_eq!(h.text_content, "quick brown fox jumps");
+        assert_eq!(h.created_at, 1000);
+        assert_eq!(h.suffix_context, None);
+
+        let mut notes: Vec<String> = fetch_margin_notes(&conn, "doc1").unwrap().into_iter().map(|n| n.content).collect();
+        notes.sort();
+        assert_eq!(notes, vec!["color", "speed"]);
+    }
+
+    #[test]
+    fn merge_absorbs_nested_highlight_but_never_crosses_colors() {
+        let conn = setup_db();
+        insert_doc(&conn, "doc1");
+        insert_highlight(&conn, "outer", "doc1", "yellow", "abcdefghij", 0, 10, None, None, 1000).unwrap();
+        insert_highlight(&conn, "inner", "doc1", "yellow", "cde", 2, 5, None, None, 1000).unwrap();
+        insert_highlight(&conn, "green", "doc1", "green", "efg", 4, 7, None, None, 1000).unwrap();
+        insert_highlight(&conn, "apart", "doc1", "yellow", "xyz", 11, 14, None, None, 1000).unwrap();
+
+        assert_eq!(merge_overlapping_highlights_inner(&conn, "doc1", 5000).unwrap(), 1);
+
+        let ids: Vec<String> = fetch_highlights(&conn, "doc1").unwrap().into_iter().map(|h| h.id).collect();
+        assert_eq!(ids, vec!["outer", "green", "apart"]);
+        let outer = fetch_highlights(&conn, "doc1").unwrap().remove(0);
+        assert_eq!(outer.text_content, "abcdefghij");
+        assert_eq!(outer.to_pos, 10);
+
+        assert_eq!(merge_overlapping_highlights_inner(&conn, "doc1", 6000).unwrap(), 0);
+    }
+
+    #[test]
+    fn merge_counts_overlap_in_utf16_units() {
+        let conn = setup_db();
+        insert_doc(&conn, "doc1");
+        // "so 😀 fun day": the emoji takes two UTF-16 units, 3..5
+        insert_highlight(&conn, "h1", "doc1", "yellow", "so 😀", 0, 5, None, Some(" fun"), 1000).unwrap();
+        insert_highlight(&conn, "h2", "doc1", "yellow", "😀 fun", 3, 9, Some("so "), Some(" day"), 2000).unwrap();
+
+        assert_eq!(merge_overlapping_highlights_inner(&conn, "doc1", 5000).unwrap(), 1);
+        let merged = fetch_highlights(&conn, "doc1").unwrap().remove(0);
+        assert_eq!((merged.from_pos, merged.to_pos), (0, 9));
+        assert_eq!(merged.text_content, "so 😀 fun");
+    }
+
+    // === Re-anchor tests ===
+
+    #[test]
+    fn reanchor_moves_shifted_highlights_and_reports_orphans() {
+        let conn = setup_db();
+        insert_doc(&conn, "doc1");
+        let old = "Hello world, how are you? Goodbye now.";
+        insert_highlight(&conn, "h1", "doc1", "yellow", "world", 6, 11, Some("Hello "), Some(", how"), 1000).unwrap();
+        insert_highlight(&conn, "h2", "doc1", "yellow", "Hello", 0, 5, None, Some(" world"), 1000).unwrap();
+        insert_highlight(&conn, "h3", "doc1", "yellow", "Goodbye", 26, 33, Some("you? "), Some(" now"), 1000).unwrap();
+        assert_eq!(&old[26..33], "Goodbye");
+
+        let new = "Hello world, how are you doing?";
+        let mut shifted = String::from("Oh! ");
+        shifted.push_str(new);
+
+        let result = reanchor_highlights_inner(&conn, "doc1", &shifted).unwrap();
+        assert_eq!(result.relocated, 2);
+        assert_eq!(result.unchanged, 0);
+        assert_eq!(result.orphaned, 1);
+        assert_eq!(result.orphaned_ids, vec!["h3".to_string()]);
+
+        let highlights = fetch_highlights(&conn, "doc1").unwrap();
+        let pos = |id: &str| {
+            let h = highlights.iter().find(|h| h.id == id).unwrap();
+            (h.from_pos, h.to_pos)
+        };
+        assert_eq!(pos("h2"), (4, 9));
+        assert_eq!(pos("h1"), (10, 15));
+        // Orphans keep their stale offsets for the UI to flag
+        assert_eq!(pos("h3"), (26, 33));
+
+        let again = reanchor_highlights_inner(&conn, "doc1", &shifted).unwrap();
+        assert_eq!(again.relocated, 0);
+        assert_eq!(again.unchanged, 2);
+    }
+
+    // === JSON import tests ===
+
+    fn imported(json: &str) -> Vec<ImportedHighlight> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn import_keeps_positions_for_unchanged_content() {
+        let conn = setup_db();
+        insert_doc(&conn, "doc1");
+        let content = "Hello world, how are you?";
+        let hs = imported(
+            r#"[{"text_content": "world", "color": "green", "from_pos": 6,
+                 "prefix_context": "Hello ", "suffix_context": ", how"}]"#,
+        );
+
+        let result = import_highlights_inner(&conn, "doc1", content, &hs).unwrap();
+        assert_eq!(result.anchored, 1);
+        assert_eq!(result.unmatched, 0);
+
+        let stored = fetch_highlights(&conn, "doc1").unwrap();
+        assert_eq!((stored[0].from_pos, stored[0].to_pos), (6, 11));
+        assert_eq!(stored[0].color, "green");
+    }
+
+    #[test]
+    fn import_remaps_shifted_content_and_skips_missing_text() {
+        let conn = setup_db();
+        insert_doc(&conn, "doc1");
+        let content = "A new opening line.\nHello world, how are you?";
+        let hs = imported(
+            r#"[{"text_content": "world", "from_pos": 6, "prefix_context": "Hello ", "suffix_context": ", how"},
+                {"text_content": "deleted sentence", "from_pos": 30}]"#,
+        );
+
+        crate::db::migrations::migrate_add_settings_table(&conn).unwrap();
+        crate::commands::settings::set_setting(&conn, "default_highlight_color", "blue").unwrap();
+
+        let result = import_highlights_inner(&conn, "doc1", content, &hs).unwrap();
+        assert_eq!(result.anchored, 1);
+        assert_eq!(result.unmatched, 1);
+
+        let stored = fetch_highlights(&conn, "doc1").unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!((stored[0].from_pos, stored[0].to_pos), (26, 31));
+        assert_eq!(stored[0].color, "blue");
+    }
+
+    // === Note template tests ===
+
+    #[test]
+    fn note_from_template_uses_color_template_or_empty() {
+        let conn = setup_db();
+        crate::db::migrations::migrate_add_settings_table(&conn).unwrap();
+        crate::commands::settings::set_setting(&conn, "note_templates", r#"{"red":"Fix this: "}"#).unwrap();
+        insert_doc(&conn, "doc1");
+        insert_highlight(&conn, "h1", "doc1", "red", "bad", 0, 3, None, None, 1000).unwrap();
+        insert_highlight(&conn, "h2", "doc1", "green", "good", 5, 9, None, None, 1000).unwrap();
+
+        let templated = create_note_from_template_inner(&conn, "h1", 2000).unwrap();
+        assert_eq!(templated.content, "Fix this: ");
+        let plain = create_note_from_template_inner(&conn, "h2", 2000).unwrap();
+        assert_eq!(plain.content, "");
+        assert_eq!(note_count(&conn), 2);
+
+        assert!(create_note_from_template_inner(&conn, "missing", 2000).is_err());
+    }
+
+    // === Color export tests ===
+
+    #[test]
+    fn color_export_includes_only_requested_color() {
+        let conn = setup_db();
+        insert_doc(&conn, "doc1");
+        insert_highlight(&conn, "h1", "doc1", "yellow", "a quote", 0, 7, None, None, 1000).unwrap();
+        insert_highlight(&conn, "h2", "doc1", "blue", "a question", 10, 20, None, None, 1000).unwrap();
+        insert_highlight(&conn, "h3", "doc1", "yellow", "another quote", 30, 43, None, None, 1000).unwrap();
+        insert_margin_note(&conn, "n1", "h1", "keep this", 1001).unwrap();
+        insert_margin_note(&conn, "n2", "h2", "why?", 1001).unwrap();
+
+        let (md, count) = build_color_export(&conn, "doc1", "yellow").unwrap();
+        assert_eq!(count, 2);
+        assert!(md.starts_with("# Annotations: \"Test Doc\" — yellow highlights\n"));
+        assert!(md.contains("> a quote\n\n**Note:** keep this\n"));
+        assert!(md.contains("> another quote\n"));
+        assert!(!md.contains("a question"));
+        assert!(!md.contains("why?"));
+    }
+
+    #[test]
+    fn color_export_with_no_matches_says_so() {
+        let conn = setup_db();
+        insert_doc(&conn, "doc1");
+        insert_highlight(&conn, "h1", "doc1", "yellow", "a quote", 0, 7, None, None, 1000).unwrap();
+
+        let (md, count) = build_color_export(&conn, "doc1", "red").unwrap();
+        assert_eq!(count, 0);
+        assert!(md.contains("_No annotations to export._"));
+    }
+
+    // === Highlight feed tests ===
+
+    #[test]
+    fn all_highlights_span_documents_newest_first() {
+        let conn = setup_db();
+        insert_doc(&conn, "doc1");
+        insert_doc(&conn, "doc2");
+        conn.execute("UPDATE documents SET title = 'Second' WHERE id = 'doc2'", []).unwrap();
+        insert_highlight(&conn, "h1", "doc1", "yellow", "oldest", 0, 6, None, None, 1000).unwrap();
+        insert_highlight(&conn, "h2", "doc2", "blue", "newest", 0, 6, None, None, 3000).unwrap();
+        insert_highlight(&conn, "h3", "doc1", "green", "middle", 10, 16, None, None, 2000).unwrap();
+        insert_margin_note(&conn, "n2", "h3", "second thought", 2002).unwrap();
+        insert_margin_note(&conn, "n1", "h3", "first thought", 2001).unwrap();
+
+        let feed = fetch_all_highlights(&conn, 10, 0).unwrap();
+        let ids: Vec<&str> = feed.iter().map(|i| i.highlight.id.as_str()).collect();
+        assert_eq!(ids, vec!["h2", "h3", "h1"]);
+        assert_eq!(feed[0].document_title.as_deref(), Some("Second"));
+        assert_eq!(feed[1].document_title.as_deref(), Some("Test Doc"));
+        assert_eq!(feed[1].notes, vec!["first thought", "second thought"]);
+        assert!(feed[0].notes.is_empty());
+
+        let page = fetch_all_highlights(&conn, 2, 1).unwrap();
+        let ids: Vec<&str> = page.iter().map(|i| i.highlight.id.as_str()).collect();
+        assert_eq!(ids, vec!["h3", "h1"]);
+    }
+
+    // === Orphaned note tests ===
+
+    #[test]
+    fn orphaned_notes_are_found_and_pruned() {
+        let conn = setup_db();
+        insert_doc(&conn, "doc1");
+        insert_highlight(&conn, "h1", "doc1", "yellow", "kept", 0, 4, None, None, 1000).unwrap();
+        insert_margin_note(&conn, "n1", "h1", "attached", 1001).unwrap();
+
+        // Simulate a backfill that bypassed the FK
+        conn.execute_batch("PRAGMA foreign_keys=OFF;").unwrap();
+        insert_margin_note(&conn, "n2", "gone", "orphan", 1002).unwrap();
+        conn.execute_batch("PRAGMA foreign_keys=ON;").unwrap();
+
+        let orphans = fetch_orphaned_notes(&conn).unwrap();
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].id, "n2");
+
+        assert_eq!(remove_orphaned_notes(&conn).unwrap(), 1);
+        assert_eq!(note_count(&conn), 1);
+        assert!(fetch_orphaned_notes(&conn).unwrap().is_empty());
+        assert_eq!(remove_orphaned_notes(&conn).unwrap(), 0);
+    }
+}
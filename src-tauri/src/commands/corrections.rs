@@ -1,10 +1,12 @@
 use crate::commands::now_millis;
+use crate::commands::writing_rules::increment_signal;
 use crate::db::migrations::DbPool;
 use crate::db::models::CorrectionInput;
-use rusqlite::Connection;
+use chrono::{SecondsFormat, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::HashMap;
 use std::fs;
 use std::io::Write;
-use std::time::SystemTime;
 use uuid::Uuid;
 
 #[derive(serde::Serialize)]
@@ -18,6 +20,7 @@ pub struct CorrectionRecord {
     pub created_at: i64,
     pub writing_type: Option<String>,
     pub polarity: Option<String>,
+    pub category: Option<String>,
 }
 
 #[derive(serde::Serialize)]
@@ -39,11 +42,53 @@ pub struct CorrectionDetail {
     pub highlight_color: String,
     pub writing_type: Option<String>,
     pub polarity: Option<String>,
+    pub category: Option<String>,
     pub document_title: Option<String>,
     pub created_at: i64,
     pub synthesized_at: Option<i64>,
 }
 
+/// A correction linked to a writing rule: `original_text` is the "before",
+/// the notes are the "after".
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleExample {
+    pub highlight_id: String,
+    pub original_text: String,
+    pub notes: Vec<String>,
+    pub document_title: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorrectionContext {
+    pub highlight_id: String,
+    pub prefix_context: Option<String>,
+    pub original_text: String,
+    pub suffix_context: Option<String>,
+    pub extended_context: Option<String>,
+    /// `prefix + <mark>original</mark> + suffix`, ready to render.
+    pub window: String,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopCorrectedDocument {
+    pub document_id: String,
+    pub document_title: Option<String>,
+    pub document_path: Option<String>,
+    pub correction_count: i64,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorrectionsStats {
+    pub total: i64,
+    pub by_writing_type: HashMap<String, i64>,
+    pub by_highlight_color: HashMap<String, i64>,
+}
+
 fn sanitize_filename_component(input: &str) -> String {
     let mut out = String::with_capacity(input.len().min(64));
     for ch in input.chars() {
@@ -60,16 +105,23 @@ fn sanitize_filename_component(input: &str) -> String {
     }
 }
 
-fn fetch_corrections(conn: &Connection, limit: i64) -> rusqlite::Result<Vec<CorrectionRecord>> {
+fn fetch_corrections(
+    conn: &Connection,
+    limit: i64,
+    category: Option<&str>,
+    since: Option<i64>,
+    until: Option<i64>,
+) -> rusqlite::Result<Vec<CorrectionRecord>> {
     let mut stmt = conn.prepare(
-        "SELECT original_text, notes_json, highlight_color, document_title, document_id, created_at, writing_type, polarity
+        "SELECT original_text, notes_json, highlight_color, document_title, document_id, created_at, writing_type, polarity, category
          FROM corrections
-         WHERE session_id != '__backfilled__'
+         WHERE session_id != '__backfilled__' AND (?2 IS NULL OR category = ?2)
+           AND (?3 IS NULL OR created_at >= ?3) AND (?4 IS NULL OR created_at <= ?4)
          ORDER BY created_at DESC
          LIMIT ?1",
     )?;
 
-    let rows = stmt.query_map([limit], |row| {
+    let rows = stmt.query_map(rusqlite::params![limit, category, since, until], |row| {
         let original_text: String = row.get(0)?;
         let notes_json: String = row.get(1)?;
         let highlight_color: String = row.get(2)?;
@@ -78,6 +130,7 @@ fn fetch_corrections(conn: &Connection, limit: i64) -> rusqlite::Result<Vec<Corr
         let created_at: i64 = row.get(5)?;
         let writing_type: Option<String> = row.get(6)?;
         let polarity: Option<String> = row.get(7)?;
+        let category: Option<String> = row.get(8)?;
 
         let notes: Vec<String> = serde_json::from_str(&notes_json).unwrap_or_default();
 
@@ -90,6 +143,7 @@ fn fetch_corrections(conn: &Connection, limit: i64) -> rusqlite::Result<Vec<Corr
             created_at,
             writing_type,
             polarity,
+            category,
         })
     })?;
 
@@ -100,6 +154,59 @@ fn fetch_corrections(conn: &Connection, limit: i64) -> rusqlite::Result<Vec<Corr
     Ok(records)
 }
 
+/// Quotes a CSV field when it contains a delimiter, quote, or line break (RFC 4180).
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn render_corrections_csv(records: &[CorrectionRecord]) -> String {
+    let mut out = String::from(
+        "created_at,document_id,document_title,writing_type,polarity,category,highlight_color,original_text,notes\n",
+    );
+    for r in records {
+        let fields = [
+            r.created_at.to_string(),
+            r.document_id.clone(),
+            r.document_title.clone().unwrap_or_default(),
+            r.writing_type.clone().unwrap_or_default(),
+            r.polarity.clone().unwrap_or_default(),
+            r.category.clone().unwrap_or_default(),
+            r.highlight_color.clone(),
+            r.original_text.clone(),
+            r.notes.join("\n"),
+        ];
+        let line: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&line.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+fn export_corrections_csv_range_inner(
+    conn: &Connection,
+    since: i64,
+    until: i64,
+    path: &std::path::Path,
+) -> Result<usize, String> {
+    if since > until {
+        return Err("`since` must not be after `until`".to_string());
+    }
+    // A negative LIMIT means no limit in SQLite; the CSV reads oldest first
+    let mut records = fetch_corrections(conn, -1, None, Some(since), Some(until)).map_err(|e| e.to_string())?;
+    records.reverse();
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {e}"))?;
+    }
+    fs::write(path, render_corrections_csv(&records)).map_err(|e| format!("Failed to write export: {e}"))?;
+
+    Ok(records.len())
+}
+
 fn count_corrections(conn: &Connection) -> rusqlite::Result<i64> {
     conn.query_row(
         "SELECT COUNT(*) FROM corrections WHERE session_id != '__backfilled__'",
@@ -109,10 +216,16 @@ fn count_corrections(conn: &Connection) -> rusqlite::Result<i64> {
 }
 
 #[tauri::command]
-pub async fn get_all_corrections(state: tauri::State<'_, DbPool>, limit: Option<i64>) -> Result<Vec<CorrectionRecord>, String> {
+pub async fn get_all_corrections(
+    state: tauri::State<'_, DbPool>,
+    limit: Option<i64>,
+    category: Option<String>,
+    since: Option<i64>,
+    until: Option<i64>,
+) -> Result<Vec<CorrectionRecord>, String> {
     let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
     let limit = limit.unwrap_or(200).clamp(1, 2000);
-    fetch_corrections(&conn, limit).map_err(|e| e.to_string())
+    fetch_corrections(&conn, limit, category.as_deref(), since, until).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -144,7 +257,7 @@ pub async fn persist_corrections(
                 eprintln!("Failed to create corrections directory: {e}");
                 return None;
             }
-            Some(dir.join(format!("corrections-{}.jsonl", safe_export_date)))
+            Some(jsonl_append_path(&dir, &format!("corrections-{}", safe_export_date), JSONL_ROTATE_BYTES))
         })
         .and_then(|jsonl_path| {
             fs::OpenOptions::new()
@@ -158,39 +271,15 @@ pub async fn persist_corrections(
                 .ok()
         });
 
-    for input in &corrections {
-        let id = Uuid::new_v4().to_string();
-        let notes_json = serde_json::to_string(&input.notes).map_err(|e| e.to_string())?;
+    let source = CorrectionSource {
+        document_id: &document_id,
+        document_title: document_title.as_deref(),
+        document_source: &document_source,
+        document_path: document_path.as_deref(),
+    };
 
-        tx.execute(
-            "INSERT INTO corrections
-                (id, highlight_id, document_id, session_id, original_text,
-                 prefix_context, suffix_context, extended_context, notes_json,
-                 document_title, document_source, document_path, category,
-                 highlight_color, created_at, updated_at, writing_type, polarity)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
-            rusqlite::params![
-                id,
-                input.highlight_id,
-                document_id,
-                session_id,
-                input.original_text,
-                input.prefix_context,
-                input.suffix_context,
-                input.extended_context,
-                notes_json,
-                document_title,
-                document_source,
-                document_path,
-                Option::<String>::None, // category
-                input.highlight_color,
-                now,
-                now,
-                input.writing_type,
-                input.polarity,
-            ],
-        )
-        .map_err(|e| e.to_string())?;
+    for input in &corrections {
+        insert_correction_input(&tx, input, &source, &session_id, now).map_err(|e| e.to_string())?;
 
         // Append JSONL record
         if let Some(file) = jsonl_file.as_mut() {
@@ -209,6 +298,7 @@ pub async fn persist_corrections(
             "highlight_color": input.highlight_color,
             "writing_type": input.writing_type,
             "polarity": input.polarity,
+            "category": input.category,
             "exported_at": now,
         });
 
@@ -240,6 +330,77 @@ pub async fn persist_corrections(
     Ok(session_id)
 }
 
+/// Size at which a day's corrections JSONL rolls over to a new `-NNN` part.
+const JSONL_ROTATE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// The file to append to for `stem`: `stem.jsonl`, then `stem-002.jsonl`, `stem-003.jsonl`, …
+/// once each reaches `max_bytes`. The backfill sorts these parts in this order.
+fn jsonl_append_path(dir: &std::path::Path, stem: &str, max_bytes: u64) -> std::path::PathBuf {
+    let mut part = 1u32;
+    loop {
+        let name = if part == 1 {
+            format!("{stem}.jsonl")
+        } else {
+            format!("{stem}-{part:03}.jsonl")
+        };
+        let path = dir.join(name);
+        match fs::metadata(&path) {
+            Ok(meta) if meta.len() >= max_bytes => part += 1,
+            _ => return path,
+        }
+    }
+}
+
+/// Document-level fields shared by every correction in one persist call.
+struct CorrectionSource<'a> {
+    document_id: &'a str,
+    document_title: Option<&'a str>,
+    document_source: &'a str,
+    document_path: Option<&'a str>,
+}
+
+fn insert_correction_input(
+    conn: &Connection,
+    input: &CorrectionInput,
+    source: &CorrectionSource,
+    session_id: &str,
+    now: i64,
+) -> rusqlite::Result<()> {
+    let id = Uuid::new_v4().to_string();
+    let notes_json = serde_json::to_string(&input.notes)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+
+    conn.execute(
+        "INSERT INTO corrections
+            (id, highlight_id, document_id, session_id, original_text,
+             prefix_context, suffix_context, extended_context, notes_json,
+             document_title, document_source, document_path, category,
+             highlight_color, created_at, updated_at, writing_type, polarity)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
+        rusqlite::params![
+            id,
+            input.highlight_id,
+            source.document_id,
+            session_id,
+            input.original_text,
+            input.prefix_context,
+            input.suffix_context,
+            input.extended_context,
+            notes_json,
+            source.document_title,
+            source.document_source,
+            source.document_path,
+            input.category,
+            input.highlight_color,
+            now,
+            now,
+            input.writing_type,
+            input.polarity,
+        ],
+    )?;
+    Ok(())
+}
+
 /// Auto-synthesize a writing rule from a correction's notes.
 /// Uses UPSERT to deduplicate by (writing_type, category, rule_text) and increment signal_count.
 fn auto_synthesize_rule(
@@ -288,7 +449,7 @@ fn fetch_corrections_flat(
     let mut stmt = conn.prepare(
         "SELECT highlight_id, original_text, notes_json, extended_context,
                 highlight_color, writing_type, polarity, document_title, created_at,
-                synthesized_at
+                synthesized_at, category
          FROM corrections
          WHERE session_id != '__backfilled__'
          ORDER BY CASE WHEN synthesized_at IS NULL THEN 0 ELSE 1 END, created_at DESC
@@ -307,6 +468,7 @@ fn fetch_corrections_flat(
             highlight_color: row.get(4)?,
             writing_type: row.get(5)?,
             polarity: row.get(6)?,
+            category: row.get(10)?,
             document_title: row.get(7)?,
             created_at: row.get(8)?,
             synthesized_at: row.get(9)?,
@@ -374,18 +536,33 @@ fn bulk_tag(
 fn fetch_corrections_by_document(
     conn: &Connection,
     limit: i64,
+    since: Option<i64>,
+    until: Option<i64>,
+) -> rusqlite::Result<Vec<DocumentCorrections>> {
+    fetch_correction_groups(conn, limit, None, since, until)
+}
+
+/// Corrections grouped by document, optionally restricted to one document and to
+/// an inclusive `created_at` window.
+fn fetch_correction_groups(
+    conn: &Connection,
+    limit: i64,
+    document_id: Option<&str>,
+    since: Option<i64>,
+    until: Option<i64>,
 ) -> rusqlite::Result<Vec<DocumentCorrections>> {
     let mut stmt = conn.prepare(
         "SELECT highlight_id, original_text, notes_json, extended_context,
                 highlight_color, writing_type, polarity, document_title, document_id,
-                document_path, created_at, synthesized_at
+                document_path, created_at, synthesized_at, category
          FROM corrections
-         WHERE session_id != '__backfilled__'
+         WHERE session_id != '__backfilled__' AND (?2 IS NULL OR document_id = ?2)
+           AND (?3 IS NULL OR created_at >= ?3) AND (?4 IS NULL OR created_at <= ?4)
          ORDER BY created_at DESC
          LIMIT ?1",
     )?;
 
-    let rows = stmt.query_map([limit], |row| {
+    let rows = stmt.query_map(rusqlite::params![limit, document_id, since, until], |row| {
         Ok((
             row.get::<_, String>(8)?,        // document_id
             row.get::<_, Option<String>>(7)?, // document_title
@@ -401,6 +578,7 @@ fn fetch_corrections_by_document(
                 highlight_color: row.get(4)?,
                 writing_type: row.get(5)?,
                 polarity: row.get(6)?,
+                category: row.get(12)?,
                 document_title: row.get(7)?,
                 created_at: row.get(10)?,
                 synthesized_at: row.get(11)?,
@@ -430,6 +608,93 @@ fn fetch_corrections_by_document(
     Ok(groups)
 }
 
+fn fetch_top_corrected_documents(
+    conn: &Connection,
+    limit: i64,
+) -> rusqlite::Result<Vec<TopCorrectedDocument>> {
+    // LEFT JOIN so corrections outlive their document; fall back to the title/path captured at persist time
+    let mut stmt = conn.prepare(
+        "SELECT c.document_id,
+                COALESCE(d.title, MAX(c.document_title)),
+                COALESCE(d.file_path, MAX(c.document_path)),
+                COUNT(*) AS correction_count
+         FROM corrections c
+         LEFT JOIN documents d ON d.id = c.document_id
+         WHERE c.session_id != '__backfilled__'
+         GROUP BY c.document_id
+         ORDER BY correction_count DESC, MAX(c.created_at) DESC
+         LIMIT ?1",
+    )?;
+
+    let rows = stmt.query_map([limit], |row| {
+        Ok(TopCorrectedDocument {
+            document_id: row.get(0)?,
+            document_title: row.get(1)?,
+            document_path: row.get(2)?,
+            correction_count: row.get(3)?,
+        })
+    })?;
+
+    rows.collect()
+}
+
+/// Correction counts per value of `column`; untagged rows are counted under "unknown".
+fn count_corrections_by(conn: &Connection, column: &str) -> rusqlite::Result<HashMap<String, i64>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT COALESCE({column}, 'unknown'), COUNT(*)
+         FROM corrections
+         WHERE session_id != '__backfilled__'
+         GROUP BY 1"
+    ))?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?;
+    rows.collect()
+}
+
+fn fetch_corrections_stats(conn: &Connection) -> rusqlite::Result<CorrectionsStats> {
+    Ok(CorrectionsStats {
+        total: count_corrections(conn)?,
+        by_writing_type: count_corrections_by(conn, "writing_type")?,
+        by_highlight_color: count_corrections_by(conn, "highlight_color")?,
+    })
+}
+
+fn fetch_correction_context(conn: &Connection, highlight_id: &str) -> rusqlite::Result<Option<CorrectionContext>> {
+    let row = conn.query_row(
+        "SELECT prefix_context, original_text, suffix_context, extended_context
+         FROM corrections
+         WHERE highlight_id = ?1",
+        [highlight_id],
+        |row| {
+            Ok((
+                row.get::<_, Option<String>>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        },
+    );
+    let (prefix_context, original_text, suffix_context, extended_context) = match row {
+        Ok(r) => r,
+        Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+
+    let window = format!(
+        "{}<mark>{}</mark>{}",
+        prefix_context.as_deref().unwrap_or(""),
+        original_text,
+        suffix_context.as_deref().unwrap_or(""),
+    );
+    Ok(Some(CorrectionContext {
+        highlight_id: highlight_id.to_string(),
+        prefix_context,
+        original_text,
+        suffix_context,
+        extended_context,
+        window,
+    }))
+}
+
 fn update_writing_type(
     conn: &Connection,
     highlight_id: &str,
@@ -464,6 +729,16 @@ pub struct CorrectionsExport {
     pub corrections: Vec<ExportedCorrection>,
 }
 
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentCorrectionsExport {
+    pub document_id: String,
+    pub document_title: Option<String>,
+    pub exported_at: String,
+    pub total_count: usize,
+    pub corrections: Vec<ExportedCorrection>,
+}
+
 #[derive(serde::Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExportedCorrection {
@@ -485,45 +760,110 @@ pub struct ExportResult {
     pub highlight_ids: Vec<String>,
 }
 
-/// Formats Unix seconds as an ISO 8601 UTC timestamp (e.g. "2026-03-01T12:34:56Z").
-fn unix_secs_to_iso8601(secs: u64) -> String {
-    let days = secs / 86400;
-    let time_of_day = secs % 86400;
-    let hours = time_of_day / 3600;
-    let minutes = (time_of_day % 3600) / 60;
-    let seconds = time_of_day % 60;
+/// Current time as an ISO 8601 UTC timestamp with second precision (e.g. "2026-03-01T12:34:56Z").
+fn iso8601_now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
 
-    let mut y = 1970i64;
-    let mut remaining_days = days as i64;
-    loop {
-        let leap = y % 4 == 0 && (y % 100 != 0 || y % 400 == 0);
-        let year_days: i64 = if leap { 366 } else { 365 };
-        if remaining_days < year_days { break; }
-        remaining_days -= year_days;
-        y += 1;
+/// Returns `false` when the link already existed.
+fn link_correction_to_rule_inner(conn: &Connection, highlight_id: &str, rule_id: &str) -> Result<bool, String> {
+    let correction_exists: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM corrections WHERE highlight_id = ?1)",
+            rusqlite::params![highlight_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if !correction_exists {
+        return Err(format!("No correction for highlight: {highlight_id}"));
+    }
+    let rule_exists: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM writing_rules WHERE id = ?1)",
+            rusqlite::params![rule_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if !rule_exists {
+        return Err(format!("Writing rule not found: {rule_id}"));
     }
-    let leap = y % 4 == 0 && (y % 100 != 0 || y % 400 == 0);
-    let month_days = [31, if leap { 29 } else { 28 }, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
-    let mut m = 0usize;
-    for &md in &month_days {
-        if remaining_days < md as i64 { break; }
-        remaining_days -= md as i64;
-        m += 1;
+
+    let inserted = conn
+        .execute(
+            "INSERT OR IGNORE INTO correction_rule_links (highlight_id, rule_id, created_at)
+             VALUES (?1, ?2, ?3)",
+            rusqlite::params![highlight_id, rule_id, now_millis()],
+        )
+        .map_err(|e| e.to_string())?;
+    if inserted > 0 {
+        increment_signal(conn, rule_id).map_err(|e| e.to_string())?;
     }
-    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", y, m + 1, remaining_days + 1, hours, minutes, seconds)
+    Ok(inserted > 0)
 }
 
-fn build_corrections_export(conn: &Connection) -> rusqlite::Result<CorrectionsExport> {
+/// Linked corrections, newest first. A highlight corrected in several
+/// sessions contributes its latest version.
+fn fetch_rule_examples(conn: &Connection, rule_id: &str) -> Result<Vec<RuleExample>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT c.highlight_id, c.original_text, c.notes_json, c.document_title, c.created_at
+             FROM correction_rule_links l
+             JOIN corrections c ON c.rowid = (
+                 SELECT rowid FROM corrections
+                 WHERE highlight_id = l.highlight_id
+                 ORDER BY created_at DESC
+                 LIMIT 1
+             )
+             WHERE l.rule_id = ?1
+             ORDER BY c.created_at DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let results = stmt
+        .query_map(rusqlite::params![rule_id], |row| {
+            let notes_json: String = row.get(2)?;
+            Ok(RuleExample {
+                highlight_id: row.get(0)?,
+                original_text: row.get(1)?,
+                notes: serde_json::from_str(&notes_json).unwrap_or_default(),
+                document_title: row.get(3)?,
+                created_at: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string());
+    results
+}
+
+/// How many rows `build_corrections_export` would include, without building it.
+fn count_exportable_corrections(conn: &Connection) -> rusqlite::Result<i64> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM corrections
+         WHERE session_id != '__backfilled__' AND synthesized_at IS NULL",
+        [],
+        |row| row.get(0),
+    )
+}
+
+/// Corrections in export shape, newest first. `pending_only` skips rows already synthesized.
+fn fetch_exported_corrections(
+    conn: &Connection,
+    document_id: Option<&str>,
+    pending_only: bool,
+) -> rusqlite::Result<Vec<ExportedCorrection>> {
     let mut stmt = conn.prepare(
         "SELECT highlight_id, original_text, notes_json, extended_context, writing_type, polarity,
                 document_title, highlight_color, created_at
          FROM corrections
-         WHERE session_id != '__backfilled__' AND synthesized_at IS NULL
+         WHERE session_id != '__backfilled__'
+           AND (?1 IS NULL OR document_id = ?1)
+           AND (?2 = 0 OR synthesized_at IS NULL)
          ORDER BY created_at DESC",
     )?;
 
-    let corrections: Vec<ExportedCorrection> = stmt
-        .query_map([], |row| {
+    let corrections = stmt
+        .query_map(rusqlite::params![document_id, pending_only], |row| {
             Ok(ExportedCorrection {
                 highlight_id: row.get(0)?,
                 original_text: row.get(1)?,
@@ -539,26 +879,51 @@ fn build_corrections_export(conn: &Connection) -> rusqlite::Result<CorrectionsEx
                 created_at: row.get(8)?,
             })
         })?
-        .collect::<Result<Vec<_>, _>>()?;
+        .collect::<Result<Vec<_>, _>>();
+    corrections
+}
 
-    let now = unix_secs_to_iso8601(
-        SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs(),
-    );
+fn build_corrections_export(conn: &Connection) -> rusqlite::Result<CorrectionsExport> {
+    let corrections = fetch_exported_corrections(conn, None, true)?;
     Ok(CorrectionsExport {
-        exported_at: now,
+        exported_at: iso8601_now(),
         total_count: corrections.len(),
         corrections,
     })
 }
 
 #[tauri::command]
-pub async fn get_corrections_by_document(state: tauri::State<'_, DbPool>, limit: Option<i64>) -> Result<Vec<DocumentCorrections>, String> {
+pub async fn get_corrections_by_document(
+    state: tauri::State<'_, DbPool>,
+    limit: Option<i64>,
+    since: Option<i64>,
+    until: Option<i64>,
+) -> Result<Vec<DocumentCorrections>, String> {
     let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
     let limit = limit.unwrap_or(50).clamp(1, 500);
-    fetch_corrections_by_document(&conn, limit).map_err(|e| e.to_string())
+    fetch_corrections_by_document(&conn, limit, since, until).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_correction_context(
+    state: tauri::State<'_, DbPool>,
+    highlight_id: String,
+) -> Result<Option<CorrectionContext>, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    fetch_correction_context(&conn, &highlight_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_top_corrected_documents(state: tauri::State<'_, DbPool>, limit: Option<i64>) -> Result<Vec<TopCorrectedDocument>, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    let limit = limit.unwrap_or(10).clamp(1, 500);
+    fetch_top_corrected_documents(&conn, limit).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_corrections_stats(state: tauri::State<'_, DbPool>) -> Result<CorrectionsStats, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    fetch_corrections_stats(&conn).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -704,7 +1069,7 @@ fn fetch_voice_signals(
         Some(p) => (
             "SELECT highlight_id, original_text, notes_json, extended_context,
                     highlight_color, writing_type, polarity, document_title, created_at,
-                    synthesized_at
+                    synthesized_at, category
              FROM corrections
              WHERE session_id != '__backfilled__' AND polarity = ?1
              ORDER BY created_at DESC
@@ -714,7 +1079,7 @@ fn fetch_voice_signals(
         None => (
             "SELECT highlight_id, original_text, notes_json, extended_context,
                     highlight_color, writing_type, polarity, document_title, created_at,
-                    synthesized_at
+                    synthesized_at, category
              FROM corrections
              WHERE session_id != '__backfilled__' AND polarity IS NOT NULL
              ORDER BY created_at DESC
@@ -737,6 +1102,7 @@ fn fetch_voice_signals(
             highlight_color: row.get(4)?,
             writing_type: row.get(5)?,
             polarity: row.get(6)?,
+            category: row.get(10)?,
             document_title: row.get(7)?,
             created_at: row.get(8)?,
             synthesized_at: row.get(9)?,
@@ -761,6 +1127,61 @@ fn export_corrections_only(conn: &Connection, path: &std::path::Path) -> Result<
     Ok(ExportResult { count, highlight_ids })
 }
 
+/// Writes one document's corrections to a file and never deletes anything.
+/// `path` may be a file, a directory, or `None` for the folder of the document's
+/// source file; the last two get `<title>.corrections.json`. Returns the written path.
+fn export_document_corrections_inner(
+    conn: &Connection,
+    document_id: &str,
+    path: Option<&std::path::Path>,
+) -> Result<std::path::PathBuf, String> {
+    let corrections = fetch_exported_corrections(conn, Some(document_id), false).map_err(|e| e.to_string())?;
+    let Some(document_title) = corrections.first().map(|c| c.document_title.clone()) else {
+        return Err("No corrections for this document".to_string());
+    };
+
+    let default_name = format!(
+        "{}.corrections.json",
+        sanitize_filename_component(document_title.as_deref().unwrap_or("untitled"))
+    );
+    let path = match path {
+        Some(p) if p.is_dir() => p.join(default_name),
+        Some(p) => p.to_path_buf(),
+        None => {
+            let document_path: Option<String> = conn
+                .query_row(
+                    "SELECT document_path FROM corrections
+                     WHERE document_id = ?1 AND document_path IS NOT NULL
+                     ORDER BY created_at DESC LIMIT 1",
+                    [document_id],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(|e| e.to_string())?;
+            document_path
+                .as_deref()
+                .and_then(|p| std::path::Path::new(p).parent())
+                .map(|dir| dir.join(&default_name))
+                .ok_or_else(|| "Document has no folder on disk; choose an export path".to_string())?
+        }
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {e}"))?;
+    }
+    let export = DocumentCorrectionsExport {
+        document_id: document_id.to_string(),
+        document_title,
+        exported_at: iso8601_now(),
+        total_count: corrections.len(),
+        corrections,
+    };
+    let json = serde_json::to_string_pretty(&export).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write export: {e}"))?;
+
+    Ok(path)
+}
+
 fn mark_synthesized(
     conn: &Connection,
     highlight_ids: &[String],
@@ -805,6 +1226,54 @@ pub async fn export_corrections_json(state: tauri::State<'_, DbPool>, path: Opti
     export_corrections_only(&conn, &export_path)
 }
 
+#[tauri::command]
+pub async fn export_document_corrections(
+    state: tauri::State<'_, DbPool>,
+    document_id: String,
+    path: Option<String>,
+) -> Result<String, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    let path = export_document_corrections_inner(&conn, &document_id, path.as_deref().map(std::path::Path::new))?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+/// Writes corrections created in `[since, until]` (millis, inclusive like the
+/// other correction filters) to a CSV file.
+/// Unlike the JSON export this never marks anything synthesized.
+#[tauri::command]
+pub async fn export_corrections_csv_range(
+    state: tauri::State<'_, DbPool>,
+    since: i64,
+    until: i64,
+    path: String,
+) -> Result<usize, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    export_corrections_csv_range_inner(&conn, since, until, std::path::Path::new(&path))
+}
+
+#[tauri::command]
+pub async fn link_correction_to_rule(
+    state: tauri::State<'_, DbPool>,
+    highlight_id: String,
+    rule_id: String,
+) -> Result<bool, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    link_correction_to_rule_inner(&conn, &highlight_id, &rule_id)
+}
+
+#[tauri::command]
+pub async fn get_rule_examples(state: tauri::State<'_, DbPool>, rule_id: String) -> Result<Vec<RuleExample>, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    fetch_rule_examples(&conn, &rule_id)
+}
+
+/// Read-only preflight for `export_corrections_json`.
+#[tauri::command]
+pub async fn get_exportable_corrections_count(state: tauri::State<'_, DbPool>) -> Result<i64, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    count_exportable_corrections(&conn).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn mark_corrections_synthesized(
     state: tauri::State<'_, DbPool>,
@@ -864,6 +1333,7 @@ mod tests {
     fn setup_full_db() -> Connection {
         let conn = Connection::open_in_memory().unwrap();
         conn.execute_batch(full_schema_sql()).unwrap();
+        crate::db::migrations::migrate_add_correction_rule_links_table(&conn).unwrap();
         conn
     }
 
@@ -907,7 +1377,7 @@ mod tests {
             .unwrap();
         }
 
-        let records = fetch_corrections(&conn, 2).unwrap();
+        let records = fetch_corrections(&conn, 2, None, None, None).unwrap();
         assert_eq!(records.len(), 2);
         assert_eq!(records[0].created_at, 4);
         assert_eq!(records[1].created_at, 3);
@@ -941,7 +1411,7 @@ mod tests {
     fn fetch_corrections_deserializes_notes_json() {
         let conn = setup_full_db();
         insert_correction(&conn, "h1", "bad phrase", r#"["use X instead","also Y"]"#);
-        let records = fetch_corrections(&conn, 10).unwrap();
+        let records = fetch_corrections(&conn, 10, None, None, None).unwrap();
         assert_eq!(records.len(), 1);
         assert_eq!(records[0].original_text, "bad phrase");
         assert_eq!(records[0].notes, vec!["use X instead", "also Y"]);
@@ -961,7 +1431,7 @@ mod tests {
         )
         .unwrap();
 
-        let records = fetch_corrections(&conn, 10).unwrap();
+        let records = fetch_corrections(&conn, 10, None, None, None).unwrap();
         assert_eq!(records.len(), 1);
         assert_eq!(records[0].original_text, "live text");
     }
@@ -1007,7 +1477,7 @@ mod tests {
             [],
         ).unwrap();
 
-        let records = fetch_corrections(&conn, 10).unwrap();
+        let records = fetch_corrections(&conn, 10, None, None, None).unwrap();
         assert_eq!(records.len(), 1);
         assert_eq!(records[0].writing_type, Some("email".to_string()));
     }
@@ -1017,7 +1487,7 @@ mod tests {
         let conn = setup_full_db();
         insert_correction(&conn, "h1", "text", r#"["note"]"#);
 
-        let records = fetch_corrections(&conn, 10).unwrap();
+        let records = fetch_corrections(&conn, 10, None, None, None).unwrap();
         assert_eq!(records[0].writing_type, None);
     }
 
@@ -1099,7 +1569,7 @@ mod tests {
     #[test]
     fn get_corrections_by_document_empty() {
         let conn = setup_full_db();
-        let groups = fetch_corrections_by_document(&conn, 50).unwrap();
+        let groups = fetch_corrections_by_document(&conn, 50, None, None).unwrap();
         assert!(groups.is_empty());
     }
 
@@ -1110,7 +1580,7 @@ mod tests {
         insert_full_correction(&conn, "h2", "doc2", "Article B", "text2", r#"["n2"]"#, 2000);
         insert_full_correction(&conn, "h3", "doc1", "Article A", "text3", r#"["n3"]"#, 1000);
 
-        let groups = fetch_corrections_by_document(&conn, 50).unwrap();
+        let groups = fetch_corrections_by_document(&conn, 50, None, None).unwrap();
         assert_eq!(groups.len(), 2);
         // First group is doc1 (most recent correction at 3000)
         assert_eq!(groups[0].document_id, "doc1");
@@ -1120,6 +1590,212 @@ mod tests {
         assert_eq!(groups[1].corrections.len(), 1);
     }
 
+    // --- category tests ---
+
+    fn correction_input(highlight_id: &str, category: Option<&str>) -> CorrectionInput {
+        CorrectionInput {
+            highlight_id: highlight_id.to_string(),
+            original_text: "text".to_string(),
+            prefix_context: None,
+            suffix_context: None,
+            extended_context: None,
+            notes: vec!["note".to_string()],
+            highlight_color: "yellow".to_string(),
+            writing_type: None,
+            polarity: None,
+            category: category.map(str::to_string),
+        }
+    }
+
+    fn persist_inputs(conn: &Connection, inputs: &[CorrectionInput]) {
+        let source = CorrectionSource {
+            document_id: "doc1",
+            document_title: Some("Doc"),
+            document_source: "file",
+            document_path: None,
+        };
+        for input in inputs {
+            insert_correction_input(conn, input, &source, "sess1", 1000).unwrap();
+        }
+    }
+
+    #[test]
+    fn persist_stores_category() {
+        let conn = setup_full_db();
+        persist_inputs(&conn, &[correction_input("h1", Some("word-choice")), correction_input("h2", None)]);
+
+        let stored: Option<String> = conn
+            .query_row("SELECT category FROM corrections WHERE highlight_id = 'h1'", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(stored.as_deref(), Some("word-choice"));
+        let stored: Option<String> = conn
+            .query_row("SELECT category FROM corrections WHERE highlight_id = 'h2'", [], |r| r.get(0))
+            .unwrap();
+        assert!(stored.is_none());
+    }
+
+    #[test]
+    fn fetched_corrections_expose_category() {
+        let conn = setup_full_db();
+        persist_inputs(&conn, &[correction_input("h1", Some("structure"))]);
+
+        let records = fetch_corrections(&conn, 10, None, None, None).unwrap();
+        assert_eq!(records[0].category.as_deref(), Some("structure"));
+        let flat = fetch_corrections_flat(&conn, 10).unwrap();
+        assert_eq!(flat[0].category.as_deref(), Some("structure"));
+        let groups = fetch_corrections_by_document(&conn, 10, None, None).unwrap();
+        assert_eq!(groups[0].corrections[0].category.as_deref(), Some("structure"));
+    }
+
+    #[test]
+    fn get_all_corrections_filters_by_category() {
+        let conn = setup_full_db();
+        persist_inputs(
+            &conn,
+            &[
+                correction_input("h1", Some("structure")),
+                correction_input("h2", Some("word-choice")),
+                correction_input("h3", Some("structure")),
+                correction_input("h4", None),
+            ],
+        );
+
+        assert_eq!(fetch_corrections(&conn, 10, Some("structure"), None, None).unwrap().len(), 2);
+        assert_eq!(fetch_corrections(&conn, 10, Some("word-choice"), None, None).unwrap().len(), 1);
+        assert_eq!(fetch_corrections(&conn, 10, Some("missing"), None, None).unwrap().len(), 0);
+        assert_eq!(fetch_corrections(&conn, 10, None, None, None).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn corrections_filter_by_created_at_bounds() {
+        let conn = setup_full_db();
+        insert_full_correction(&conn, "h1", "doc1", "Article A", "text1", "[]", 1000);
+        insert_full_correction(&conn, "h2", "doc1", "Article A", "text2", "[]", 2000);
+        insert_full_correction(&conn, "h3", "doc2", "Article B", "text3", "[]", 3000);
+
+        let times = |since, until| -> Vec<i64> {
+            fetch_corrections(&conn, 10, None, since, until)
+                .unwrap()
+                .iter()
+                .map(|r| r.created_at)
+                .collect()
+        };
+        assert_eq!(times(Some(2000), None), vec![3000, 2000]);
+        assert_eq!(times(None, Some(2000)), vec![2000, 1000]);
+        assert_eq!(times(Some(1500), Some(2500)), vec![2000]);
+        assert_eq!(times(None, None).len(), 3);
+
+        let groups = fetch_corrections_by_document(&conn, 10, Some(2500), None).unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].document_id, "doc2");
+        let groups = fetch_corrections_by_document(&conn, 10, None, Some(2000)).unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].corrections.len(), 2);
+    }
+
+    // --- get_correction_context tests ---
+
+    #[test]
+    fn correction_context_marks_original_between_prefix_and_suffix() {
+        let conn = setup_full_db();
+        let mut input = correction_input("h1", None);
+        input.original_text = "very unique".to_string();
+        input.prefix_context = Some("This is ".to_string());
+        input.suffix_context = Some(" in every way.".to_string());
+        input.extended_context = Some("Intro. This is very unique in every way. Outro.".to_string());
+        persist_inputs(&conn, &[input]);
+
+        let ctx = fetch_correction_context(&conn, "h1").unwrap().unwrap();
+        assert_eq!(ctx.window, "This is <mark>very unique</mark> in every way.");
+        assert_eq!(ctx.original_text, "very unique");
+        assert!(ctx.extended_context.unwrap().starts_with("Intro."));
+    }
+
+    #[test]
+    fn correction_context_without_prefix_or_suffix() {
+        let conn = setup_full_db();
+        persist_inputs(&conn, &[correction_input("h1", None)]);
+
+        let ctx = fetch_correction_context(&conn, "h1").unwrap().unwrap();
+        assert_eq!(ctx.window, "<mark>text</mark>");
+        assert!(fetch_correction_context(&conn, "missing").unwrap().is_none());
+    }
+
+    // --- get_top_corrected_documents tests ---
+
+    #[test]
+    fn top_corrected_documents_ranks_by_count() {
+        let conn = setup_full_db();
+        conn.execute_batch(
+            "CREATE TABLE documents (id TEXT PRIMARY KEY, title TEXT, file_path TEXT);
+             INSERT INTO documents (id, title, file_path) VALUES ('doc1', 'Renamed A', '/a.md');
+             INSERT INTO documents (id, title, file_path) VALUES ('doc2', 'B', '/b.md');",
+        )
+        .unwrap();
+        insert_full_correction(&conn, "h1", "doc1", "Article A", "t", "[]", 1000);
+        insert_full_correction(&conn, "h2", "doc2", "B", "t", "[]", 1000);
+        insert_full_correction(&conn, "h3", "doc2", "B", "t", "[]", 2000);
+        insert_full_correction(&conn, "h4", "doc2", "B", "t", "[]", 3000);
+        // doc3 was deleted; its stored title should still show
+        insert_full_correction(&conn, "h5", "doc3", "Gone", "t", "[]", 1000);
+        insert_full_correction(&conn, "h6", "doc3", "Gone", "t", "[]", 1000);
+        // Backfilled rows don't count
+        conn.execute("UPDATE corrections SET session_id = '__backfilled__' WHERE highlight_id = 'h1'", []).unwrap();
+        insert_full_correction(&conn, "h7", "doc1", "Article A", "t", "[]", 500);
+
+        let top = fetch_top_corrected_documents(&conn, 10).unwrap();
+        let summary: Vec<(&str, i64)> = top.iter().map(|d| (d.document_id.as_str(), d.correction_count)).collect();
+        assert_eq!(summary, vec![("doc2", 3), ("doc3", 2), ("doc1", 1)]);
+        assert_eq!(top[1].document_title.as_deref(), Some("Gone"));
+        assert_eq!(top[2].document_title.as_deref(), Some("Renamed A"));
+        assert_eq!(top[2].document_path.as_deref(), Some("/a.md"));
+
+        let limited = fetch_top_corrected_documents(&conn, 1).unwrap();
+        assert_eq!(limited.len(), 1);
+    }
+
+    // --- JSONL rotation tests ---
+
+    #[test]
+    fn jsonl_append_path_rotates_full_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let stem = "corrections-2026-02-23";
+        assert_eq!(jsonl_append_path(dir.path(), stem, 10), dir.path().join("corrections-2026-02-23.jsonl"));
+
+        fs::write(dir.path().join("corrections-2026-02-23.jsonl"), "0123456789").unwrap();
+        assert_eq!(jsonl_append_path(dir.path(), stem, 10), dir.path().join("corrections-2026-02-23-002.jsonl"));
+
+        fs::write(dir.path().join("corrections-2026-02-23-002.jsonl"), "short").unwrap();
+        assert_eq!(jsonl_append_path(dir.path(), stem, 10), dir.path().join("corrections-2026-02-23-002.jsonl"));
+    }
+
+    // --- get_corrections_stats tests ---
+
+    #[test]
+    fn corrections_stats_group_by_type_and_color() {
+        let conn = setup_full_db();
+        for (id, ty) in [("h1", Some("email")), ("h2", Some("email")), ("h3", Some("essay")), ("h4", None)] {
+            insert_full_correction(&conn, id, "doc1", "A", "t", "[]", 1000);
+            conn.execute("UPDATE corrections SET writing_type = ?1 WHERE highlight_id = ?2", rusqlite::params![ty, id])
+                .unwrap();
+        }
+        conn.execute("UPDATE corrections SET highlight_color = 'blue' WHERE highlight_id = 'h3'", []).unwrap();
+        insert_full_correction(&conn, "h5", "doc1", "A", "t", "[]", 1000);
+        conn.execute(
+            "UPDATE corrections SET session_id = '__backfilled__', writing_type = 'email' WHERE highlight_id = 'h5'",
+            [],
+        )
+        .unwrap();
+
+        let stats = fetch_corrections_stats(&conn).unwrap();
+        assert_eq!(stats.total, 4);
+        assert_eq!(stats.by_writing_type.get("email"), Some(&2));
+        assert_eq!(stats.by_writing_type.get("essay"), Some(&1));
+        assert_eq!(stats.by_writing_type.get("unknown"), Some(&1));
+        assert_eq!(stats.by_highlight_color.get("yellow"), Some(&3));
+        assert_eq!(stats.by_highlight_color.get("blue"), Some(&1));
+    }
+
     // --- update_correction_writing_type tests ---
 
     #[test]
@@ -1186,6 +1862,85 @@ mod tests {
         assert_eq!(export.corrections[1].original_text, "bad text");
     }
 
+    #[test]
+    fn build_export_timestamp_is_rfc3339_utc() {
+        let conn = setup_full_db();
+        let export = build_corrections_export(&conn).unwrap();
+        // Same "YYYY-MM-DDTHH:MM:SSZ" shape the export has always used
+        assert_eq!(export.exported_at.len(), 20);
+        assert!(export.exported_at.ends_with('Z'));
+        chrono::DateTime::parse_from_rfc3339(&export.exported_at).unwrap();
+    }
+
+    // --- correction_rule_links tests ---
+
+    fn insert_rule(conn: &Connection, id: &str) {
+        conn.execute(
+            "INSERT INTO writing_rules (id, writing_type, category, rule_text, created_at, updated_at)
+             VALUES (?1, 'general', 'clarity', ?2, 1000, 1000)",
+            rusqlite::params![id, format!("Rule {id}")],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn link_correction_to_rule_is_idempotent_and_validated() {
+        let conn = setup_full_db();
+        insert_rule(&conn, "r1");
+        insert_correction(&conn, "h1", "utilize", r#"["use"]"#);
+
+        assert!(link_correction_to_rule_inner(&conn, "h1", "r1").unwrap());
+        assert!(!link_correction_to_rule_inner(&conn, "h1", "r1").unwrap());
+        // Only the first link counts as a new signal
+        let signal_count: i64 = conn
+            .query_row("SELECT signal_count FROM writing_rules WHERE id = 'r1'", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(signal_count, 2);
+        assert!(link_correction_to_rule_inner(&conn, "missing", "r1").is_err());
+        assert!(link_correction_to_rule_inner(&conn, "h1", "missing").is_err());
+    }
+
+    #[test]
+    fn rule_examples_return_linked_corrections() {
+        let conn = setup_full_db();
+        insert_rule(&conn, "r1");
+        insert_rule(&conn, "r2");
+        insert_full_correction(&conn, "h1", "doc1", "Essay", "utilize", r#"["use"]"#, 1000);
+        insert_full_correction(&conn, "h2", "doc1", "Essay", "in order to", r#"["to"]"#, 2000);
+        insert_full_correction(&conn, "h3", "doc1", "Essay", "unrelated", r#"["x"]"#, 3000);
+        link_correction_to_rule_inner(&conn, "h1", "r1").unwrap();
+        link_correction_to_rule_inner(&conn, "h2", "r1").unwrap();
+        link_correction_to_rule_inner(&conn, "h3", "r2").unwrap();
+
+        let examples = fetch_rule_examples(&conn, "r1").unwrap();
+        assert_eq!(examples.len(), 2);
+        assert_eq!(examples[0].original_text, "in order to");
+        assert_eq!(examples[0].notes, vec!["to"]);
+        assert_eq!(examples[1].highlight_id, "h1");
+        assert_eq!(examples[1].document_title.as_deref(), Some("Essay"));
+        assert!(fetch_rule_examples(&conn, "r3").unwrap().is_empty());
+    }
+
+    #[test]
+    fn exportable_count_excludes_backfilled_and_synthesized() {
+        let conn = setup_full_db();
+        insert_full_correction(&conn, "h1", "doc1", "Doc", "text1", r#"["n1"]"#, 1000);
+        insert_full_correction(&conn, "h2", "doc1", "Doc", "text2", r#"["n2"]"#, 2000);
+        insert_full_correction(&conn, "h3", "doc1", "Doc", "text3", r#"["n3"]"#, 3000);
+        conn.execute("UPDATE corrections SET synthesized_at = 1 WHERE highlight_id = 'h3'", []).unwrap();
+        conn.execute(
+            "INSERT INTO corrections
+                (id, highlight_id, document_id, session_id, original_text, notes_json,
+                 document_title, document_source, highlight_color, created_at, updated_at)
+             VALUES ('bf1', 'hbf', 'doc1', '__backfilled__', 'legacy text', '[\"legacy\"]', 'Doc', 'file', 'yellow', 500, 500)",
+            [],
+        )
+        .unwrap();
+
+        assert_eq!(count_exportable_corrections(&conn).unwrap(), 2);
+        assert_eq!(build_corrections_export(&conn).unwrap().total_count, 2);
+    }
+
     #[test]
     fn export_does_not_mark_synthesized() {
         let conn = setup_full_db();
@@ -1449,7 +2204,7 @@ mod tests {
             [],
         ).unwrap();
 
-        let records = fetch_corrections(&conn, 10).unwrap();
+        let records = fetch_corrections(&conn, 10, None, None, None).unwrap();
         assert_eq!(records.len(), 1);
         assert_eq!(records[0].polarity, Some("positive".to_string()));
     }
@@ -1459,7 +2214,7 @@ mod tests {
         let conn = setup_full_db();
         insert_correction(&conn, "h1", "text", r#"["note"]"#);
 
-        let records = fetch_corrections(&conn, 10).unwrap();
+        let records = fetch_corrections(&conn, 10, None, None, None).unwrap();
         assert_eq!(records[0].polarity, None);
     }
 
@@ -1696,4 +2451,79 @@ mod tests {
             .unwrap();
         assert!(after.is_some());
     }
+
+    #[test]
+    fn csv_range_export_writes_only_rows_in_range() {
+        let conn = setup_full_db();
+        insert_full_correction(&conn, "h0", "doc1", "Doc", "before", r#"["n0"]"#, 999);
+        insert_full_correction(&conn, "h1", "doc1", "Doc", "at since", r#"["n1"]"#, 1000);
+        insert_full_correction(&conn, "h2", "doc1", "Doc, quoted \"title\"", "inside", r#"["a","b"]"#, 1500);
+        insert_full_correction(&conn, "h3", "doc1", "Doc", "at until", r#"["n3"]"#, 2000);
+        insert_full_correction(&conn, "h4", "doc1", "Doc", "after", r#"["n4"]"#, 2001);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("range.csv");
+        let count = export_corrections_csv_range_inner(&conn, 1000, 2000, &path).unwrap();
+        assert_eq!(count, 3);
+
+        let csv = fs::read_to_string(&path).unwrap();
+        assert!(csv.starts_with("created_at,document_id,"));
+        assert!(csv.contains("at since"));
+        assert!(csv.contains("\"Doc, quoted \"\"title\"\"\""));
+        assert!(csv.contains("\"a\nb\""));
+        assert!(csv.contains("at until"));
+        assert!(csv.find("at since").unwrap() < csv.find("at until").unwrap());
+        assert!(!csv.contains("before"));
+        assert!(!csv.contains("after"));
+        // Nothing is cleared or marked
+        assert_eq!(count_corrections(&conn).unwrap(), 5);
+    }
+
+    #[test]
+    fn document_export_contains_only_that_document() {
+        let conn = setup_full_db();
+        insert_full_correction(&conn, "h1", "doc1", "My Essay", "mine", r#"["n1"]"#, 1000);
+        insert_full_correction(&conn, "h2", "doc1", "My Essay", "also mine", r#"["n2"]"#, 2000);
+        insert_full_correction(&conn, "h3", "doc2", "Other", "theirs", r#"["n3"]"#, 3000);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = export_document_corrections_inner(&conn, "doc1", Some(dir.path())).unwrap();
+        assert_eq!(path, dir.path().join("My_Essay.corrections.json"));
+
+        let exported: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(exported["documentId"], "doc1");
+        let texts: Vec<&str> = exported["corrections"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["originalText"].as_str().unwrap())
+            .collect();
+        assert_eq!(texts, vec!["also mine", "mine"]);
+
+        assert!(export_document_corrections_inner(&conn, "doc3", Some(dir.path())).is_err());
+    }
+
+    #[test]
+    fn document_export_to_file_leaves_other_documents_untouched() {
+        let conn = setup_full_db();
+        insert_full_correction(&conn, "h1", "doc1", "Mine", "mine", r#"["n1"]"#, 1000);
+        insert_full_correction(&conn, "h2", "doc2", "Other", "theirs", r#"["n2"]"#, 2000);
+        insert_full_correction(&conn, "h3", "doc2", "Other", "theirs too", r#"["n3"]"#, 3000);
+
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("out").join("mine.json");
+        let path = export_document_corrections_inner(&conn, "doc1", Some(&target)).unwrap();
+        assert_eq!(path, target);
+
+        let exported: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(exported["totalCount"], 1);
+        assert_eq!(exported["corrections"][0]["highlightId"], "h1");
+
+        // Nothing is deleted or marked, for either document
+        assert_eq!(count_corrections(&conn).unwrap(), 3);
+        let other = fetch_correction_groups(&conn, -1, Some("doc2"), None, None).unwrap();
+        assert_eq!(other[0].corrections.len(), 2);
+        assert!(other[0].corrections.iter().all(|c| c.synthesized_at.is_none()));
+        assert_eq!(fetch_exported_corrections(&conn, Some("doc1"), true).unwrap().len(), 1);
+    }
 }
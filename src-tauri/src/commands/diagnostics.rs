@@ -0,0 +1,304 @@
+use crate::commands::keep_local::{keep_local_health, HttpClient};
+use crate::db::migrations::{data_dir, DbPool};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckResult {
+    pub ok: bool,
+    pub detail: Option<String>,
+}
+
+impl CheckResult {
+    fn pass() -> Self {
+        CheckResult { ok: true, detail: None }
+    }
+
+    fn fail(detail: impl Into<String>) -> Self {
+        CheckResult {
+            ok: false,
+            detail: Some(detail.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfCheckReport {
+    pub database: CheckResult,
+    pub fts5: CheckResult,
+    pub data_dir_writable: CheckResult,
+    pub keep_local: CheckResult,
+    pub table_counts: BTreeMap<String, i64>,
+}
+
+/// A row that references a missing parent row.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityViolation {
+    pub table: String,
+    pub rowid: Option<i64>,
+    pub parent: String,
+    pub detail: String,
+}
+
+/// References checked explicitly, on top of `PRAGMA foreign_key_check`, so
+/// they are caught even in databases whose tables predate the FK clauses.
+const REFERENCE_CHECKS: &[(&str, &str, &str)] = &[
+    ("open_tabs", "document_id", "documents"),
+    ("highlights", "document_id", "documents"),
+    ("margin_notes", "highlight_id", "highlights"),
+];
+
+// === Inner functions (testable with &Connection) ===
+
+fn check_integrity(conn: &Connection) -> CheckResult {
+    match conn.query_row("PRAGMA integrity_check", [], |row| row.get::<_, String>(0)) {
+        Ok(result) if result == "ok" => CheckResult::pass(),
+        Ok(result) => CheckResult::fail(result),
+        Err(e) => CheckResult::fail(e.to_string()),
+    }
+}
+
+fn table_exists(conn: &Connection, name: &str) -> Result<bool, String> {
+    conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+        [name],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+fn find_integrity_violations(conn: &Connection) -> Result<Vec<IntegrityViolation>, String> {
+    let mut stmt = conn
+        .prepare("PRAGMA foreign_key_check")
+        .map_err(|e| e.to_string())?;
+    let mut violations: Vec<IntegrityViolation> = stmt
+        .query_map([], |row| {
+            let table: String = row.get(0)?;
+            let parent: String = row.get(2)?;
+            Ok(IntegrityViolation {
+                detail: format!("{table} row references a missing {parent} row"),
+                table,
+                rowid: row.get(1)?,
+                parent,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    for (table, column, parent) in REFERENCE_CHECKS {
+        if !table_exists(conn, table)? || !table_exists(conn, parent)? {
+            continue;
+        }
+        let sql = format!(
+            "SELECT c.rowid, c.{column} FROM {table} c
+             WHERE NOT EXISTS (SELECT 1 FROM {parent} p WHERE p.id = c.{column})
+             ORDER BY c.rowid"
+        );
+        let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+        let orphans = stmt
+            .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        for (rowid, missing) in orphans {
+            let already_reported = violations
+                .iter()
+                .any(|v| v.table == *table && v.rowid == Some(rowid) && v.parent == *parent);
+            if !already_reported {
+                violations.push(IntegrityViolation {
+                    table: table.to_string(),
+                    rowid: Some(rowid),
+                    parent: parent.to_string(),
+                    detail: format!("{table}.{column} '{missing}' does not exist in {parent}"),
+                });
+            }
+        }
+    }
+
+    Ok(violations)
+}
+
+fn check_fts5(conn: &Connection) -> CheckResult {
+    let probe = conn.execute_batch(
+        "CREATE VIRTUAL TABLE temp.fts5_probe USING fts5(content);
+         DROP TABLE temp.fts5_probe;",
+    );
+    match probe {
+        Ok(()) => CheckResult::pass(),
+        Err(e) => CheckResult::fail(e.to_string()),
+    }
+}
+
+fn check_dir_writable(dir: &Path) -> CheckResult {
+    let probe = dir.join(format!(".self-check-{}", uuid::Uuid::new_v4()));
+    match std::fs::write(&probe, b"ok") {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+            CheckResult::pass()
+        }
+        Err(e) => CheckResult::fail(format!("{}: {}", dir.display(), e)),
+    }
+}
+
+/// Row counts for every user table. FTS shadow tables are skipped.
+fn fetch_table_counts(conn: &Connection) -> Result<BTreeMap<String, i64>, String> {
+    let mut stmt = conn
+        .prepare("SELECT name, sql FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'")
+        .map_err(|e| e.to_string())?;
+    let tables: Vec<(String, Option<String>)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let virtual_prefixes: Vec<String> = tables
+        .iter()
+        .filter(|(_, sql)| sql.as_deref().is_some_and(|s| s.starts_with("CREATE VIRTUAL TABLE")))
+        .map(|(name, _)| format!("{name}_"))
+        .collect();
+
+    let mut counts = BTreeMap::new();
+    for (name, _) in &tables {
+        if virtual_prefixes.iter().any(|p| name.starts_with(p.as_str())) {
+            continue;
+        }
+        let count: i64 = conn
+            .query_row(&format!("SELECT COUNT(*) FROM \"{}\"", name.replace('"', "\"\"")), [], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        counts.insert(name.clone(), count);
+    }
+    Ok(counts)
+}
+
+/// Each check records its own failure; none aborts the report.
+fn build_report(conn: &Connection, dir: Option<&Path>, keep_local: CheckResult) -> SelfCheckReport {
+    let table_counts = fetch_table_counts(conn).unwrap_or_else(|e| {
+        eprintln!("self_check: failed to count tables: {e}");
+        BTreeMap::new()
+    });
+    SelfCheckReport {
+        database: check_integrity(conn),
+        fts5: check_fts5(conn),
+        data_dir_writable: match dir {
+            Some(dir) => check_dir_writable(dir),
+            None => CheckResult::fail("Could not determine data directory"),
+        },
+        keep_local,
+        table_counts,
+    }
+}
+
+// === Tauri command handlers ===
+
+#[tauri::command]
+pub async fn self_check(
+    state: tauri::State<'_, DbPool>,
+    client: tauri::State<'_, HttpClient>,
+) -> Result<SelfCheckReport, String> {
+    let keep_local = match keep_local_health(client).await {
+        Ok(health) if health.ok => CheckResult::pass(),
+        Ok(_) => CheckResult::fail("keep-local reported unhealthy"),
+        Err(e) => CheckResult::fail(e),
+    };
+    let dir = data_dir().ok();
+
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    Ok(build_report(&conn, dir.as_deref(), keep_local))
+}
+
+/// Reports dangling references, e.g. after a bulk import. An empty list means
+/// the database is consistent.
+#[tauri::command]
+pub async fn verify_integrity(state: tauri::State<'_, DbPool>) -> Result<Vec<IntegrityViolation>, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    find_integrity_violations(&conn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE documents (id TEXT PRIMARY KEY, title TEXT);
+             CREATE TABLE highlights (id TEXT PRIMARY KEY, document_id TEXT NOT NULL);
+             CREATE VIRTUAL TABLE documents_fts USING fts5(title, content, document_id UNINDEXED);
+             INSERT INTO documents (id, title) VALUES ('d1', 'One'), ('d2', 'Two');
+             INSERT INTO highlights (id, document_id) VALUES ('h1', 'd1');",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn healthy_setup_reports_all_checks_ok() {
+        let conn = setup_db();
+        let dir = tempfile::tempdir().unwrap();
+
+        let report = build_report(&conn, Some(dir.path()), CheckResult::pass());
+        assert!(report.database.ok);
+        assert!(report.fts5.ok);
+        assert!(report.data_dir_writable.ok);
+        assert!(report.keep_local.ok);
+        assert_eq!(report.table_counts.get("documents"), Some(&2));
+        assert_eq!(report.table_counts.get("highlights"), Some(&1));
+        assert_eq!(report.table_counts.get("documents_fts"), Some(&0));
+        assert!(!report.table_counts.keys().any(|k| k.starts_with("documents_fts_")));
+        // The writability probe cleans up after itself
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn failing_parts_do_not_fail_the_report() {
+        let conn = setup_db();
+        let report = build_report(
+            &conn,
+            Some(Path::new("/nonexistent/margin-self-check")),
+            CheckResult::fail("keep-local server unreachable"),
+        );
+        assert!(report.database.ok);
+        assert!(!report.data_dir_writable.ok);
+        assert!(!report.keep_local.ok);
+        assert_eq!(report.table_counts.len(), 3);
+    }
+
+    #[test]
+    fn integrity_violations_report_dangling_references() {
+        let conn = Connection::open_in_memory().unwrap();
+        // Simulate a bulk import that ran with foreign keys disabled
+        conn.execute_batch(
+            "PRAGMA foreign_keys = OFF;
+             CREATE TABLE documents (id TEXT PRIMARY KEY);
+             CREATE TABLE highlights (
+                id TEXT PRIMARY KEY,
+                document_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE
+             );
+             CREATE TABLE margin_notes (id TEXT PRIMARY KEY, highlight_id TEXT NOT NULL);
+             CREATE TABLE open_tabs (id TEXT PRIMARY KEY, document_id TEXT NOT NULL);
+             INSERT INTO documents (id) VALUES ('d1');
+             INSERT INTO highlights (id, document_id) VALUES ('h1', 'd1'), ('h2', 'gone');
+             INSERT INTO margin_notes (id, highlight_id) VALUES ('n1', 'h1'), ('n2', 'missing');
+             INSERT INTO open_tabs (id, document_id) VALUES ('t1', 'd1');",
+        )
+        .unwrap();
+
+        let violations = find_integrity_violations(&conn).unwrap();
+        assert_eq!(violations.len(), 2, "{violations:?}");
+        // The FK violation is reported once, not again by the explicit check
+        assert_eq!(violations[0].table, "highlights");
+        assert_eq!(violations[0].parent, "documents");
+        assert_eq!(violations[1].table, "margin_notes");
+        assert!(violations[1].detail.contains("'missing'"));
+
+        conn.execute("DELETE FROM margin_notes WHERE id = 'n2'", []).unwrap();
+        conn.execute("DELETE FROM highlights WHERE id = 'h2'", []).unwrap();
+        assert!(find_integrity_violations(&conn).unwrap().is_empty());
+    }
+}
@@ -1,18 +1,109 @@
+use crate::commands::now_millis;
+use crate::commands::search::{remove_document_index_inner, set_indexed_title};
 use crate::db::migrations::DbPool;
 use crate::db::models::Document;
+use crate::front_matter::parse_front_matter;
 use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
 use std::path::Path;
 use uuid::Uuid;
 
+/// Files larger than this are not read for word counting.
+const MAX_TEXT_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Matches the frontend's `countWords`: whitespace-separated tokens.
+pub(crate) fn count_words(text: &str) -> i64 {
+    text.split_whitespace().count() as i64
+}
+
+/// Words of prose only: a leading front matter block and fenced code blocks
+/// (``` or ~~~) are skipped. An unclosed fence runs to the end.
+pub(crate) fn count_prose_words(content: &str) -> i64 {
+    let (_, body) = parse_front_matter(content);
+    let mut fence: Option<&str> = None;
+    let mut words = 0;
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+        match fence {
+            Some(marker) => {
+                if trimmed.starts_with(marker) {
+                    fence = None;
+                }
+            }
+            None if trimmed.starts_with("```") => fence = Some("```"),
+            None if trimmed.starts_with("~~~") => fence = Some("~~~"),
+            None => words += count_words(line),
+        }
+    }
+    words
+}
+
+/// Reads a file as text, returning `None` for oversized or binary (NUL-containing) files.
+pub(crate) fn read_text_file_guarded(path: &Path) -> Result<Option<String>, String> {
+    let meta = std::fs::metadata(path).map_err(|e| format!("Failed to stat '{}': {}", path.display(), e))?;
+    if meta.len() > MAX_TEXT_FILE_BYTES {
+        return Ok(None);
+    }
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    if bytes.contains(&0) {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(&bytes).into_owned()))
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagCount {
+    pub tag: String,
+    pub count: i64,
+}
+
 // === Inner functions (testable with &Connection) ===
 
-fn fetch_recent_documents(conn: &Connection, limit: i64) -> Result<Vec<Document>, String> {
+/// Pinned documents come first, most recently pinned on top; archived
+/// documents are left out unless `include_archived`.
+fn fetch_recent_documents(conn: &Connection, limit: i64, include_archived: bool) -> Result<Vec<Document>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, source, file_path, keep_local_id, title, author, url,
+                    word_count, last_opened_at, created_at, reading_progress
+             FROM documents
+             WHERE ?2 OR archived = 0
+             ORDER BY pinned_at IS NULL, pinned_at DESC, last_opened_at DESC
+             LIMIT ?1",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let results = stmt
+        .query_map(rusqlite::params![limit, include_archived], Document::from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string());
+    results
+}
+
+fn fetch_document(conn: &Connection, document_id: &str) -> Result<Document, String> {
+    conn.query_row(
+        "SELECT id, source, file_path, keep_local_id, title, author, url,
+                word_count, last_opened_at, created_at, reading_progress
+         FROM documents WHERE id = ?1",
+        [document_id],
+        Document::from_row,
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => format!("Document not found: {document_id}"),
+        e => e.to_string(),
+    })
+}
+
+fn fetch_recently_created_documents(conn: &Connection, limit: i64) -> Result<Vec<Document>, String> {
     let mut stmt = conn
         .prepare(
             "SELECT id, source, file_path, keep_local_id, title, author, url,
-                    word_count, last_opened_at, created_at
+                    word_count, last_opened_at, created_at, reading_progress
              FROM documents
-             ORDER BY last_opened_at DESC
+             ORDER BY created_at DESC
              LIMIT ?1",
         )
         .map_err(|e| e.to_string())?;
@@ -25,32 +116,209 @@ fn fetch_recent_documents(conn: &Connection, limit: i64) -> Result<Vec<Document>
     results
 }
 
-fn upsert_document_inner(conn: &Connection, mut doc: Document) -> Result<Document, String> {
-    let existing_id: Option<String> = if let Some(ref fp) = doc.file_path {
+pub(crate) fn fetch_file_document_paths(conn: &Connection) -> Result<Vec<(String, String)>, String> {
+    let mut stmt = conn
+        .prepare("SELECT id, file_path FROM documents WHERE source = 'file' AND file_path IS NOT NULL")
+        .map_err(|e| e.to_string())?;
+    let results = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string());
+    results
+}
+
+/// File I/O only — no connection, so callers can run it off the DB lock.
+/// Unreadable, oversized, and binary files are left out.
+pub(crate) fn compute_word_counts(docs: &[(String, String)]) -> Vec<(String, i64)> {
+    docs.iter()
+        .filter_map(|(id, path)| match read_text_file_guarded(Path::new(path)) {
+            Ok(Some(text)) => Some((id.clone(), count_prose_words(&text))),
+            Ok(None) => None,
+            Err(e) => {
+                eprintln!("recompute_word_counts: {e}");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Returns how many documents actually changed.
+fn apply_word_counts(conn: &Connection, counts: &[(String, i64)]) -> Result<usize, String> {
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let mut updated = 0;
+    for (id, count) in counts {
+        updated += tx
+            .execute(
+                "UPDATE documents SET word_count = ?1 WHERE id = ?2 AND COALESCE(word_count, -1) != ?1",
+                rusqlite::params![count, id],
+            )
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(updated)
+}
+
+/// Pinning an already-pinned document keeps its original `pinned_at`.
+fn set_document_pinned_inner(conn: &Connection, id: &str, pinned: bool, now: i64) -> Result<(), String> {
+    let updated = conn
+        .execute(
+            "UPDATE documents
+             SET pinned_at = CASE WHEN ?1 THEN COALESCE(pinned_at, ?3) END
+             WHERE id = ?2",
+            rusqlite::params![pinned as i64, id, now],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("Document not found: {id}"));
+    }
+    Ok(())
+}
+
+/// Progress is clamped to 0.0–1.0; NaN counts as the start.
+fn set_reading_progress_inner(conn: &Connection, id: &str, progress: f64) -> Result<f64, String> {
+    let progress = if progress.is_nan() { 0.0 } else { progress.clamp(0.0, 1.0) };
+    let updated = conn
+        .execute(
+            "UPDATE documents SET reading_progress = ?1 WHERE id = ?2",
+            rusqlite::params![progress, id],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("Document not found: {id}"));
+    }
+    Ok(progress)
+}
+
+/// Archiving only hides a document from recents; search still finds it.
+fn set_document_archived_inner(conn: &Connection, id: &str, archived: bool) -> Result<(), String> {
+    let updated = conn
+        .execute(
+            "UPDATE documents SET archived = ?1 WHERE id = ?2",
+            rusqlite::params![archived as i64, id],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("Document not found: {id}"));
+    }
+    Ok(())
+}
+
+/// Changes only the display title (row and search index); the file keeps its name.
+/// The title is marked custom so later upserts and front matter don't replace it.
+fn set_document_title_inner(conn: &Connection, document_id: &str, title: &str) -> Result<Document, String> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err("Title cannot be empty".to_string());
+    }
+
+    let doc = conn
+        .query_row(
+            "UPDATE documents SET title = ?1, custom_title = 1 WHERE id = ?2
+             RETURNING id, source, file_path, keep_local_id, title, author, url,
+                       word_count, last_opened_at, created_at, reading_progress",
+            rusqlite::params![title, document_id],
+            Document::from_row,
+        )
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => format!("Document not found: {document_id}"),
+            e => e.to_string(),
+        })?;
+
+    set_indexed_title(conn, document_id, title)?;
+    Ok(doc)
+}
+
+/// What `upsert_document` takes from the file itself rather than the caller.
+pub(crate) struct FileMetadata {
+    word_count: i64,
+    title: Option<String>,
+    author: Option<String>,
+}
+
+/// Reads the document's file, if any. `None` for unreadable files, which are
+/// then stored as sent. Does no DB work, so callers can run it unlocked.
+fn read_file_metadata(doc: &Document) -> Option<FileMetadata> {
+    let content = read_text_file_guarded(Path::new(doc.file_path.as_deref()?)).ok()??;
+    Some(file_metadata_from_content(&content))
+}
+
+/// `read_file_metadata` for content the caller has already read.
+pub(crate) fn file_metadata_from_content(content: &str) -> FileMetadata {
+    let fm = parse_front_matter(content).0;
+    FileMetadata {
+        word_count: count_prose_words(content),
+        title: fm.as_ref().and_then(|fm| fm.title.clone()),
+        author: fm.and_then(|fm| fm.author),
+    }
+}
+
+/// Front matter `title`/`author` win over what the caller sent, so a file's
+/// own metadata shows up in the library, and `word_count` is recounted with
+/// `count_prose_words` like every other path. The title is left alone when
+/// the user has set a `custom_title`.
+fn apply_file_metadata(doc: &mut Document, meta: Option<FileMetadata>, custom_title: bool) {
+    let Some(meta) = meta else {
+        return;
+    };
+    doc.word_count = meta.word_count;
+    if meta.title.is_some() && !custom_title {
+        doc.title = meta.title;
+    }
+    if meta.author.is_some() {
+        doc.author = meta.author;
+    }
+}
+
+/// Deletes a document row; highlights, notes and tabs cascade via FKs.
+/// The file on disk, if any, is left alone.
+fn delete_document_inner(conn: &Connection, document_id: &str) -> Result<(), String> {
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let deleted = tx
+        .execute("DELETE FROM documents WHERE id = ?1", [document_id])
+        .map_err(|e| e.to_string())?;
+    if deleted == 0 {
+        return Err(format!("Document not found: {document_id}"));
+    }
+    remove_document_index_inner(&tx, document_id)?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// A `custom_title` set through `set_document_title` is kept over both the
+/// sent title and front matter; the returned document carries the stored one.
+pub(crate) fn upsert_document_inner(conn: &Connection, doc: Document) -> Result<Document, String> {
+    let meta = read_file_metadata(&doc);
+    upsert_document_with_metadata(conn, doc, meta)
+}
+
+pub(crate) fn upsert_document_with_metadata(conn: &Connection, mut doc: Document, meta: Option<FileMetadata>) -> Result<Document, String> {
+    let existing: Option<(String, bool)> = if let Some(ref fp) = doc.file_path {
         conn.query_row(
-            "SELECT id FROM documents WHERE file_path = ?1",
+            "SELECT id, custom_title FROM documents WHERE file_path = ?1",
             rusqlite::params![fp],
-            |row| row.get(0),
+            |row| Ok((row.get(0)?, row.get(1)?)),
         )
         .ok()
     } else if let Some(ref kl_id) = doc.keep_local_id {
         conn.query_row(
-            "SELECT id FROM documents WHERE keep_local_id = ?1",
+            "SELECT id, custom_title FROM documents WHERE keep_local_id = ?1",
             rusqlite::params![kl_id],
-            |row| row.get(0),
+            |row| Ok((row.get(0)?, row.get(1)?)),
         )
         .ok()
     } else {
         None
     };
+    apply_file_metadata(&mut doc, meta, existing.as_ref().is_some_and(|(_, custom)| *custom));
 
-    if let Some(eid) = existing_id {
+    if let Some((eid, _)) = existing {
         doc.id = eid;
     } else if doc.id.is_empty() {
         doc.id = Uuid::new_v4().to_string();
     }
 
-    conn.execute(
+    // Reading progress is only changed by `set_reading_progress`; hand back the stored value
+    (doc.reading_progress, doc.title) = conn.query_row(
         "INSERT INTO documents
             (id, source, file_path, keep_local_id, title, author, url,
              word_count, last_opened_at, created_at)
@@ -59,12 +327,13 @@ fn upsert_document_inner(conn: &Connection, mut doc: Document) -> Result<Documen
             source = excluded.source,
             file_path = excluded.file_path,
             keep_local_id = excluded.keep_local_id,
-            title = excluded.title,
+            title = CASE WHEN documents.custom_title THEN documents.title ELSE excluded.title END,
             author = excluded.author,
             url = excluded.url,
             word_count = excluded.word_count,
             last_opened_at = excluded.last_opened_at,
-            created_at = excluded.created_at",
+            created_at = excluded.created_at
+         RETURNING reading_progress, title",
         rusqlite::params![
             doc.id,
             doc.source,
@@ -77,20 +346,162 @@ fn upsert_document_inner(conn: &Connection, mut doc: Document) -> Result<Documen
             doc.last_opened_at,
             doc.created_at,
         ],
+        |row| Ok((row.get(0)?, row.get(1)?)),
     )
     .map_err(|e| e.to_string())?;
 
     Ok(doc)
 }
 
+/// Tags are compared and displayed in their `normalize_tag` form, so "Rust"
+/// and " rust" count as one tag. Folded in Rust rather than with SQLite's
+/// ASCII-only `LOWER`, so "Café" and "café" group too.
+fn fetch_tag_counts(conn: &Connection) -> Result<Vec<TagCount>, String> {
+    let mut stmt = conn
+        .prepare("SELECT DISTINCT document_id, tag FROM document_tags")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut documents_by_tag: BTreeMap<String, HashSet<String>> = BTreeMap::new();
+    for (document_id, tag) in rows {
+        if let Ok(tag) = normalize_tag(&tag) {
+            documents_by_tag.entry(tag).or_default().insert(document_id);
+        }
+    }
+
+    // Stable sort keeps the map's alphabetical order among equal counts
+    let mut counts: Vec<TagCount> = documents_by_tag
+        .into_iter()
+        .map(|(tag, docs)| TagCount { tag, count: docs.len() as i64 })
+        .collect();
+    counts.sort_by_key(|t| std::cmp::Reverse(t.count));
+    Ok(counts)
+}
+
+/// Trimmed, lowercased (Unicode-aware) form that tags are stored in.
+fn normalize_tag(tag: &str) -> Result<String, String> {
+    let tag = tag.trim().to_lowercase();
+    if tag.is_empty() {
+        return Err("Tag cannot be empty".to_string());
+    }
+    Ok(tag)
+}
+
+/// Adding a tag the document already has is a no-op.
+fn add_document_tag_inner(conn: &Connection, document_id: &str, tag: &str, now: i64) -> Result<(), String> {
+    let tag = normalize_tag(tag)?;
+    conn.execute(
+        "INSERT OR IGNORE INTO document_tags (id, document_id, tag, created_at) VALUES (?1, ?2, ?3, ?4)",
+        rusqlite::params![Uuid::new_v4().to_string(), document_id, tag, now],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn remove_document_tag_inner(conn: &Connection, document_id: &str, tag: &str) -> Result<(), String> {
+    let tag = normalize_tag(tag)?;
+    conn.execute(
+        "DELETE FROM document_tags WHERE document_id = ?1 AND tag = ?2",
+        rusqlite::params![document_id, tag],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn fetch_document_tags(conn: &Connection, document_id: &str) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT tag FROM document_tags WHERE document_id = ?1 ORDER BY tag")
+        .map_err(|e| e.to_string())?;
+
+    let results = stmt
+        .query_map([document_id], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string());
+    results
+}
+
+/// Documents carrying `tag`, most recently opened first.
+fn fetch_documents_by_tag(conn: &Connection, tag: &str) -> Result<Vec<Document>, String> {
+    let tag = normalize_tag(tag)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT d.id, d.source, d.file_path, d.keep_local_id, d.title, d.author, d.url,
+                    d.word_count, d.last_opened_at, d.created_at, d.reading_progress
+             FROM documents d
+             JOIN document_tags t ON t.document_id = d.id
+             WHERE t.tag = ?1
+             ORDER BY d.last_opened_at DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let results = stmt
+        .query_map([tag], Document::from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string());
+    results
+}
+
+/// One-time cleanup for tags written before normalization: folds every tag to
+/// its trimmed lowercase form, keeping the earliest row when a document holds
+/// several spellings. Returns the number of rows collapsed.
+fn normalize_all_tags_inner(conn: &Connection) -> Result<usize, String> {
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+
+    let rows: Vec<(String, String, String)> = {
+        let mut stmt = tx
+            .prepare("SELECT id, document_id, tag FROM document_tags ORDER BY created_at, id")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        rows
+    };
+
+    // Duplicates go first so the renames can't hit UNIQUE(document_id, tag)
+    let mut kept = HashSet::new();
+    let mut renames = Vec::new();
+    let mut collapsed = 0;
+    for (id, document_id, tag) in rows {
+        let Ok(normalized) = normalize_tag(&tag) else {
+            continue;
+        };
+        if !kept.insert((document_id, normalized.clone())) {
+            tx.execute("DELETE FROM document_tags WHERE id = ?1", [&id])
+                .map_err(|e| e.to_string())?;
+            collapsed += 1;
+        } else if normalized != tag {
+            renames.push((id, normalized));
+        }
+    }
+    for (id, tag) in renames {
+        tx.execute("UPDATE document_tags SET tag = ?1 WHERE id = ?2", rusqlite::params![tag, id])
+            .map_err(|e| e.to_string())?;
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(collapsed)
+}
+
 // === Tauri command handlers ===
 
 #[tauri::command]
-pub async fn get_recent_documents(state: tauri::State<'_, DbPool>, limit: Option<i64>) -> Result<Vec<Document>, String> {
+pub async fn get_recent_documents(
+    state: tauri::State<'_, DbPool>,
+    limit: Option<i64>,
+    include_archived: Option<bool>,
+) -> Result<Vec<Document>, String> {
     // Drop the DB lock before doing filesystem I/O to avoid blocking other commands
     let docs = {
         let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
-        fetch_recent_documents(&conn, limit.unwrap_or(20))?
+        fetch_recent_documents(&conn, limit.unwrap_or(20), include_archived.unwrap_or(false))?
     };
     // Filter out file-backed documents whose files no longer exist on disk.
     // May return fewer than `limit` results — acceptable for a recent docs list.
@@ -103,10 +514,114 @@ pub async fn get_recent_documents(state: tauri::State<'_, DbPool>, limit: Option
         .collect())
 }
 
+#[tauri::command]
+pub async fn get_document(state: tauri::State<'_, DbPool>, document_id: String) -> Result<Document, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    fetch_document(&conn, &document_id)
+}
+
+#[tauri::command]
+pub async fn get_recently_created_documents(state: tauri::State<'_, DbPool>, limit: Option<i64>) -> Result<Vec<Document>, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    fetch_recently_created_documents(&conn, limit.unwrap_or(20))
+}
+
+#[tauri::command]
+pub async fn delete_document(state: tauri::State<'_, DbPool>, document_id: String) -> Result<(), String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    delete_document_inner(&conn, &document_id)
+}
+
+#[tauri::command]
+pub async fn word_count(content: String) -> Result<i64, String> {
+    Ok(count_prose_words(&content))
+}
+
+#[tauri::command]
+pub async fn recompute_word_counts(state: tauri::State<'_, DbPool>) -> Result<usize, String> {
+    let docs = {
+        let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+        fetch_file_document_paths(&conn)?
+    }; // lock dropped here
+
+    let counts = compute_word_counts(&docs);
+
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    apply_word_counts(&conn, &counts)
+}
+
+/// Pinned documents sort ahead of everything else in `get_recent_documents`.
+#[tauri::command]
+pub async fn set_document_pinned(state: tauri::State<'_, DbPool>, id: String, pinned: bool) -> Result<(), String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    set_document_pinned_inner(&conn, &id, pinned, now_millis())
+}
+
+/// Returns the stored (clamped) progress.
+#[tauri::command]
+pub async fn set_reading_progress(state: tauri::State<'_, DbPool>, id: String, progress: f64) -> Result<f64, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    set_reading_progress_inner(&conn, &id, progress)
+}
+
+#[tauri::command]
+pub async fn set_document_archived(state: tauri::State<'_, DbPool>, id: String, archived: bool) -> Result<(), String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    set_document_archived_inner(&conn, &id, archived)
+}
+
+#[tauri::command]
+pub async fn set_document_title(
+    state: tauri::State<'_, DbPool>,
+    document_id: String,
+    title: String,
+) -> Result<Document, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    set_document_title_inner(&conn, &document_id, &title)
+}
+
 #[tauri::command]
 pub async fn upsert_document(state: tauri::State<'_, DbPool>, doc: Document) -> Result<Document, String> {
+    // Read and parse the file before locking; only the write needs the DB
+    let meta = read_file_metadata(&doc);
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    upsert_document_with_metadata(&conn, doc, meta)
+}
+
+#[tauri::command]
+pub async fn get_tag_counts(state: tauri::State<'_, DbPool>) -> Result<Vec<TagCount>, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    fetch_tag_counts(&conn)
+}
+
+#[tauri::command]
+pub async fn add_document_tag(state: tauri::State<'_, DbPool>, document_id: String, tag: String) -> Result<(), String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    add_document_tag_inner(&conn, &document_id, &tag, now_millis())
+}
+
+#[tauri::command]
+pub async fn remove_document_tag(state: tauri::State<'_, DbPool>, document_id: String, tag: String) -> Result<(), String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    remove_document_tag_inner(&conn, &document_id, &tag)
+}
+
+#[tauri::command]
+pub async fn get_document_tags(state: tauri::State<'_, DbPool>, document_id: String) -> Result<Vec<String>, String> {
     let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
-    upsert_document_inner(&conn, doc)
+    fetch_document_tags(&conn, &document_id)
+}
+
+#[tauri::command]
+pub async fn get_documents_by_tag(state: tauri::State<'_, DbPool>, tag: String) -> Result<Vec<Document>, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    fetch_documents_by_tag(&conn, &tag)
+}
+
+#[tauri::command]
+pub async fn normalize_all_tags(state: tauri::State<'_, DbPool>) -> Result<usize, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    normalize_all_tags_inner(&conn)
 }
 
 #[cfg(test)]
@@ -125,6 +640,10 @@ mod tests {
              word_count INTEGER DEFAULT 0,
              last_opened_at INTEGER NOT NULL,
              created_at INTEGER NOT NULL,
+             pinned_at INTEGER,
+             archived INTEGER NOT NULL DEFAULT 0,
+             reading_progress REAL NOT NULL DEFAULT 0,
+             custom_title INTEGER NOT NULL DEFAULT 0,
              UNIQUE(file_path),
              UNIQUE(keep_local_id)
          );"
@@ -136,6 +655,34 @@ mod tests {
         conn
     }
 
+    fn setup_tag_db() -> Connection {
+        let conn = setup_db();
+        conn.execute_batch(
+            "CREATE TABLE document_tags (
+                 id TEXT PRIMARY KEY,
+                 document_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+                 tag TEXT NOT NULL,
+                 created_at INTEGER NOT NULL,
+                 UNIQUE(document_id, tag)
+             );",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn insert_tag(conn: &Connection, document_id: &str, tag: &str) {
+        conn.execute(
+            "INSERT OR IGNORE INTO documents (id, source, last_opened_at, created_at) VALUES (?1, 'file', 0, 0)",
+            rusqlite::params![document_id],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO document_tags (id, document_id, tag, created_at) VALUES (?1, ?2, ?3, 0)",
+            rusqlite::params![uuid::Uuid::new_v4().to_string(), document_id, tag],
+        )
+        .unwrap();
+    }
+
     fn make_doc(id: &str, source: &str, file_path: Option<&str>, keep_local_id: Option<&str>, last_opened_at: i64) -> Document {
         Document {
             id: id.to_string(),
@@ -148,6 +695,7 @@ mod tests {
             word_count: 100,
             last_opened_at,
             created_at: 1000,
+            reading_progress: 0.0,
         }
     }
 
@@ -162,6 +710,93 @@ mod tests {
         assert_eq!(count, 1);
     }
 
+    #[test]
+    fn upsert_prefers_front_matter_title_and_author() {
+        let conn = setup_db();
+        let dir = tempfile::tempdir().unwrap();
+        let with_fm = dir.path().join("essay.md");
+        std::fs::write(&with_fm, "---\ntitle: On Rhythm\nauthor: Ada\n---\nbody").unwrap();
+        let plain = dir.path().join("plain.md");
+        std::fs::write(&plain, "no metadata").unwrap();
+
+        let mut doc = make_doc("d1", "file", Some(with_fm.to_str().unwrap()), None, 1000);
+        doc.title = Some("essay".to_string());
+        let result = upsert_document_inner(&conn, doc).unwrap();
+        assert_eq!(result.title.as_deref(), Some("On Rhythm"));
+        assert_eq!(result.author.as_deref(), Some("Ada"));
+
+        let mut doc = make_doc("d2", "file", Some(plain.to_str().unwrap()), None, 1000);
+        doc.title = Some("plain".to_string());
+        let result = upsert_document_inner(&conn, doc).unwrap();
+        assert_eq!(result.title.as_deref(), Some("plain"));
+    }
+
+    #[test]
+    fn prose_word_count_skips_front_matter_and_code() {
+        assert_eq!(count_prose_words("Just some plain prose here."), 5);
+        assert_eq!(count_prose_words("---\ntitle: A Long Title Here\n---\nTwo words"), 2);
+        assert_eq!(
+            count_prose_words("Before code.\n\n```rust\nfn main() { let x = 1; }\n```\nAfter ~~~ it.\n  ~~~\nhidden text\n  ~~~\nend"),
+            6
+        );
+        assert_eq!(count_prose_words("text\n```\nnever closed\nstill code"), 1);
+        assert_eq!(count_prose_words(""), 0);
+    }
+
+    #[test]
+    fn upsert_recounts_words_from_file() {
+        let conn = setup_db();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("counted.md");
+        std::fs::write(&path, "---\ntitle: T\n---\none two three").unwrap();
+
+        let mut doc = make_doc("d1", "file", Some(path.to_str().unwrap()), None, 1000);
+        doc.word_count = 0;
+        assert_eq!(upsert_document_inner(&conn, doc).unwrap().word_count, 3);
+
+        let mut doc = make_doc("d1", "file", Some(path.to_str().unwrap()), None, 1000);
+        doc.word_count = 42;
+        assert_eq!(upsert_document_inner(&conn, doc).unwrap().word_count, 3);
+    }
+
+    #[test]
+    fn delete_document_cascades_and_clears_index() {
+        let conn = setup_db();
+        conn.execute_batch(
+            "PRAGMA foreign_keys=ON;
+             CREATE TABLE highlights (
+                 id TEXT PRIMARY KEY,
+                 document_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE
+             );
+             CREATE TABLE margin_notes (
+                 id TEXT PRIMARY KEY,
+                 highlight_id TEXT NOT NULL REFERENCES highlights(id) ON DELETE CASCADE
+             );",
+        )
+        .unwrap();
+        upsert_document_inner(&conn, make_doc("d1", "file", Some("/essay.md"), None, 1000)).unwrap();
+        upsert_document_inner(&conn, make_doc("d2", "file", Some("/other.md"), None, 1000)).unwrap();
+        conn.execute_batch(
+            "INSERT INTO highlights (id, document_id) VALUES ('h1', 'd1'), ('h2', 'd2');
+             INSERT INTO margin_notes (id, highlight_id) VALUES ('n1', 'h1'), ('n2', 'h2');",
+        )
+        .unwrap();
+        conn.execute_batch(
+            "CREATE VIRTUAL TABLE documents_fts USING fts5(title, content, document_id UNINDEXED);
+             INSERT INTO documents_fts (document_id, title, content) VALUES ('d1', 'Essay', 'body text');",
+        )
+        .unwrap();
+
+        delete_document_inner(&conn, "d1").unwrap();
+        let count = |sql: &str| -> i64 { conn.query_row(sql, [], |r| r.get(0)).unwrap() };
+        assert_eq!(count("SELECT COUNT(*) FROM documents"), 1);
+        assert_eq!(count("SELECT COUNT(*) FROM highlights"), 1);
+        assert_eq!(count("SELECT COUNT(*) FROM margin_notes"), 1);
+        assert_eq!(count("SELECT COUNT(*) FROM documents_fts WHERE document_id = 'd1'"), 0);
+
+        assert!(delete_document_inner(&conn, "d1").unwrap_err().contains("not found"));
+    }
+
     #[test]
     fn upsert_generates_id_when_empty() {
         let conn = setup_db();
@@ -225,13 +860,110 @@ mod tests {
         upsert_document_inner(&conn, make_doc("d2", "file", Some("/b.md"), None, 3000)).unwrap();
         upsert_document_inner(&conn, make_doc("d3", "file", Some("/c.md"), None, 2000)).unwrap();
 
-        let docs = fetch_recent_documents(&conn, 10).unwrap();
+        let docs = fetch_recent_documents(&conn, 10, false).unwrap();
         assert_eq!(docs.len(), 3);
         assert_eq!(docs[0].id, "d2"); // most recent
         assert_eq!(docs[1].id, "d3");
         assert_eq!(docs[2].id, "d1"); // oldest
     }
 
+    #[test]
+    fn fetch_document_by_id() {
+        let conn = setup_db();
+        upsert_document_inner(&conn, make_doc("d1", "file", Some("/a.md"), None, 1000)).unwrap();
+        upsert_document_inner(&conn, make_doc("d2", "keep-local", None, Some("kl-1"), 2000)).unwrap();
+
+        let doc = fetch_document(&conn, "d2").unwrap();
+        assert_eq!(doc.id, "d2");
+        assert_eq!(doc.keep_local_id.as_deref(), Some("kl-1"));
+        assert_eq!(doc.last_opened_at, 2000);
+
+        let err = fetch_document(&conn, "missing").unwrap_err();
+        assert!(err.contains("Document not found"), "{err}");
+    }
+
+    #[test]
+    fn pinned_documents_lead_recents() {
+        let conn = setup_db();
+        upsert_document_inner(&conn, make_doc("old_pin", "file", Some("/a.md"), None, 1000)).unwrap();
+        upsert_document_inner(&conn, make_doc("newest", "file", Some("/b.md"), None, 5000)).unwrap();
+        upsert_document_inner(&conn, make_doc("new_pin", "file", Some("/c.md"), None, 3000)).unwrap();
+        upsert_document_inner(&conn, make_doc("middle", "file", Some("/d.md"), None, 2000)).unwrap();
+        set_document_pinned_inner(&conn, "old_pin", true, 10).unwrap();
+        set_document_pinned_inner(&conn, "new_pin", true, 20).unwrap();
+
+        let ids: Vec<String> = fetch_recent_documents(&conn, 10, false).unwrap().into_iter().map(|d| d.id).collect();
+        assert_eq!(ids, vec!["new_pin", "old_pin", "newest", "middle"]);
+
+        // Re-opening (upsert) keeps the pin; unpinning restores recency order
+        upsert_document_inner(&conn, make_doc("old_pin", "file", Some("/a.md"), None, 1500)).unwrap();
+        set_document_pinned_inner(&conn, "new_pin", false, 30).unwrap();
+        let ids: Vec<String> = fetch_recent_documents(&conn, 10, false).unwrap().into_iter().map(|d| d.id).collect();
+        assert_eq!(ids, vec!["old_pin", "newest", "new_pin", "middle"]);
+
+        assert!(set_document_pinned_inner(&conn, "missing", true, 40).is_err());
+    }
+
+    #[test]
+    fn pinned_at_orders_pins_and_survives_repinning() {
+        let conn = setup_db();
+        upsert_document_inner(&conn, make_doc("older", "file", Some("/a.md"), None, 1000)).unwrap();
+        upsert_document_inner(&conn, make_doc("newer", "file", Some("/b.md"), None, 9000)).unwrap();
+        upsert_document_inner(&conn, make_doc("other", "file", Some("/c.md"), None, 500)).unwrap();
+        set_document_pinned_inner(&conn, "other", true, 100).unwrap();
+        set_document_pinned_inner(&conn, "older", true, 200).unwrap();
+
+        // A pinned older document outranks an unpinned newer one
+        let ids: Vec<String> = fetch_recent_documents(&conn, 10, false).unwrap().into_iter().map(|d| d.id).collect();
+        assert_eq!(ids, vec!["older", "other", "newer"]);
+
+        // Pinning again doesn't bump it; unpinning clears the timestamp
+        set_document_pinned_inner(&conn, "other", true, 300).unwrap();
+        let ids: Vec<String> = fetch_recent_documents(&conn, 10, false).unwrap().into_iter().map(|d| d.id).collect();
+        assert_eq!(ids, vec!["older", "other", "newer"]);
+        set_document_pinned_inner(&conn, "other", false, 400).unwrap();
+        let pinned_at: Option<i64> = conn
+            .query_row("SELECT pinned_at FROM documents WHERE id = 'other'", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(pinned_at, None);
+    }
+
+    #[test]
+    fn reading_progress_round_trips_and_clamps() {
+        let conn = setup_db();
+        upsert_document_inner(&conn, make_doc("d1", "file", Some("/a.md"), None, 1000)).unwrap();
+
+        assert_eq!(set_reading_progress_inner(&conn, "d1", 0.42).unwrap(), 0.42);
+        assert_eq!(fetch_document(&conn, "d1").unwrap().reading_progress, 0.42);
+
+        // Re-opening the document doesn't reset it
+        let reopened = upsert_document_inner(&conn, make_doc("d1", "file", Some("/a.md"), None, 2000)).unwrap();
+        assert_eq!(reopened.reading_progress, 0.42);
+
+        assert_eq!(set_reading_progress_inner(&conn, "d1", 1.7).unwrap(), 1.0);
+        assert_eq!(fetch_document(&conn, "d1").unwrap().reading_progress, 1.0);
+        assert_eq!(set_reading_progress_inner(&conn, "d1", -0.3).unwrap(), 0.0);
+        assert_eq!(set_reading_progress_inner(&conn, "d1", f64::NAN).unwrap(), 0.0);
+        assert!(set_reading_progress_inner(&conn, "missing", 0.5).is_err());
+    }
+
+    #[test]
+    fn archived_documents_are_hidden_from_recents_by_default() {
+        let conn = setup_db();
+        upsert_document_inner(&conn, make_doc("kept", "file", Some("/a.md"), None, 1000)).unwrap();
+        upsert_document_inner(&conn, make_doc("archived", "file", Some("/b.md"), None, 2000)).unwrap();
+        set_document_archived_inner(&conn, "archived", true).unwrap();
+
+        let ids: Vec<String> = fetch_recent_documents(&conn, 10, false).unwrap().into_iter().map(|d| d.id).collect();
+        assert_eq!(ids, vec!["kept"]);
+        let ids: Vec<String> = fetch_recent_documents(&conn, 10, true).unwrap().into_iter().map(|d| d.id).collect();
+        assert_eq!(ids, vec!["archived", "kept"]);
+
+        set_document_archived_inner(&conn, "archived", false).unwrap();
+        assert_eq!(fetch_recent_documents(&conn, 10, false).unwrap().len(), 2);
+        assert!(set_document_archived_inner(&conn, "missing", true).is_err());
+    }
+
     #[test]
     fn fetch_recent_documents_respects_limit() {
         let conn = setup_db();
@@ -242,14 +974,233 @@ mod tests {
             ).unwrap();
         }
 
-        let docs = fetch_recent_documents(&conn, 2).unwrap();
+        let docs = fetch_recent_documents(&conn, 2, false).unwrap();
         assert_eq!(docs.len(), 2);
     }
 
     #[test]
     fn fetch_recent_documents_empty_table() {
         let conn = setup_db();
-        let docs = fetch_recent_documents(&conn, 10).unwrap();
+        let docs = fetch_recent_documents(&conn, 10, false).unwrap();
         assert!(docs.is_empty());
     }
+
+    #[test]
+    fn recently_created_differs_from_recently_opened() {
+        let conn = setup_db();
+        // d1 was added first but opened most recently; d2 is newest but stale
+        let mut d1 = make_doc("d1", "file", Some("/a.md"), None, 9000);
+        d1.created_at = 1000;
+        let mut d2 = make_doc("d2", "file", Some("/b.md"), None, 2000);
+        d2.created_at = 5000;
+        let mut d3 = make_doc("d3", "file", Some("/c.md"), None, 3000);
+        d3.created_at = 3000;
+        for doc in [d1, d2, d3] {
+            upsert_document_inner(&conn, doc).unwrap();
+        }
+
+        let opened: Vec<String> = fetch_recent_documents(&conn, 10, false).unwrap().into_iter().map(|d| d.id).collect();
+        let created: Vec<String> = fetch_recently_created_documents(&conn, 10).unwrap().into_iter().map(|d| d.id).collect();
+        assert_eq!(opened, vec!["d1", "d3", "d2"]);
+        assert_eq!(created, vec!["d2", "d3", "d1"]);
+
+        assert_eq!(fetch_recently_created_documents(&conn, 1).unwrap().len(), 1);
+    }
+
+    // === Word count tests ===
+
+    #[test]
+    fn count_words_splits_on_any_whitespace() {
+        assert_eq!(count_words(""), 0);
+        assert_eq!(count_words("   \n\t "), 0);
+        assert_eq!(count_words("one  two\nthree\tfour"), 4);
+    }
+
+    #[test]
+    fn recompute_word_counts_updates_stale_counts() {
+        let conn = setup_db();
+        let dir = tempfile::tempdir().unwrap();
+        let stale = dir.path().join("stale.md");
+        let current = dir.path().join("current.md");
+        let binary = dir.path().join("binary.md");
+        std::fs::write(&stale, "five words in this file").unwrap();
+        std::fs::write(&current, "one two three").unwrap();
+        std::fs::write(&binary, b"abc\0def").unwrap();
+
+        for (id, path, words) in [("d1", &stale, 2), ("d2", &current, 3), ("d3", &binary, 7)] {
+            let mut doc = make_doc(id, "file", Some(path.to_str().unwrap()), None, 1000);
+            doc.word_count = words;
+            upsert_document_inner(&conn, doc).unwrap();
+        }
+        upsert_document_inner(&conn, make_doc("d4", "file", Some("/missing/file.md"), None, 1000)).unwrap();
+        upsert_document_inner(&conn, make_doc("d5", "keep-local", None, Some("kl-1"), 1000)).unwrap();
+        // Upsert recounts from the file, so go stale behind its back
+        conn.execute("UPDATE documents SET word_count = 2 WHERE id = 'd1'", []).unwrap();
+
+        let docs = fetch_file_document_paths(&conn).unwrap();
+        assert_eq!(docs.len(), 4);
+        let counts = compute_word_counts(&docs);
+        assert_eq!(apply_word_counts(&conn, &counts).unwrap(), 1);
+
+        let word_count = |id: &str| -> i64 {
+            conn.query_row("SELECT word_count FROM documents WHERE id = ?1", [id], |r| r.get(0)).unwrap()
+        };
+        assert_eq!(word_count("d1"), 5);
+        assert_eq!(word_count("d2"), 3);
+        assert_eq!(word_count("d3"), 7); // binary file left untouched
+    }
+
+    #[test]
+    fn set_document_title_leaves_file_path_and_updates_search() {
+        let conn = setup_db();
+        upsert_document_inner(&conn, make_doc("d1", "file", Some("/notes/draft-3.md"), None, 1000)).unwrap();
+        conn.execute_batch(
+            "CREATE VIRTUAL TABLE documents_fts USING fts5(title, content, document_id UNINDEXED);
+             INSERT INTO documents_fts (document_id, title, content) VALUES ('d1', 'draft-3', 'body text');",
+        )
+        .unwrap();
+
+        let doc = set_document_title_inner(&conn, "d1", "  Quarterly Plan ").unwrap();
+        assert_eq!(doc.title.as_deref(), Some("Quarterly Plan"));
+        assert_eq!(doc.file_path.as_deref(), Some("/notes/draft-3.md"));
+
+        let hit: String = conn
+            .query_row(
+                "SELECT document_id FROM documents_fts WHERE documents_fts MATCH 'title:quarterly'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(hit, "d1");
+
+        assert!(set_document_title_inner(&conn, "d1", "   ").is_err());
+        assert!(set_document_title_inner(&conn, "missing", "Title").is_err());
+    }
+
+    #[test]
+    fn custom_title_survives_reopen_and_front_matter() {
+        let dir = std::env::temp_dir().join("margin_test_documents_custom_title");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("draft.md");
+        std::fs::write(&path, "---\ntitle: Front Matter Title\n---\nbody").unwrap();
+        let path = path.to_string_lossy().to_string();
+        let conn = setup_db();
+
+        let doc = upsert_document_inner(&conn, make_doc("d1", "file", Some(&path), None, 1000)).unwrap();
+        assert_eq!(doc.title.as_deref(), Some("Front Matter Title"));
+        set_document_title_inner(&conn, "d1", "My Title").unwrap();
+
+        // Re-opening sends the basename; neither it nor front matter wins
+        let mut reopened = make_doc("d1", "file", Some(&path), None, 2000);
+        reopened.title = Some("draft".to_string());
+        let doc = upsert_document_inner(&conn, reopened).unwrap();
+        assert_eq!(doc.title.as_deref(), Some("My Title"));
+        assert_eq!(fetch_document(&conn, "d1").unwrap().title.as_deref(), Some("My Title"));
+    }
+
+    #[test]
+    fn tag_counts_are_empty_without_tags() {
+        let conn = setup_tag_db();
+        assert!(fetch_tag_counts(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn tag_counts_group_overlapping_tags() {
+        let conn = setup_tag_db();
+        insert_tag(&conn, "d1", "rust");
+        insert_tag(&conn, "d1", "essay");
+        insert_tag(&conn, "d2", "Rust");
+        insert_tag(&conn, "d2", "draft");
+        insert_tag(&conn, "d3", " rust ");
+        insert_tag(&conn, "d3", "essay");
+        // Same tag twice on one document (differing case) still counts the document once
+        insert_tag(&conn, "d3", "RUST");
+        // Non-ASCII case folds the same way `normalize_tag` stores it
+        insert_tag(&conn, "d1", "Café");
+        insert_tag(&conn, "d2", "CAFÉ");
+
+        let counts: Vec<(String, i64)> = fetch_tag_counts(&conn)
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.tag, t.count))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                ("rust".to_string(), 3),
+                ("café".to_string(), 2),
+                ("essay".to_string(), 2),
+                ("draft".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn document_tags_are_normalized_and_unique() {
+        let conn = setup_tag_db();
+        conn.execute("INSERT INTO documents (id, source, last_opened_at, created_at) VALUES ('d1', 'file', 0, 0)", [])
+            .unwrap();
+
+        add_document_tag_inner(&conn, "d1", " Rust ", 1).unwrap();
+        add_document_tag_inner(&conn, "d1", "rust", 2).unwrap();
+        add_document_tag_inner(&conn, "d1", "Essay", 3).unwrap();
+        assert!(add_document_tag_inner(&conn, "d1", "   ", 4).is_err());
+        assert_eq!(fetch_document_tags(&conn, "d1").unwrap(), vec!["essay", "rust"]);
+
+        remove_document_tag_inner(&conn, "d1", "RUST").unwrap();
+        assert_eq!(fetch_document_tags(&conn, "d1").unwrap(), vec!["essay"]);
+    }
+
+    #[test]
+    fn documents_by_tag_filters_and_orders_by_recency() {
+        let conn = setup_tag_db();
+        conn.execute_batch(
+            "INSERT INTO documents (id, source, last_opened_at, created_at) VALUES
+                 ('old', 'file', 100, 0), ('new', 'file', 200, 0), ('other', 'file', 300, 0);",
+        )
+        .unwrap();
+        add_document_tag_inner(&conn, "old", "draft", 1).unwrap();
+        add_document_tag_inner(&conn, "new", "draft", 1).unwrap();
+        add_document_tag_inner(&conn, "other", "final", 1).unwrap();
+
+        let ids: Vec<String> = fetch_documents_by_tag(&conn, "Draft").unwrap().into_iter().map(|d| d.id).collect();
+        assert_eq!(ids, vec!["new", "old"]);
+        assert!(fetch_documents_by_tag(&conn, "missing").unwrap().is_empty());
+    }
+
+    #[test]
+    fn normalize_all_tags_merges_case_variants_per_document() {
+        let conn = setup_tag_db();
+        insert_tag(&conn, "d1", "Rust");
+        insert_tag(&conn, "d1", "rust");
+        insert_tag(&conn, "d1", " RUST ");
+        insert_tag(&conn, "d1", "Essay");
+        insert_tag(&conn, "d2", "Rust");
+        insert_tag(&conn, "d2", "Café");
+        insert_tag(&conn, "d2", "CAFÉ");
+
+        assert_eq!(normalize_all_tags_inner(&conn).unwrap(), 3);
+
+        let mut tags: Vec<(String, String)> = conn
+            .prepare("SELECT document_id, tag FROM document_tags")
+            .unwrap()
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        tags.sort();
+        assert_eq!(
+            tags,
+            vec![
+                ("d1".to_string(), "essay".to_string()),
+                ("d1".to_string(), "rust".to_string()),
+                ("d2".to_string(), "café".to_string()),
+                ("d2".to_string(), "rust".to_string()),
+            ]
+        );
+
+        // Already-normalized data is left alone
+        assert_eq!(normalize_all_tags_inner(&conn).unwrap(), 0);
+    }
 }
@@ -1,9 +1,24 @@
-use crate::db::migrations::DbPool;
+use crate::commands::annotations::copy_annotations;
+use crate::commands::documents::{
+    count_prose_words, fetch_file_document_paths, file_metadata_from_content, read_text_file_guarded,
+    upsert_document_inner, upsert_document_with_metadata,
+};
+use crate::commands::{now_millis, parallel_map};
+use crate::commands::search::{index_all_documents, index_file_from_disk, index_file_inner, remove_document_index_inner};
+use crate::commands::settings::{scan_threads, set_workspace_dir};
+use crate::db::migrations::{data_dir, DbPool};
 use crate::db::models::Document;
+use crate::watcher::{FileWatcher, SelfWrites};
 use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
 use std::fs;
-use std::path::Path;
-use std::process::Command;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::Manager;
+use tauri_plugin_dialog::DialogExt;
+use uuid::Uuid;
 
 #[derive(Serialize)]
 pub struct FileEntry {
@@ -12,45 +27,305 @@ pub struct FileEntry {
     pub is_dir: bool,
 }
 
+#[derive(Serialize, Debug)]
+pub struct FileMetadata {
+    pub size_bytes: u64,
+    pub modified_ms: Option<i64>,
+    /// Not every filesystem records a creation time.
+    pub created_ms: Option<i64>,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct AttachmentReport {
+    pub existing: Vec<String>,
+    pub missing: Vec<String>,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct ImportSummary {
+    pub imported: usize,
+    /// Files that already had a document row.
+    pub skipped: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct BrokenLinks {
+    pub document_id: String,
+    pub file_path: String,
+    pub links: Vec<String>,
+}
+
 #[tauri::command]
-pub async fn open_file_dialog() -> Result<Option<String>, String> {
-    let output = Command::new("osascript")
-        .arg("-e")
-        .arg(r#"POSIX path of (choose file of type {"md","markdown","txt"} with prompt "Open Markdown File")"#)
-        .output()
-        .map_err(|e| format!("Failed to run osascript: {}", e))?;
-
-    if !output.status.success() {
-        // User cancelled the dialog (osascript returns non-zero)
+pub async fn open_file_dialog(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    let picked = app
+        .dialog()
+        .file()
+        .set_title("Open Markdown File")
+        .add_filter("Markdown", &["md", "markdown", "txt"])
+        .blocking_pick_file();
+
+    // None means the user cancelled
+    let Some(picked) = picked else {
         return Ok(None);
+    };
+    let path = picked.into_path().map_err(|e| format!("Failed to resolve selected file: {}", e))?;
+    Ok(Some(path.to_string_lossy().to_string()))
+}
+
+#[tauri::command]
+pub async fn read_file(path: String) -> Result<String, String> {
+    fs::read_to_string(&path).map_err(|e| format!("Failed to read file '{}': {}", path, e))
+}
+
+/// Writes `content` to a hidden sibling (`.name.md.tmp`) and renames it over
+/// `path`, so a crash or full disk mid-write never leaves a truncated file.
+/// Symlinks are resolved first so the link itself survives the rename.
+fn write_file_atomic(path: &Path, content: &str) -> std::io::Result<()> {
+    let target = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let name = target
+        .file_name()
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "path has no file name"))?;
+    let tmp = target.with_file_name(format!(".{}.tmp", name.to_string_lossy()));
+    let permissions = fs::metadata(&target).ok().map(|m| m.permissions());
+
+    let result = fs::File::create(&tmp)
+        .and_then(|mut file| {
+            file.write_all(content.as_bytes())?;
+            if let Some(permissions) = permissions {
+                file.set_permissions(permissions)?;
+            }
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&tmp, &target));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
     }
+    result
+}
+
+/// Overwrites `path` atomically (see `write_file_atomic`).
+fn write_file_content(path: &Path, content: &str) -> Result<(), String> {
+    write_file_atomic(path, content).map_err(|e| format!("Failed to write file '{}': {}", path.display(), e))
+}
+
+/// What `save_file` should leave on disk: `content` itself, or with `append`
+/// the current file followed by `content` (a missing file counts as empty).
+/// Appends are written whole through `write_file_content` too, so a crash
+/// can't leave a half-written tail.
+fn saved_content(path: &Path, content: &str, append: bool) -> Result<String, String> {
+    if !append {
+        return Ok(content.to_string());
+    }
+    let mut existing = match fs::read_to_string(path) {
+        Ok(existing) => existing,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("Failed to read file '{}': {}", path.display(), e)),
+    };
+    existing.push_str(content);
+    Ok(existing)
+}
+
+fn system_time_ms(time: std::io::Result<std::time::SystemTime>) -> Option<i64> {
+    time.ok()
+        .and_then(|t| t.duration_since(std::time::SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as i64)
+}
 
-    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
-    if path.is_empty() {
+fn file_metadata_inner(path: &Path) -> Result<FileMetadata, String> {
+    let meta = fs::metadata(path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => format!("File does not exist: {}", path.display()),
+        _ => format!("Failed to read metadata for '{}': {}", path.display(), e),
+    })?;
+    Ok(FileMetadata {
+        size_bytes: meta.len(),
+        modified_ms: system_time_ms(meta.modified()),
+        created_ms: system_time_ms(meta.created()),
+    })
+}
+
+#[tauri::command]
+pub async fn get_file_metadata(path: String) -> Result<FileMetadata, String> {
+    file_metadata_inner(Path::new(&path))
+}
+
+/// Backups kept per file by `save_file`; older ones are pruned.
+const BACKUPS_TO_KEEP: usize = 10;
+
+/// `~/.margin/backups`, holding one directory of `{timestamp}.md` copies per file.
+fn backups_root() -> Result<PathBuf, String> {
+    Ok(data_dir().map_err(|e| e.to_string())?.join("backups"))
+}
+
+/// `{root}/{file name}-{hash}`, keyed on a hash of the canonical path so two
+/// `notes.md` in different folders never share backups. The parent is
+/// canonicalized rather than the file, which may have been deleted.
+fn backup_dir(root: &Path, path: &Path) -> Result<PathBuf, String> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| format!("Cannot determine file name: {}", path.display()))?;
+    let parent = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let canonical = fs::canonicalize(parent).map_or_else(|_| path.to_path_buf(), |parent| parent.join(name));
+    let digest = Sha256::digest(canonical.to_string_lossy().as_bytes());
+    let hash: String = digest[..8].iter().map(|b| format!("{b:02x}")).collect();
+    Ok(root.join(format!("{}-{hash}", name.to_string_lossy())))
+}
+
+/// Backup timestamps for `path`, oldest first.
+fn list_backups_in(root: &Path, path: &Path) -> Result<Vec<i64>, String> {
+    let dir = backup_dir(root, path)?;
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Ok(Vec::new());
+    };
+    let mut timestamps: Vec<i64> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            name.strip_suffix(".md")?.parse().ok()
+        })
+        .collect();
+    timestamps.sort_unstable();
+    Ok(timestamps)
+}
+
+/// Copies the current `path` to `{backup_dir}/{timestamp}.md`, then
+/// prunes all but the newest `keep`. A file that doesn't exist yet (first
+/// save) has nothing to back up and returns `None`.
+fn backup_file(root: &Path, path: &Path, timestamp: i64, keep: usize) -> Result<Option<PathBuf>, String> {
+    if !path.is_file() {
         return Ok(None);
     }
+    let dir = backup_dir(root, path)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create backup directory: {}", e))?;
+    let backup = dir.join(format!("{timestamp}.md"));
+    fs::copy(path, &backup).map_err(|e| format!("Failed to back up '{}': {}", path.display(), e))?;
+
+    let timestamps = list_backups_in(root, path)?;
+    for old in timestamps.iter().take(timestamps.len().saturating_sub(keep)) {
+        let _ = fs::remove_file(dir.join(format!("{old}.md")));
+    }
+    Ok(Some(backup))
+}
+
+/// Overwrites `path` with the backup taken at `timestamp`. The current
+/// content is backed up first, so a restore can itself be undone.
+fn restore_backup_inner(root: &Path, path: &Path, timestamp: i64, now: i64) -> Result<String, String> {
+    let backup = backup_dir(root, path)?.join(format!("{timestamp}.md"));
+    let content = fs::read_to_string(&backup)
+        .map_err(|e| format!("Failed to read backup '{}': {}", backup.display(), e))?;
+    backup_file(root, path, now, BACKUPS_TO_KEEP)?;
+    write_file_content(path, &content)?;
+    Ok(content)
+}
 
-    Ok(Some(path))
+/// Keeps a tracked document's word count and search index in step with what
+/// was just saved. Returns false for paths that aren't in the library.
+fn refresh_saved_document(conn: &rusqlite::Connection, path: &str, saved: &str) -> Result<bool, String> {
+    let updated = conn
+        .execute(
+            "UPDATE documents SET word_count = ?1 WHERE file_path = ?2",
+            rusqlite::params![count_prose_words(saved), path],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Ok(false);
+    }
+    index_file_inner(conn, path, saved)
 }
 
 #[tauri::command]
-pub async fn read_file(path: String) -> Result<String, String> {
-    fs::read_to_string(&path).map_err(|e| format!("Failed to read file '{}': {}", path, e))
+pub async fn save_file(
+    state: tauri::State<'_, DbPool>,
+    self_writes: tauri::State<'_, SelfWrites>,
+    path: String,
+    content: String,
+    append: Option<bool>,
+    backup: Option<bool>,
+) -> Result<(), String> {
+    if backup.unwrap_or(false) {
+        // A failed backup shouldn't cost the user their save
+        if let Err(e) = backups_root().and_then(|root| backup_file(&root, Path::new(&path), now_millis(), BACKUPS_TO_KEEP)) {
+            eprintln!("save_file: failed to back up {path}: {e}");
+        }
+    }
+    let saved = saved_content(Path::new(&path), &content, append.unwrap_or(false))?;
+
+    // Record before writing so the watcher's events see the expectation
+    self_writes.record(Path::new(&path), saved.as_bytes());
+    write_file_content(Path::new(&path), &saved)?;
+
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    if let Err(e) = refresh_saved_document(&conn, &path, &saved) {
+        eprintln!("save_file: failed to refresh index for {path}: {e}");
+    }
+    Ok(())
 }
 
 #[tauri::command]
-pub async fn save_file(path: String, content: String) -> Result<(), String> {
-    fs::write(&path, &content).map_err(|e| format!("Failed to write file '{}': {}", path, e))
+pub async fn list_backups(path: String) -> Result<Vec<i64>, String> {
+    list_backups_in(&backups_root()?, Path::new(&path))
 }
 
 #[tauri::command]
-pub async fn list_markdown_files(dir: String) -> Result<Vec<FileEntry>, String> {
-    let root = Path::new(&dir);
+pub async fn restore_backup(state: tauri::State<'_, DbPool>, path: String, timestamp: i64) -> Result<String, String> {
+    let restored = restore_backup_inner(&backups_root()?, Path::new(&path), timestamp, now_millis())?;
+
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    if let Err(e) = refresh_saved_document(&conn, &path, &restored) {
+        eprintln!("restore_backup: failed to refresh index for {path}: {e}");
+    }
+    Ok(restored)
+}
+
+#[tauri::command]
+pub async fn list_markdown_files(state: tauri::State<'_, DbPool>, dir: String) -> Result<Vec<FileEntry>, String> {
+    let threads = {
+        let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+        scan_threads(&conn)
+    };
+    list_markdown_tree(Path::new(&dir), threads)
+}
+
+/// Opens `dir` as the workspace root: remembers it, watches it recursively
+/// (re-indexing changed files), and re-indexes stale documents in the
+/// background. Returns the initial file tree.
+#[tauri::command]
+pub async fn set_workspace(
+    state: tauri::State<'_, DbPool>,
+    watcher: tauri::State<'_, Mutex<FileWatcher>>,
+    app_handle: tauri::AppHandle,
+    dir: String,
+) -> Result<Vec<FileEntry>, String> {
+    let threads = {
+        let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+        scan_threads(&conn)
+    };
+    let entries = list_markdown_tree(Path::new(&dir), threads)?;
+
+    {
+        let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+        set_workspace_dir(&conn, &dir)?;
+    }
+    watcher
+        .lock()
+        .map_err(|e| format!("Failed to lock watcher state: {e}"))?
+        .watch_directory(&dir, true, &app_handle)?;
+
+    std::thread::spawn(move || {
+        if let Err(e) = index_all_documents(app_handle.state::<DbPool>()) {
+            eprintln!("set_workspace: background index failed: {e}");
+        }
+    });
+
+    Ok(entries)
+}
+
+/// Markdown files and folders under `root`, directories first.
+fn list_markdown_tree(root: &Path, threads: usize) -> Result<Vec<FileEntry>, String> {
     if !root.is_dir() {
-        return Err(format!("'{}' is not a directory", dir));
+        return Err(format!("'{}' is not a directory", root.display()));
     }
 
-    let mut entries = collect_markdown_entries(root)?;
+    let mut entries = collect_markdown_entries_parallel(root, threads)?;
 
     // Sort: directories first, then alphabetically by name (case-insensitive)
     entries.sort_by(|a, b| {
@@ -62,7 +337,8 @@ pub async fn list_markdown_files(dir: String) -> Result<Vec<FileEntry>, String>
     Ok(entries)
 }
 
-fn rename_file_inner(conn: &rusqlite::Connection, old_path: String, new_name: String) -> Result<Document, String> {
+/// Validates a user-supplied file name and ensures it has a markdown extension.
+fn markdown_file_name(new_name: &str) -> Result<String, String> {
     let new_name = new_name.trim().to_string();
     if new_name.is_empty() {
         return Err("File name cannot be empty".to_string());
@@ -72,11 +348,22 @@ fn rename_file_inner(conn: &rusqlite::Connection, old_path: String, new_name: St
     }
 
     // Ensure .md extension
-    let new_name = if new_name.ends_with(".md") || new_name.ends_with(".markdown") {
-        new_name
+    if new_name.ends_with(".md") || new_name.ends_with(".markdown") {
+        Ok(new_name)
     } else {
-        format!("{}.md", new_name)
-    };
+        Ok(format!("{}.md", new_name))
+    }
+}
+
+fn title_for_file_name(name: &str) -> String {
+    name.strip_suffix(".md")
+        .or_else(|| name.strip_suffix(".markdown"))
+        .unwrap_or(name)
+        .to_string()
+}
+
+fn rename_file_inner(conn: &rusqlite::Connection, old_path: String, new_name: String) -> Result<Document, String> {
+    let new_name = markdown_file_name(&new_name)?;
 
     let old = Path::new(&old_path);
     let parent = old
@@ -94,29 +381,36 @@ fn rename_file_inner(conn: &rusqlite::Connection, old_path: String, new_name: St
         return Err(format!("Source file does not exist: {}", old_path));
     }
 
+    relocate_file(conn, &old_path, &new_path, Some(&title_for_file_name(&new_name)))
+}
+
+/// Renames `old_path` to `new_path` on disk, then points its documents row at
+/// the new path (and `title`, when given). Rolls the file back if the DB
+/// update fails. Callers must have checked that `new_path` is free.
+fn relocate_file(
+    conn: &rusqlite::Connection,
+    old_path: &str,
+    new_path: &Path,
+    title: Option<&str>,
+) -> Result<Document, String> {
     // Rename on disk first — atomic on macOS (rename(2))
-    fs::rename(&old_path, &new_path)
+    fs::rename(old_path, new_path)
         .map_err(|e| format!("Failed to rename file: {}", e))?;
 
     let new_path_str = new_path.to_string_lossy().to_string();
-    let new_title = new_name
-        .strip_suffix(".md")
-        .or_else(|| new_name.strip_suffix(".markdown"))
-        .unwrap_or(&new_name)
-        .to_string();
 
     // Update database and return updated document in one query.
     // Roll back the file rename if the DB operation fails.
     let doc = conn
         .query_row(
-            "UPDATE documents SET file_path = ?1, title = ?2 WHERE file_path = ?3
+            "UPDATE documents SET file_path = ?1, title = COALESCE(?2, title) WHERE file_path = ?3
              RETURNING id, source, file_path, keep_local_id, title, author, url,
-                       word_count, last_opened_at, created_at",
-            rusqlite::params![new_path_str, new_title, old_path],
+                       word_count, last_opened_at, created_at, reading_progress",
+            rusqlite::params![new_path_str, title, old_path],
             Document::from_row,
         )
         .map_err(|e| {
-            match fs::rename(&new_path, &old_path) {
+            match fs::rename(new_path, old_path) {
                 Ok(()) => format!("Failed to update database (file rename rolled back): {}", e),
                 Err(rb_err) => format!(
                     "Failed to update database AND rollback failed — file is at '{}' but DB has old path. \
@@ -129,12 +423,597 @@ fn rename_file_inner(conn: &rusqlite::Connection, old_path: String, new_name: St
     Ok(doc)
 }
 
+/// Moves a file into another existing directory, keeping its name and title.
+fn move_file_inner(conn: &rusqlite::Connection, old_path: &str, new_dir: &Path) -> Result<Document, String> {
+    if !new_dir.is_dir() {
+        return Err(format!("Destination is not a directory: {}", new_dir.display()));
+    }
+    let old = Path::new(old_path);
+    if !old.exists() {
+        return Err(format!("Source file does not exist: {}", old_path));
+    }
+    let file_name = old
+        .file_name()
+        .ok_or_else(|| format!("Cannot determine file name: {}", old_path))?;
+    let new_path = new_dir.join(file_name);
+    if new_path.exists() {
+        return Err(format!(
+            "A file named '{}' already exists in {}",
+            file_name.to_string_lossy(),
+            new_dir.display()
+        ));
+    }
+
+    relocate_file(conn, old_path, &new_path, None)
+}
+
+/// Creates `name` (validated like a rename) inside `dir` with `initial_content`
+/// and records it in `documents`. Never overwrites: an existing file is an
+/// error, and the new file is removed again if the database write fails.
+fn create_file_inner(conn: &rusqlite::Connection, dir: &Path, name: &str, initial_content: &str) -> Result<Document, String> {
+    let name = markdown_file_name(name)?;
+    if !dir.is_dir() {
+        return Err(format!("Not a directory: {}", dir.display()));
+    }
+    let path = dir.join(&name);
+
+    // create_new fails if the file exists, closing the check-then-write gap
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(initial_content.as_bytes()))
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::AlreadyExists => format!("A file named '{}' already exists", name),
+            _ => format!("Failed to create file '{}': {}", path.display(), e),
+        })?;
+
+    let now = now_millis();
+    let doc = Document {
+        id: String::new(),
+        source: "file".to_string(),
+        file_path: Some(path.to_string_lossy().to_string()),
+        keep_local_id: None,
+        title: Some(title_for_file_name(&name)),
+        author: None,
+        url: None,
+        word_count: count_prose_words(initial_content),
+        last_opened_at: now,
+        created_at: now,
+        reading_progress: 0.0,
+    };
+    upsert_document_inner(conn, doc).map_err(|e| {
+        let _ = fs::remove_file(&path);
+        format!("Failed to record document (file removed): {}", e)
+    })
+}
+
+/// A markdown file found by `scan_import_files`, read off the DB lock.
+struct ImportFile {
+    path: String,
+    name: String,
+    /// `None` for files too large or binary to read as text.
+    content: Option<String>,
+    modified_ms: Option<i64>,
+    created_ms: Option<i64>,
+}
+
+/// Walks `dir` and reads every markdown file whose path isn't in `tracked`.
+/// File I/O only, so the walk of a large vault never holds the DB lock.
+/// Returns the files and how many were already tracked.
+fn scan_import_files(dir: &Path, tracked: &HashSet<String>) -> Result<(Vec<ImportFile>, usize), String> {
+    if !dir.is_dir() {
+        return Err(format!("Not a directory: {}", dir.display()));
+    }
+    let mut files = Vec::new();
+    let mut skipped = 0;
+    for entry in collect_markdown_entries(dir)?.into_iter().filter(|e| !e.is_dir) {
+        if tracked.contains(&entry.path) {
+            skipped += 1;
+            continue;
+        }
+        let meta = file_metadata_inner(Path::new(&entry.path)).ok();
+        files.push(ImportFile {
+            content: read_text_file_guarded(Path::new(&entry.path)).ok().flatten(),
+            modified_ms: meta.as_ref().and_then(|m| m.modified_ms),
+            created_ms: meta.and_then(|m| m.created_ms),
+            path: entry.path,
+            name: entry.name,
+        });
+    }
+    Ok((files, skipped))
+}
+
+/// Records and indexes `files` in one transaction. Title and word count come
+/// from the content already read (front matter, else the file name);
+/// timestamps come from the file so an import doesn't flood recents. Files
+/// tracked since the scan are skipped. Returns the imported and skipped counts.
+fn record_import_files(conn: &rusqlite::Connection, files: Vec<ImportFile>) -> Result<(usize, usize), String> {
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let mut imported = 0;
+    let mut skipped = 0;
+    for file in files {
+        let exists: bool = tx
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM documents WHERE file_path = ?1)",
+                [&file.path],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        if exists {
+            skipped += 1;
+            continue;
+        }
+
+        let modified = file.modified_ms.unwrap_or_else(now_millis);
+        let doc = Document {
+            id: String::new(),
+            source: "file".to_string(),
+            file_path: Some(file.path.clone()),
+            keep_local_id: None,
+            title: Some(title_for_file_name(&file.name)),
+            author: None,
+            url: None,
+            word_count: 0,
+            last_opened_at: modified,
+            created_at: file.created_ms.unwrap_or(modified),
+            reading_progress: 0.0,
+        };
+        upsert_document_with_metadata(&tx, doc, file.content.as_deref().map(file_metadata_from_content))?;
+        if let Some(content) = &file.content {
+            index_file_inner(&tx, &file.path, content)?;
+        }
+        imported += 1;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok((imported, skipped))
+}
+
+/// Imports every untracked markdown file under `dir`. The lock is taken only
+/// to list tracked paths and to write the rows; the walk and the reads run
+/// without it.
+fn import_directory_inner(pool: &DbPool, dir: &Path) -> Result<ImportSummary, String> {
+    let tracked: HashSet<String> = {
+        let conn = pool.0.lock().unwrap_or_else(|e| e.into_inner());
+        fetch_file_document_paths(&conn)?.into_iter().map(|(_, path)| path).collect()
+    };
+    let (files, already_tracked) = scan_import_files(dir, &tracked)?;
+    let conn = pool.0.lock().unwrap_or_else(|e| e.into_inner());
+    let (imported, skipped) = record_import_files(&conn, files)?;
+    Ok(ImportSummary { imported, skipped: already_tracked + skipped })
+}
+
+/// `name` if it is free in `dir`, else the first free of "name (copy).md",
+/// "name (copy 2).md", and so on.
+fn unique_copy_name(dir: &Path, name: &str) -> String {
+    if !dir.join(name).exists() {
+        return name.to_string();
+    }
+    let stem = title_for_file_name(name);
+    let ext = &name[stem.len()..];
+    let mut candidate = format!("{stem} (copy){ext}");
+    let mut n = 2;
+    while dir.join(&candidate).exists() {
+        candidate = format!("{stem} (copy {n}){ext}");
+        n += 1;
+    }
+    candidate
+}
+
+/// Copies a file's text to `new_name` next to it, as a brand-new document
+/// with no annotations. A taken `new_name` is not an error: the copy gets
+/// the first free "(copy)" variant instead (see `unique_copy_name`).
+fn duplicate_file_inner(conn: &rusqlite::Connection, path: &str, new_name: &str) -> Result<Document, String> {
+    let new_name = markdown_file_name(new_name)?;
+    let source = Path::new(path);
+    let content = fs::read_to_string(source).map_err(|e| format!("Failed to read file '{}': {}", path, e))?;
+    let parent = source
+        .parent()
+        .ok_or_else(|| "Cannot determine parent directory".to_string())?;
+
+    create_file_inner(conn, parent, &unique_copy_name(parent, &new_name), &content)
+}
+
+/// Moves `path` to the platform's Trash / Recycle Bin via the `trash` crate,
+/// which also handles per-volume trash folders.
+fn move_to_trash(path: &Path) -> Result<(), String> {
+    trash::delete(path).map_err(|e| format!("Failed to move '{}' to Trash: {}", path.display(), e))
+}
+
+/// Moves a file to the Trash with `trash` (`move_to_trash` outside tests)
+/// and deletes its `documents` row; highlights, notes and tabs go with the
+/// row via FK cascades. The file is never removed outright, and the row is
+/// only deleted once the file has moved: if trashing fails, both stay.
+fn delete_file_inner(
+    conn: &rusqlite::Connection,
+    path: &str,
+    trash: impl FnOnce(&Path) -> Result<(), String>,
+) -> Result<(), String> {
+    let file = Path::new(path);
+    if !file.is_file() {
+        return Err(format!("File does not exist: {}", path));
+    }
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let document_id: Option<String> = match tx.query_row(
+        "SELECT id FROM documents WHERE file_path = ?1",
+        [path],
+        |row| row.get(0),
+    ) {
+        Ok(id) => Some(id),
+        Err(rusqlite::Error::QueryReturnedNoRows) => None,
+        Err(e) => return Err(e.to_string()),
+    };
+    if let Some(id) = &document_id {
+        remove_document_index_inner(&tx, id)?;
+        tx.execute("DELETE FROM documents WHERE id = ?1", [id])
+            .map_err(|e| e.to_string())?;
+    }
+
+    trash(file)?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Copies a file-backed document next to the original and gives the copy its
+/// own `documents` row plus fresh copies of every highlight and margin note.
+/// Corrections are not copied: they record edits made to the original.
+fn duplicate_document_inner(
+    conn: &mut rusqlite::Connection,
+    document_id: &str,
+    new_name: String,
+) -> Result<Document, String> {
+    let new_name = markdown_file_name(&new_name)?;
+    let source_path: Option<String> = conn
+        .query_row(
+            "SELECT file_path FROM documents WHERE id = ?1",
+            [document_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => "Document not found".to_string(),
+            other => other.to_string(),
+        })?;
+    let source_path = source_path.ok_or_else(|| "Document has no file on disk".to_string())?;
+
+    let source = Path::new(&source_path);
+    let parent = source
+        .parent()
+        .ok_or_else(|| "Cannot determine parent directory".to_string())?;
+    let new_path = parent.join(&new_name);
+    if new_path.exists() {
+        return Err(format!("A file named '{}' already exists", new_name));
+    }
+    if !source.exists() {
+        return Err(format!("Source file does not exist: {}", source_path));
+    }
+
+    fs::copy(source, &new_path).map_err(|e| format!("Failed to copy file: {}", e))?;
+
+    let new_path_str = new_path.to_string_lossy().to_string();
+    let result = (|| {
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let now = now_millis();
+        let doc = tx
+            .query_row(
+                "INSERT INTO documents
+                    (id, source, file_path, keep_local_id, title, author, url,
+                     word_count, last_opened_at, created_at)
+                 SELECT ?1, source, ?2, NULL, ?3, author, url, word_count, ?4, ?4
+                 FROM documents WHERE id = ?5
+                 RETURNING id, source, file_path, keep_local_id, title, author, url,
+                           word_count, last_opened_at, created_at, reading_progress",
+                rusqlite::params![
+                    Uuid::new_v4().to_string(),
+                    new_path_str,
+                    title_for_file_name(&new_name),
+                    now,
+                    document_id
+                ],
+                Document::from_row,
+            )
+            .map_err(|e| e.to_string())?;
+        copy_annotations(&tx, document_id, &doc.id)?;
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(doc)
+    })();
+
+    result.map_err(|e: String| {
+        let _ = fs::remove_file(&new_path);
+        format!("Failed to duplicate document (copy removed): {}", e)
+    })
+}
+
+fn is_markdown_target(target: &str) -> bool {
+    let lower = target.to_lowercase();
+    lower.ends_with(".md") || lower.ends_with(".markdown")
+}
+
+/// Extensions that make a `[[...]]` embed an attachment rather than a note,
+/// so `[[v1.2 notes]]` still links the note "v1.2 notes".
+const ATTACHMENT_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "webp", "svg", "bmp", "avif", "heic", "pdf", "mp3", "wav", "m4a", "ogg", "flac",
+    "mp4", "mov", "webm", "zip", "csv",
+];
+
+fn is_attachment_target(target: &str) -> bool {
+    Path::new(target)
+        .extension()
+        .is_some_and(|ext| ATTACHMENT_EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str()))
+}
+
+fn is_remote_target(target: &str) -> bool {
+    target.contains("://") || target.starts_with("data:") || target.starts_with("mailto:")
+}
+
+/// Lines outside fenced code blocks, with inline code spans removed.
+fn prose_lines(content: &str) -> Vec<String> {
+    let inline_code_re = regex::Regex::new(r"`[^`]*`").expect("valid inline code regex");
+    let mut lines = Vec::new();
+    let mut in_fence = false;
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if !in_fence {
+            lines.push(inline_code_re.replace_all(line, "").into_owned());
+        }
+    }
+    lines
+}
+
+/// Finds `![alt](path)` images and `[[file.ext]]` / `![[file.ext]]` embeds,
+/// ignoring fenced code blocks, inline code, remote URLs, and note wikilinks.
+fn collect_attachment_refs(content: &str) -> Vec<String> {
+    let image_re = regex::Regex::new(r#"!\[[^\]]*\]\(\s*(?:<([^>]+)>|([^)\s]+))(?:\s+"[^"]*")?\s*\)"#)
+        .expect("valid image regex");
+    let embed_re = regex::Regex::new(r"\[\[([^\]|#]+)(?:[|#][^\]]*)?\]\]").expect("valid embed regex");
+
+    let mut refs: Vec<String> = Vec::new();
+    let mut push = |target: &str| {
+        let target = target.trim();
+        if !target.is_empty() && !is_remote_target(target) && !refs.iter().any(|r| r == target) {
+            refs.push(target.to_string());
+        }
+    };
+
+    for line in prose_lines(content) {
+        for caps in image_re.captures_iter(&line) {
+            if let Some(m) = caps.get(1).or_else(|| caps.get(2)) {
+                push(m.as_str());
+            }
+        }
+        for caps in embed_re.captures_iter(&line) {
+            let target = caps[1].trim();
+            // [[Other note]] links documents, not attachments
+            if is_attachment_target(target) {
+                push(target);
+            }
+        }
+    }
+    refs
+}
+
+fn resolve_attachments(document_path: &Path, content: &str) -> AttachmentReport {
+    let base = document_path.parent().unwrap_or_else(|| Path::new(""));
+    let mut report = AttachmentReport::default();
+    for target in collect_attachment_refs(content) {
+        let resolved: PathBuf = if Path::new(&target).is_absolute() {
+            PathBuf::from(&target)
+        } else {
+            base.join(&target)
+        };
+        let resolved_str = resolved.to_string_lossy().to_string();
+        if resolved.exists() {
+            report.existing.push(resolved_str);
+        } else {
+            report.missing.push(resolved_str);
+        }
+    }
+    report
+}
+
+#[derive(Debug, PartialEq)]
+enum NoteLink {
+    /// `[text](path.md)`, resolved against the linking document's directory
+    Relative(String),
+    /// `[[Name]]`, resolved by file name anywhere in the library
+    Wiki(String),
+}
+
+/// Finds links to other notes, with the same code-span and remote-URL
+/// exclusions as attachment scanning. Heading fragments are dropped.
+fn collect_note_links(content: &str) -> Vec<NoteLink> {
+    let link_re = regex::Regex::new(r#"\[[^\]]*\]\(\s*(?:<([^>]+)>|([^)\s]+))(?:\s+"[^"]*")?\s*\)"#)
+        .expect("valid link regex");
+    let wiki_re = regex::Regex::new(r"\[\[([^\]|#]+)(?:[|#][^\]]*)?\]\]").expect("valid wikilink regex");
+
+    let mut links: Vec<NoteLink> = Vec::new();
+    for line in prose_lines(content) {
+        for caps in link_re.captures_iter(&line) {
+            // `![alt](path)` is an image, not a link
+            if line[..caps.get(0).map_or(0, |m| m.start())].ends_with('!') {
+                continue;
+            }
+            let Some(m) = caps.get(1).or_else(|| caps.get(2)) else { continue };
+            let target = m.as_str().split('#').next().unwrap_or("").trim().replace("%20", " ");
+            if !target.is_empty() && !is_remote_target(&target) && is_markdown_target(&target) {
+                let link = NoteLink::Relative(target);
+                if !links.contains(&link) {
+                    links.push(link);
+                }
+            }
+        }
+        for caps in wiki_re.captures_iter(&line) {
+            let target = caps[1].trim();
+            if !is_attachment_target(target) {
+                let link = NoteLink::Wiki(target.to_string());
+                if !links.contains(&link) {
+                    links.push(link);
+                }
+            }
+        }
+    }
+    links
+}
+
+/// Returns the link targets (as written) that resolve neither to a known
+/// document path nor to a file on disk.
+fn find_broken_links_in(
+    document_path: &Path,
+    content: &str,
+    known_paths: &HashSet<String>,
+    known_names: &HashSet<String>,
+) -> Vec<String> {
+    let base = document_path.parent().unwrap_or_else(|| Path::new(""));
+    collect_note_links(content)
+        .into_iter()
+        .filter_map(|link| match link {
+            NoteLink::Relative(target) => {
+                let resolved = base.join(&target);
+                let found = known_paths.contains(resolved.to_string_lossy().as_ref()) || resolved.exists();
+                (!found).then_some(target)
+            }
+            NoteLink::Wiki(name) => {
+                let file_name = if is_markdown_target(&name) { name.clone() } else { format!("{name}.md") };
+                let found = known_names.contains(&file_name.to_lowercase()) || base.join(&file_name).exists();
+                (!found).then_some(name)
+            }
+        })
+        .collect()
+}
+
+/// File I/O only, so callers can run it off the DB lock. Unreadable,
+/// oversized, and binary files are skipped.
+fn scan_broken_links(docs: &[(String, String)]) -> Vec<BrokenLinks> {
+    let known_paths: HashSet<String> = docs.iter().map(|(_, path)| path.clone()).collect();
+    let known_names: HashSet<String> = docs
+        .iter()
+        .filter_map(|(_, path)| Path::new(path).file_name())
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .collect();
+
+    docs.iter()
+        .filter_map(|(id, path)| {
+            let content = match read_text_file_guarded(Path::new(path)) {
+                Ok(content) => content?,
+                Err(e) => {
+                    eprintln!("find_broken_links: {e}");
+                    return None;
+                }
+            };
+            let links = find_broken_links_in(Path::new(path), &content, &known_paths, &known_names);
+            (!links.is_empty()).then(|| BrokenLinks {
+                document_id: id.clone(),
+                file_path: path.clone(),
+                links,
+            })
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub async fn list_attachments(state: tauri::State<'_, DbPool>, document_id: String) -> Result<AttachmentReport, String> {
+    let file_path: Option<String> = {
+        let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+        conn.query_row(
+            "SELECT file_path FROM documents WHERE id = ?1",
+            rusqlite::params![document_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?
+    };
+    let file_path = file_path.ok_or_else(|| "Document has no file on disk".to_string())?;
+    // Oversized or binary files have no references worth scanning
+    let Some(content) = read_text_file_guarded(Path::new(&file_path))? else {
+        return Ok(AttachmentReport::default());
+    };
+    Ok(resolve_attachments(Path::new(&file_path), &content))
+}
+
+#[tauri::command]
+pub async fn find_broken_links(state: tauri::State<'_, DbPool>) -> Result<Vec<BrokenLinks>, String> {
+    let docs = {
+        let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+        fetch_file_document_paths(&conn)?
+    };
+    Ok(scan_broken_links(&docs))
+}
+
 #[tauri::command]
 pub async fn rename_file(state: tauri::State<'_, DbPool>, old_path: String, new_name: String) -> Result<Document, String> {
     let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
     rename_file_inner(&conn, old_path, new_name)
 }
 
+#[tauri::command]
+pub async fn move_file(state: tauri::State<'_, DbPool>, old_path: String, new_dir: String) -> Result<Document, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    move_file_inner(&conn, &old_path, Path::new(&new_dir))
+}
+
+#[tauri::command]
+pub async fn create_file(
+    state: tauri::State<'_, DbPool>,
+    dir: String,
+    name: String,
+    initial_content: Option<String>,
+) -> Result<Document, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    let content = initial_content.as_deref().unwrap_or("");
+    let doc = create_file_inner(&conn, Path::new(&dir), &name, content)?;
+    if let Some(path) = &doc.file_path {
+        if let Err(e) = index_file_inner(&conn, path, content) {
+            eprintln!("create_file: failed to index {path}: {e}");
+        }
+    }
+    Ok(doc)
+}
+
+#[tauri::command]
+pub async fn delete_file(state: tauri::State<'_, DbPool>, path: String) -> Result<(), String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    delete_file_inner(&conn, &path, move_to_trash)
+}
+
+#[tauri::command]
+pub async fn duplicate_file(state: tauri::State<'_, DbPool>, path: String, new_name: String) -> Result<Document, String> {
+    let doc = {
+        let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+        duplicate_file_inner(&conn, &path, &new_name)?
+    };
+    if let Some(path) = &doc.file_path {
+        if let Err(e) = index_file_from_disk(&state, path) {
+            eprintln!("duplicate_file: failed to index {path}: {e}");
+        }
+    }
+    Ok(doc)
+}
+
+#[tauri::command]
+pub async fn import_directory(state: tauri::State<'_, DbPool>, dir: String) -> Result<ImportSummary, String> {
+    import_directory_inner(&state, Path::new(&dir))
+}
+
+#[tauri::command]
+pub async fn duplicate_document(
+    state: tauri::State<'_, DbPool>,
+    document_id: String,
+    new_name: String,
+) -> Result<Document, String> {
+    let doc = {
+        let mut conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+        duplicate_document_inner(&mut conn, &document_id, new_name)?
+    };
+    if let Some(path) = &doc.file_path {
+        if let Err(e) = index_file_from_disk(&state, path) {
+            eprintln!("duplicate_document: failed to index {path}: {e}");
+        }
+    }
+    Ok(doc)
+}
+
 pub fn collect_markdown_entries(dir: &Path) -> Result<Vec<FileEntry>, String> {
     let mut results = Vec::new();
 
@@ -177,9 +1056,65 @@ pub fn collect_markdown_entries(dir: &Path) -> Result<Vec<FileEntry>, String> {
     Ok(results)
 }
 
+/// Same output as `collect_markdown_entries`, but each top-level subdirectory
+/// is walked on one of up to `threads` workers.
+pub fn collect_markdown_entries_parallel(dir: &Path, threads: usize) -> Result<Vec<FileEntry>, String> {
+    if threads <= 1 {
+        return collect_markdown_entries(dir);
+    }
+
+    let read_dir =
+        fs::read_dir(dir).map_err(|e| format!("Failed to read directory '{}': {}", dir.display(), e))?;
+
+    let mut top_level = Vec::new();
+    for entry in read_dir {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        // Skip hidden files/dirs
+        if !name.starts_with('.') {
+            top_level.push((name, entry.path()));
+        }
+    }
+
+    let walked = parallel_map(top_level, threads, |(name, path)| -> Result<Vec<FileEntry>, String> {
+        if path.is_dir() {
+            let children = collect_markdown_entries(&path)?;
+            if children.is_empty() {
+                return Ok(Vec::new());
+            }
+            let mut out = vec![FileEntry {
+                name,
+                path: path.to_string_lossy().to_string(),
+                is_dir: true,
+            }];
+            out.extend(children);
+            return Ok(out);
+        }
+        let is_markdown = path.extension().is_some_and(|ext| {
+            let ext_lower = ext.to_string_lossy().to_lowercase();
+            ext_lower == "md" || ext_lower == "markdown"
+        });
+        if !is_markdown {
+            return Ok(Vec::new());
+        }
+        Ok(vec![FileEntry {
+            name,
+            path: path.to_string_lossy().to_string(),
+            is_dir: false,
+        }])
+    });
+
+    let mut results = Vec::new();
+    for group in walked {
+        results.extend(group?);
+    }
+    Ok(results)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::commands::documents::compute_word_counts;
     use rusqlite::Connection;
 
     fn make_test_dir(name: &str) -> std::path::PathBuf {
@@ -205,6 +1140,8 @@ mod tests {
                 created_at INTEGER NOT NULL DEFAULT 0,
                 access_count INTEGER DEFAULT 0,
                 indexed_at INTEGER,
+                reading_progress REAL NOT NULL DEFAULT 0,
+                custom_title INTEGER NOT NULL DEFAULT 0,
                 UNIQUE(file_path),
                 UNIQUE(keep_local_id)
             );",
@@ -323,6 +1260,214 @@ mod tests {
         assert_eq!(entries[1].name, "Beta.md");
     }
 
+    #[test]
+    fn parallel_walk_matches_serial_walk() {
+        let dir = make_test_dir("parallel_walk");
+        for sub in ["a", "b", "c"] {
+            fs::create_dir_all(dir.join(sub).join("nested")).unwrap();
+            fs::write(dir.join(sub).join("top.md"), "# top").unwrap();
+            fs::write(dir.join(sub).join("nested").join("deep.md"), "# deep").unwrap();
+        }
+        fs::create_dir_all(dir.join("empty")).unwrap();
+        fs::write(dir.join("root.md"), "# root").unwrap();
+        fs::write(dir.join(".hidden.md"), "# hidden").unwrap();
+
+        let paths = |entries: Vec<FileEntry>| {
+            let mut p: Vec<String> = entries.into_iter().map(|e| e.path).collect();
+            p.sort();
+            p
+        };
+        let serial = paths(collect_markdown_entries(&dir).unwrap());
+        assert_eq!(paths(collect_markdown_entries_parallel(&dir, 1).unwrap()), serial);
+        assert_eq!(paths(collect_markdown_entries_parallel(&dir, 4).unwrap()), serial);
+        assert_eq!(serial.len(), 1 + 3 * 4);
+    }
+
+    // === attachment tests ===
+
+    #[test]
+    fn collects_image_and_embed_refs_outside_code() {
+        let content = "# Doc\n\
+            ![diagram](img/diagram.png)\n\
+            ![with title](<my photo.jpg> \"Title\")\n\
+            ![[scan.pdf]] and [[sketch.png|alias]]\n\
+            [[Another note]] and [[other.md]]\n\
+            ![remote](https://example.com/x.png)\n\
+            `![inline](inline.png)`\n\
+            ```\n\
+            ![fenced](fenced.png)\n\
+            ```\n\
+            ![dup](img/diagram.png)\n";
+
+        assert_eq!(
+            collect_attachment_refs(content),
+            vec!["img/diagram.png", "my photo.jpg", "scan.pdf", "sketch.png"]
+        );
+    }
+
+    #[test]
+    fn resolves_attachments_relative_to_document() {
+        let dir = make_test_dir("attachments");
+        fs::create_dir_all(dir.join("img")).unwrap();
+        fs::write(dir.join("img").join("present.png"), b"png").unwrap();
+        let doc_path = dir.join("note.md");
+        let content = "![ok](img/present.png)\n![gone](img/missing.png)\n";
+
+        let report = resolve_attachments(&doc_path, content);
+        assert_eq!(report.existing, vec![dir.join("img/present.png").to_string_lossy().to_string()]);
+        assert_eq!(report.missing, vec![dir.join("img/missing.png").to_string_lossy().to_string()]);
+    }
+
+    // === save_file tests ===
+
+    #[test]
+    fn append_preserves_existing_content() {
+        let dir = make_test_dir("append_existing");
+        let path = dir.join("daily.md");
+        fs::write(&path, "# Today\n- first\n").unwrap();
+
+        let saved = saved_content(&path, "- captured\n", true).unwrap();
+        write_file_content(&path, &saved).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "# Today\n- first\n- captured\n");
+    }
+
+    #[test]
+    fn append_creates_missing_file_and_overwrite_replaces() {
+        let dir = make_test_dir("append_create");
+        let path = dir.join("new.md");
+
+        assert_eq!(saved_content(&path, "one\n", true).unwrap(), "one\n");
+        write_file_content(&path, "one\n").unwrap();
+        assert_eq!(saved_content(&path, "two\n", false).unwrap(), "two\n");
+        write_file_content(&path, "two\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "two\n");
+    }
+
+    #[test]
+    fn overwrite_goes_through_temp_file_and_leaves_none_behind() {
+        let dir = make_test_dir("atomic_save");
+        let path = dir.join("essay.md");
+        fs::write(&path, "old").unwrap();
+
+        write_file_content(&path, "new").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert!(!dir.join(".essay.md.tmp").exists());
+        let names: Vec<_> = fs::read_dir(&dir).unwrap().map(|e| e.unwrap().file_name()).collect();
+        assert_eq!(names.len(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn overwrite_keeps_symlink_and_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = make_test_dir("atomic_save_symlink");
+        let real = dir.join("real.md");
+        let link = dir.join("link.md");
+        fs::write(&real, "old").unwrap();
+        fs::set_permissions(&real, fs::Permissions::from_mode(0o600)).unwrap();
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+
+        write_file_content(&link, "new").unwrap();
+        assert!(fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+        assert_eq!(fs::read_to_string(&real).unwrap(), "new");
+        assert_eq!(fs::metadata(&real).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+
+    #[test]
+    fn backups_skip_missing_source_and_prune_oldest() {
+        let dir = make_test_dir("backups_prune");
+        let root = dir.join("backups");
+        let path = dir.join("essay.md");
+
+        assert_eq!(backup_file(&root, &path, 1, 3).unwrap(), None);
+        assert!(!root.exists());
+
+        for ts in 1..=5 {
+            fs::write(&path, format!("v{ts}")).unwrap();
+            backup_file(&root, &path, ts, 3).unwrap();
+        }
+        assert_eq!(list_backups_in(&root, &path).unwrap(), vec![3, 4, 5]);
+        assert_eq!(fs::read_to_string(backup_dir(&root, &path).unwrap().join("5.md")).unwrap(), "v5");
+    }
+
+    #[test]
+    fn backups_are_kept_apart_for_same_named_files() {
+        let dir = make_test_dir("backups_same_name");
+        let root = dir.join("backups");
+        let a = dir.join("a").join("notes.md");
+        let b = dir.join("b").join("notes.md");
+        for path in [&a, &b] {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+        }
+        fs::write(&a, "from a").unwrap();
+        fs::write(&b, "from b").unwrap();
+
+        backup_file(&root, &a, 1, BACKUPS_TO_KEEP).unwrap();
+        backup_file(&root, &b, 2, BACKUPS_TO_KEEP).unwrap();
+        assert_ne!(backup_dir(&root, &a).unwrap(), backup_dir(&root, &b).unwrap());
+        assert_eq!(list_backups_in(&root, &a).unwrap(), vec![1]);
+        assert_eq!(list_backups_in(&root, &b).unwrap(), vec![2]);
+
+        // The same file reached through a non-canonical path shares its backups
+        let indirect = dir.join("b").join("..").join("a").join("notes.md");
+        assert_eq!(list_backups_in(&root, &indirect).unwrap(), vec![1]);
+    }
+
+    #[test]
+    fn restore_backup_overwrites_and_backs_up_current() {
+        let dir = make_test_dir("backups_restore");
+        let root = dir.join("backups");
+        let path = dir.join("essay.md");
+        fs::write(&path, "good draft").unwrap();
+        backup_file(&root, &path, 100, BACKUPS_TO_KEEP).unwrap();
+        fs::write(&path, "bad save").unwrap();
+
+        assert_eq!(restore_backup_inner(&root, &path, 100, 200).unwrap(), "good draft");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "good draft");
+        assert_eq!(fs::read_to_string(backup_dir(&root, &path).unwrap().join("200.md")).unwrap(), "bad save");
+        assert!(restore_backup_inner(&root, &path, 999, 300).is_err());
+    }
+
+    #[test]
+    fn failed_overwrite_reports_path() {
+        let dir = make_test_dir("atomic_save_missing_dir");
+        let path = dir.join("gone").join("essay.md");
+
+        let err = write_file_content(&path, "text").unwrap_err();
+        assert!(err.starts_with("Failed to write file '"));
+        assert!(err.contains("essay.md"));
+    }
+
+    // === find_broken_links tests ===
+
+    #[test]
+    fn broken_links_report_only_unresolvable_targets() {
+        let dir = make_test_dir("broken_links");
+        let source = dir.join("source.md");
+        let sibling = dir.join("sibling.md");
+        fs::write(
+            &source,
+            "See [sibling](sibling.md#intro) and [[Elsewhere]].\n\
+             Broken: [gone](missing.md) and [[Nowhere|alias]].\n\
+             Adjacent: [one](sibling.md)[two](also-missing.md), [[v1.2 notes]] and [[Release v2.0]].\n\
+             Ignored: [site](https://example.com/a.md), `[[InCode]]`, [[diagram.png]], ![img](missing.md).\n",
+        )
+        .unwrap();
+        fs::write(&sibling, "no links here").unwrap();
+        fs::write(dir.join("v1.2 notes.md"), "dotted name").unwrap();
+        let docs = vec![
+            ("d1".to_string(), source.to_string_lossy().to_string()),
+            ("d2".to_string(), sibling.to_string_lossy().to_string()),
+            // Tracked document that resolves [[Elsewhere]] by name only
+            ("d3".to_string(), "/library/other/Elsewhere.md".to_string()),
+        ];
+
+        let report = scan_broken_links(&docs);
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].document_id, "d1");
+        assert_eq!(report[0].links, vec!["missing.md", "Nowhere", "also-missing.md", "Release v2.0"]);
+    }
+
     // === rename_file_inner tests ===
 
     #[test]
@@ -493,4 +1638,409 @@ mod tests {
             "new file should not exist after rollback"
         );
     }
+
+    #[test]
+    fn file_metadata_reports_size_and_mtime() {
+        let dir = make_test_dir("file_metadata");
+        let path = dir.join("sized.md");
+        fs::write(&path, "12345").unwrap();
+
+        let meta = file_metadata_inner(&path).unwrap();
+        assert_eq!(meta.size_bytes, 5);
+        assert!(meta.modified_ms.unwrap() > 0);
+
+        let err = file_metadata_inner(&dir.join("missing.md")).unwrap_err();
+        assert!(err.contains("does not exist"));
+    }
+
+    #[test]
+    fn move_file_relocates_and_updates_document() {
+        let dir = make_test_dir("move_file");
+        let sub_dir = dir.join("drafts");
+        fs::create_dir(&sub_dir).unwrap();
+        let conn = setup_db();
+        let doc = create_file_inner(&conn, &dir, "essay", "text").unwrap();
+        let old_path = doc.file_path.unwrap();
+
+        let moved = move_file_inner(&conn, &old_path, &sub_dir).unwrap();
+        let new_path = sub_dir.join("essay.md");
+        assert_eq!(moved.id, doc.id);
+        assert_eq!(moved.file_path.as_deref(), Some(new_path.to_string_lossy().as_ref()));
+        assert_eq!(moved.title.as_deref(), Some("essay"));
+        assert!(new_path.exists());
+        assert!(!Path::new(&old_path).exists());
+    }
+
+    #[test]
+    fn move_file_rejects_clobber_and_missing_dir() {
+        let dir = make_test_dir("move_file_rejects");
+        let sub_dir = dir.join("drafts");
+        fs::create_dir(&sub_dir).unwrap();
+        fs::write(sub_dir.join("essay.md"), "other").unwrap();
+        let conn = setup_db();
+        let doc = create_file_inner(&conn, &dir, "essay", "mine").unwrap();
+        let old_path = doc.file_path.unwrap();
+
+        assert!(move_file_inner(&conn, &old_path, &sub_dir).unwrap_err().contains("already exists"));
+        assert!(move_file_inner(&conn, &old_path, &dir.join("nope")).is_err());
+        assert_eq!(fs::read_to_string(&old_path).unwrap(), "mine");
+        assert_eq!(fs::read_to_string(sub_dir.join("essay.md")).unwrap(), "other");
+    }
+
+    #[test]
+    fn move_file_rolls_back_when_no_document_row_matches() {
+        let dir = make_test_dir("move_file_rollback");
+        let sub_dir = dir.join("drafts");
+        fs::create_dir(&sub_dir).unwrap();
+        let old = dir.join("loose.md");
+        fs::write(&old, "# loose").unwrap();
+        let conn = setup_db();
+
+        let err = move_file_inner(&conn, &old.to_string_lossy(), &sub_dir).unwrap_err();
+        assert!(err.contains("rolled back"));
+        assert!(old.exists());
+        assert!(!sub_dir.join("loose.md").exists());
+    }
+
+    #[test]
+    fn create_file_writes_content_and_records_document() {
+        let dir = make_test_dir("create_file");
+        let conn = setup_db();
+
+        let doc = create_file_inner(&conn, &dir, "ideas", "# Ideas\n\nthree words here").unwrap();
+        let path = dir.join("ideas.md");
+        assert_eq!(doc.file_path.as_deref(), Some(path.to_string_lossy().as_ref()));
+        assert_eq!(doc.title.as_deref(), Some("ideas"));
+        assert_eq!(doc.source, "file");
+        assert_eq!(doc.word_count, 5);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "# Ideas\n\nthree words here");
+
+        let stored: String = conn
+            .query_row("SELECT id FROM documents WHERE file_path = ?1", [path.to_string_lossy()], |row| row.get(0))
+            .unwrap();
+        assert_eq!(stored, doc.id);
+    }
+
+    #[test]
+    fn create_file_validates_name() {
+        let dir = make_test_dir("create_file_names");
+        let conn = setup_db();
+
+        assert!(create_file_inner(&conn, &dir, "  ", "").is_err());
+        assert!(create_file_inner(&conn, &dir, "sub/escape", "").is_err());
+        assert!(create_file_inner(&conn, &dir, "..\\escape", "").is_err());
+        assert!(create_file_inner(&conn, &dir.join("missing"), "note", "").is_err());
+
+        let doc = create_file_inner(&conn, &dir, "notes.markdown", "").unwrap();
+        assert!(doc.file_path.unwrap().ends_with("notes.markdown"));
+    }
+
+    #[test]
+    fn create_file_refuses_to_overwrite() {
+        let dir = make_test_dir("create_file_exists");
+        fs::write(dir.join("taken.md"), "keep me").unwrap();
+        let conn = setup_db();
+
+        let err = create_file_inner(&conn, &dir, "taken", "new").unwrap_err();
+        assert!(err.contains("already exists"));
+        assert_eq!(fs::read_to_string(dir.join("taken.md")).unwrap(), "keep me");
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM documents", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn save_upsert_and_recompute_agree_on_word_count() {
+        let dir = make_test_dir("word_count_paths");
+        let content = "---\ntitle: Counted\n---\nFour words of prose.\n\n```\nlet skipped = true;\n```\n";
+        let path = dir.join("counted.md");
+        fs::write(&path, content).unwrap();
+        let path = path.to_string_lossy().to_string();
+        let conn = setup_db();
+        let stored = |conn: &Connection| -> i64 {
+            conn.query_row("SELECT word_count FROM documents WHERE file_path = ?1", [&path], |row| row.get(0))
+                .unwrap()
+        };
+
+        let doc = Document {
+            id: String::new(),
+            source: "file".to_string(),
+            file_path: Some(path.clone()),
+            keep_local_id: None,
+            title: None,
+            author: None,
+            url: None,
+            word_count: 99,
+            last_opened_at: 0,
+            created_at: 0,
+            reading_progress: 0.0,
+        };
+        let upserted = upsert_document_inner(&conn, doc).unwrap().word_count;
+
+        conn.execute("UPDATE documents SET word_count = 0", []).unwrap();
+        assert!(refresh_saved_document(&conn, &path, content).unwrap());
+        let saved = stored(&conn);
+
+        let id: String = conn.query_row("SELECT id FROM documents", [], |row| row.get(0)).unwrap();
+        let recomputed = compute_word_counts(&[(id, path.clone())])[0].1;
+
+        assert_eq!(upserted, 4);
+        assert_eq!(saved, 4);
+        assert_eq!(recomputed, 4);
+    }
+
+    #[test]
+    fn import_directory_records_untracked_files() {
+        let dir = make_test_dir("import_directory");
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::write(dir.join("plain.md"), "one two three").unwrap();
+        fs::write(dir.join("nested/titled.md"), "---\ntitle: Real Title\n---\nfour words of body").unwrap();
+        fs::write(dir.join("notes.txt"), "not markdown").unwrap();
+        let conn = setup_db();
+        let tracked = create_file_inner(&conn, &dir, "tracked", "already here").unwrap();
+        let pool = DbPool(Mutex::new(conn));
+
+        let summary = import_directory_inner(&pool, &dir).unwrap();
+        assert_eq!((summary.imported, summary.skipped), (2, 1));
+        let conn = pool.0.lock().unwrap();
+
+        let titled: (String, i64) = conn
+            .query_row(
+                "SELECT title, word_count FROM documents WHERE file_path = ?1",
+                [dir.join("nested/titled.md").to_string_lossy()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(titled, ("Real Title".to_string(), 4));
+        // Indexed from the content read during the scan
+        let indexed: Option<String> = conn
+            .query_row(
+                "SELECT f.content FROM documents_fts f JOIN documents d ON d.id = f.document_id WHERE d.file_path = ?1",
+                [dir.join("plain.md").to_string_lossy()],
+                |row| row.get(0),
+            )
+            .ok();
+        assert_eq!(indexed.as_deref(), Some("one two three"));
+        let plain: String = conn
+            .query_row("SELECT title FROM documents WHERE file_path = ?1", [dir.join("plain.md").to_string_lossy()], |row| row.get(0))
+            .unwrap();
+        assert_eq!(plain, "plain");
+        let tracked_id: String = conn
+            .query_row("SELECT id FROM documents WHERE file_path = ?1", [tracked.file_path.unwrap()], |row| row.get(0))
+            .unwrap();
+        assert_eq!(tracked_id, tracked.id);
+
+        // A second run finds nothing new
+        drop(conn);
+        let summary = import_directory_inner(&pool, &dir).unwrap();
+        assert_eq!((summary.imported, summary.skipped), (0, 3));
+        assert!(import_directory_inner(&pool, &dir.join("missing")).is_err());
+    }
+
+    #[test]
+    fn duplicate_file_copies_text_as_new_document() {
+        let dir = make_test_dir("duplicate_file");
+        let conn = setup_db();
+        let original = create_file_inner(&conn, &dir, "draft", "# draft\n\nbody").unwrap();
+
+        let copy = duplicate_file_inner(&conn, original.file_path.as_deref().unwrap(), "variant").unwrap();
+        assert_ne!(copy.id, original.id);
+        assert_eq!(copy.title.as_deref(), Some("variant"));
+        assert_eq!(fs::read_to_string(dir.join("variant.md")).unwrap(), "# draft\n\nbody");
+    }
+
+    #[test]
+    fn duplicate_file_picks_copy_name_on_collision() {
+        let dir = make_test_dir("duplicate_file_collision");
+        let conn = setup_db();
+        let original = create_file_inner(&conn, &dir, "draft", "text").unwrap();
+        let path = original.file_path.unwrap();
+
+        let first = duplicate_file_inner(&conn, &path, "draft").unwrap();
+        assert!(first.file_path.unwrap().ends_with("draft (copy).md"));
+        let second = duplicate_file_inner(&conn, &path, "draft.md").unwrap();
+        assert!(second.file_path.unwrap().ends_with("draft (copy 2).md"));
+        assert!(duplicate_file_inner(&conn, &path, "a/b").is_err());
+    }
+
+    #[test]
+    fn delete_file_trashes_file_and_removes_document() {
+        let dir = make_test_dir("delete_file");
+        let trash = dir.join("Trash");
+        fs::create_dir(&trash).unwrap();
+        let conn = setup_db();
+        let doc = create_file_inner(&conn, &dir, "gone", "# bye").unwrap();
+        let path = doc.file_path.unwrap();
+
+        // Stands in for the system Trash so the test doesn't fill the real one
+        let to_test_trash = |file: &Path| fs::rename(file, trash.join("gone.md")).map_err(|e| e.to_string());
+        delete_file_inner(&conn, &path, to_test_trash).unwrap();
+        assert!(!Path::new(&path).exists());
+        assert_eq!(fs::read_to_string(trash.join("gone.md")).unwrap(), "# bye");
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM documents", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn delete_file_errors_when_missing_and_keeps_row() {
+        let dir = make_test_dir("delete_file_missing");
+        let conn = setup_db();
+        let doc = create_file_inner(&conn, &dir, "ghost", "").unwrap();
+        let path = doc.file_path.unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let err = delete_file_inner(&conn, &path, |_| panic!("nothing to trash")).unwrap_err();
+        assert!(err.contains("does not exist"));
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM documents", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn delete_file_keeps_file_and_row_when_trashing_fails() {
+        let dir = make_test_dir("delete_file_trash_fails");
+        let conn = setup_db();
+        let doc = create_file_inner(&conn, &dir, "kept", "# still here").unwrap();
+        let path = doc.file_path.unwrap();
+
+        let err = delete_file_inner(&conn, &path, |_| Err("Trash unavailable".to_string())).unwrap_err();
+        assert_eq!(err, "Trash unavailable");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "# still here");
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM documents", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn duplicate_copies_file_and_annotations_with_new_ids() {
+        let dir = make_test_dir("duplicate_document");
+        let original = dir.join("draft.md");
+        fs::write(&original, "# draft\n\nSome text").unwrap();
+        let mut conn = setup_db();
+        conn.execute_batch(
+            "CREATE TABLE highlights (
+                id TEXT PRIMARY KEY,
+                document_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+                color TEXT NOT NULL,
+                text_content TEXT NOT NULL,
+                from_pos INTEGER NOT NULL,
+                to_pos INTEGER NOT NULL,
+                prefix_context TEXT,
+                suffix_context TEXT,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL,
+                deleted_at INTEGER
+            );
+            CREATE TABLE margin_notes (
+                id TEXT PRIMARY KEY,
+                highlight_id TEXT NOT NULL REFERENCES highlights(id) ON DELETE CASCADE,
+                content TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL,
+                parent_note_id TEXT REFERENCES margin_notes(id) ON DELETE CASCADE
+            );
+            CREATE TABLE highlight_tags (
+                highlight_id TEXT NOT NULL REFERENCES highlights(id) ON DELETE CASCADE,
+                tag TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                PRIMARY KEY (highlight_id, tag)
+            );",
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO documents (id, source, file_path, title, word_count, last_opened_at, created_at)
+             VALUES ('d1', 'file', ?1, 'draft', 3, 1000, 1000)",
+            rusqlite::params![original.to_string_lossy().to_string()],
+        )
+        .unwrap();
+        conn.execute_batch(
+            "INSERT INTO highlights VALUES ('h1', 'd1', 'yellow', 'Some', 9, 13, NULL, NULL, 1, 1, NULL);
+             INSERT INTO highlights VALUES ('h2', 'd1', 'green', 'text', 14, 18, NULL, NULL, 1, 1, NULL);
+             INSERT INTO highlight_tags VALUES ('h1', 'quote', 1);
+             INSERT INTO margin_notes VALUES ('n1', 'h1', 'first', 1, 1, NULL);
+             -- A reply older than its parent, as after a merge moved it
+             INSERT INTO margin_notes VALUES ('n2', 'h1', 'second', 0, 0, 'n1');",
+        )
+        .unwrap();
+
+        let copy = duplicate_document_inner(&mut conn, "d1", "draft copy".to_string()).unwrap();
+        assert_ne!(copy.id, "d1");
+        assert_eq!(copy.title.as_deref(), Some("draft copy"));
+        assert_eq!(copy.word_count, 3);
+        let copy_path = copy.file_path.clone().unwrap();
+        assert!(copy_path.ends_with("draft copy.md"));
+        assert_eq!(fs::read_to_string(&copy_path).unwrap(), "# draft\n\nSome text");
+
+        let copied: Vec<(String, String, i64)> = conn
+            .prepare("SELECT id, color, from_pos FROM highlights WHERE document_id = ?1 ORDER BY from_pos")
+            .unwrap()
+            .query_map([&copy.id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(copied.len(), 2);
+        assert!(copied.iter().all(|(id, _, _)| id != "h1" && id != "h2"));
+        assert_eq!((copied[0].1.as_str(), copied[0].2), ("yellow", 9));
+
+        let notes: Vec<String> = conn
+            .prepare("SELECT id FROM margin_notes WHERE highlight_id = ?1 ORDER BY content")
+            .unwrap()
+            .query_map([&copied[0].0], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(notes.len(), 2);
+        assert!(notes.iter().all(|id| id != "n1" && id != "n2"));
+        let reply_parent: Option<String> = conn
+            .query_row("SELECT parent_note_id FROM margin_notes WHERE id = ?1", [&notes[1]], |row| row.get(0))
+            .unwrap();
+        assert_eq!(reply_parent.as_deref(), Some(notes[0].as_str()));
+        let tags: Vec<String> = conn
+            .prepare("SELECT tag FROM highlight_tags WHERE highlight_id = ?1")
+            .unwrap()
+            .query_map([&copied[0].0], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(tags, vec!["quote"]);
+
+        let originals: i64 = conn
+            .query_row("SELECT COUNT(*) FROM highlights WHERE document_id = 'd1'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(originals, 2);
+    }
+
+    #[test]
+    fn duplicate_rejects_existing_target() {
+        let dir = make_test_dir("duplicate_existing");
+        let original = dir.join("a.md");
+        fs::write(&original, "a").unwrap();
+        fs::write(dir.join("b.md"), "b").unwrap();
+        let mut conn = setup_db();
+        conn.execute(
+            "INSERT INTO documents (id, source, file_path, title) VALUES ('d1', 'file', ?1, 'a')",
+            rusqlite::params![original.to_string_lossy().to_string()],
+        )
+        .unwrap();
+
+        let err = duplicate_document_inner(&mut conn, "d1", "b".to_string()).unwrap_err();
+        assert!(err.contains("already exists"));
+        assert_eq!(fs::read_to_string(dir.join("b.md")).unwrap(), "b");
+    }
+
+    #[test]
+    fn workspace_tree_rejects_files_and_lists_directories() {
+        let dir = make_test_dir("workspace_tree");
+        let file = dir.join("note.md");
+        fs::write(&file, "# note").unwrap();
+        fs::create_dir_all(dir.join("drafts")).unwrap();
+        fs::write(dir.join("drafts").join("a.md"), "a").unwrap();
+
+        let err = list_markdown_tree(&file, 2).err().unwrap();
+        assert!(err.contains("is not a directory"));
+
+        let entries = list_markdown_tree(&dir, 2).unwrap();
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names[0], "drafts");
+        assert!(entries[0].is_dir);
+        assert!(names.contains(&"note.md"));
+        assert!(names.contains(&"a.md"));
+    }
 }
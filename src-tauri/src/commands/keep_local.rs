@@ -1,4 +1,12 @@
+use crate::commands::documents::{count_prose_words, upsert_document_inner};
+use crate::commands::now_millis;
+use crate::commands::search::index_document_inner;
+use crate::db::migrations::{data_dir, DbPool};
+use crate::db::models::Document;
+use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
 use tauri::State;
 
 const BASE_URL: &str = "http://127.0.0.1:8787";
@@ -32,10 +40,10 @@ pub struct KeepLocalItem {
     pub content_available: bool,
 }
 
+/// Items stay raw JSON so each one can be parsed on its own.
 #[derive(Deserialize)]
 struct ItemsResponse {
-    items: Vec<KeepLocalItem>,
-    #[allow(dead_code)]
+    items: Vec<serde_json::Value>,
     count: i64,
 }
 
@@ -44,6 +52,28 @@ struct ItemsResponse {
 pub struct KeepLocalListResult {
     pub items: Vec<KeepLocalItem>,
     pub count: i64,
+    /// Items skipped because they didn't match `KeepLocalItem`.
+    pub failed_count: usize,
+}
+
+/// Parses items one at a time so a single odd item doesn't blank the list.
+fn parse_items_response(data: ItemsResponse) -> KeepLocalListResult {
+    let mut items = Vec::with_capacity(data.items.len());
+    let mut failed_count = 0;
+    for value in data.items {
+        match serde_json::from_value::<KeepLocalItem>(value) {
+            Ok(item) => items.push(item),
+            Err(e) => {
+                eprintln!("keep_local_list_items: skipping unparseable item: {e}");
+                failed_count += 1;
+            }
+        }
+    }
+    KeepLocalListResult {
+        items,
+        count: data.count,
+        failed_count,
+    }
 }
 
 #[tauri::command]
@@ -111,20 +141,12 @@ pub async fn keep_local_list_items(
         .await
         .map_err(|e| format!("Failed to parse items response: {e}"))?;
 
-    Ok(KeepLocalListResult {
-        count: data.count,
-        items: data.items,
-    })
+    Ok(parse_items_response(data))
 }
 
-#[tauri::command]
-pub async fn keep_local_get_item(
-    client: State<'_, HttpClient>,
-    item_id: String,
-) -> Result<KeepLocalItem, String> {
-    let safe_id = urlencoding(&item_id);
+async fn fetch_item(client: &reqwest::Client, item_id: &str) -> Result<KeepLocalItem, String> {
+    let safe_id = urlencoding(item_id);
     let resp = client
-        .0
         .get(format!("{BASE_URL}/api/items/{safe_id}?content=0"))
         .send()
         .await
@@ -138,14 +160,9 @@ pub async fn keep_local_get_item(
         .map_err(|e| format!("Failed to parse item response: {e}"))
 }
 
-#[tauri::command]
-pub async fn keep_local_get_content(
-    client: State<'_, HttpClient>,
-    item_id: String,
-) -> Result<String, String> {
-    let safe_id = urlencoding(&item_id);
+async fn fetch_content(client: &reqwest::Client, item_id: &str) -> Result<String, String> {
+    let safe_id = urlencoding(item_id);
     let resp = client
-        .0
         .get(format!("{BASE_URL}/api/items/{safe_id}/content"))
         .send()
         .await
@@ -168,6 +185,141 @@ pub async fn keep_local_get_content(
     Ok(body)
 }
 
+/// `~/.margin/keep-local-cache`, holding the last fetched content of each item.
+fn cache_root() -> Result<PathBuf, String> {
+    Ok(data_dir().map_err(|e| e.to_string())?.join("keep-local-cache"))
+}
+
+/// Item ids are percent-encoded so they can't escape the cache directory.
+fn cache_path(root: &Path, item_id: &str) -> PathBuf {
+    root.join(format!("{}.md", urlencoding(item_id)))
+}
+
+fn read_cached_content(root: &Path, item_id: &str) -> Option<String> {
+    fs::read_to_string(cache_path(root, item_id)).ok().filter(|body| !body.is_empty())
+}
+
+/// The last fetched content of a keep-local item, if it is cached.
+pub(crate) fn cached_content(item_id: &str) -> Option<String> {
+    read_cached_content(&cache_root().ok()?, item_id)
+}
+
+fn write_cached_content(root: &Path, item_id: &str, content: &str) -> Result<(), String> {
+    fs::create_dir_all(root).map_err(|e| format!("Failed to create cache directory: {e}"))?;
+    fs::write(cache_path(root, item_id), content).map_err(|e| format!("Failed to write cached content: {e}"))
+}
+
+/// Removes every cached item; returns how many were removed.
+fn clear_cache_inner(root: &Path) -> Result<usize, String> {
+    let entries = match fs::read_dir(root) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(format!("Failed to read cache directory: {e}")),
+    };
+    let mut removed = 0;
+    for entry in entries {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.extension().is_some_and(|ext| ext == "md") {
+            fs::remove_file(&path).map_err(|e| format!("Failed to remove {}: {e}", path.display()))?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Upserts the `documents` row for a keep-local item (matched on
+/// `keep_local_id`, keeping its original `created_at`) and indexes `content`.
+fn import_keep_local_document(conn: &Connection, item: &KeepLocalItem, content: &str, now: i64) -> Result<Document, String> {
+    let existing_created_at: Option<i64> = match conn.query_row(
+        "SELECT created_at FROM documents WHERE keep_local_id = ?1",
+        [&item.id],
+        |row| row.get(0),
+    ) {
+        Ok(created_at) => Some(created_at),
+        Err(rusqlite::Error::QueryReturnedNoRows) => None,
+        Err(e) => return Err(e.to_string()),
+    };
+
+    let word_count = if item.word_count > 0 { item.word_count } else { count_prose_words(content) };
+    let doc = upsert_document_inner(
+        conn,
+        Document {
+            id: String::new(),
+            source: "keep-local".to_string(),
+            file_path: None,
+            keep_local_id: Some(item.id.clone()),
+            title: item.title.clone(),
+            author: item.author.clone(),
+            url: Some(item.url.clone()),
+            word_count,
+            last_opened_at: now,
+            created_at: existing_created_at.unwrap_or(now),
+            reading_progress: 0.0,
+        },
+    )?;
+    index_document_inner(conn, &doc.id, doc.title.as_deref().unwrap_or("Untitled"), content)?;
+    Ok(doc)
+}
+
+#[tauri::command]
+pub async fn keep_local_get_item(
+    client: State<'_, HttpClient>,
+    item_id: String,
+) -> Result<KeepLocalItem, String> {
+    fetch_item(&client.0, &item_id).await
+}
+
+/// Serves cached content straight away and refreshes it in the background;
+/// without a cache entry it fetches and caches. A cache write failure only logs.
+#[tauri::command]
+pub async fn keep_local_get_content(
+    client: State<'_, HttpClient>,
+    item_id: String,
+) -> Result<String, String> {
+    let root = cache_root()?;
+
+    if let Some(cached) = read_cached_content(&root, &item_id) {
+        let client = client.0.clone();
+        tauri::async_runtime::spawn(async move {
+            // Server down: keep serving the cached copy
+            if let Ok(body) = fetch_content(&client, &item_id).await {
+                if let Err(e) = write_cached_content(&root, &item_id, &body) {
+                    eprintln!("keep_local_get_content: {e}");
+                }
+            }
+        });
+        return Ok(cached);
+    }
+
+    let body = fetch_content(&client.0, &item_id).await?;
+    if let Err(e) = write_cached_content(&root, &item_id, &body) {
+        eprintln!("keep_local_get_content: {e}");
+    }
+    Ok(body)
+}
+
+#[tauri::command]
+pub async fn clear_keep_local_cache() -> Result<usize, String> {
+    clear_cache_inner(&cache_root()?)
+}
+
+/// Fetches the item and its content, then records it as a Margin document so
+/// highlights attach to a stable id.
+#[tauri::command]
+pub async fn import_keep_local_item(
+    client: State<'_, HttpClient>,
+    db: State<'_, DbPool>,
+    item_id: String,
+) -> Result<Document, String> {
+    let item = fetch_item(&client.0, &item_id).await?;
+    let content = fetch_content(&client.0, &item_id).await?;
+    if let Err(e) = cache_root().and_then(|root| write_cached_content(&root, &item_id, &content)) {
+        eprintln!("import_keep_local_item: {e}");
+    }
+    let conn = db.0.lock().unwrap_or_else(|e| e.into_inner());
+    import_keep_local_document(&conn, &item, &content, now_millis())
+}
+
 /// Simple percent-encoding for query parameter values.
 fn urlencoding(s: &str) -> String {
     let mut result = String::with_capacity(s.len());
@@ -188,6 +340,110 @@ fn urlencoding(s: &str) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn malformed_item_is_skipped_and_counted() {
+        let body = r#"{
+            "items": [
+                {"id": "a", "url": "https://a.example", "createdAt": 1, "status": "unread"},
+                {"id": "b", "url": "https://b.example", "createdAt": "yesterday", "status": "unread"},
+                {"id": "c", "url": "https://c.example", "createdAt": 3, "status": "read", "wordCount": 120}
+            ],
+            "count": 3
+        }"#;
+        let data: ItemsResponse = serde_json::from_str(body).unwrap();
+
+        let result = parse_items_response(data);
+        let ids: Vec<&str> = result.items.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "c"]);
+        assert_eq!(result.items[1].word_count, 120);
+        assert_eq!(result.failed_count, 1);
+        assert_eq!(result.count, 3);
+    }
+
+    fn setup_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE documents (
+                 id TEXT PRIMARY KEY,
+                 source TEXT NOT NULL,
+                 file_path TEXT,
+                 keep_local_id TEXT,
+                 title TEXT,
+                 author TEXT,
+                 url TEXT,
+                 word_count INTEGER DEFAULT 0,
+                 last_opened_at INTEGER NOT NULL,
+                 created_at INTEGER NOT NULL,
+                 reading_progress REAL NOT NULL DEFAULT 0,
+                 custom_title INTEGER NOT NULL DEFAULT 0,
+                 UNIQUE(file_path),
+                 UNIQUE(keep_local_id)
+             );",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn make_item(id: &str, title: &str, word_count: i64) -> KeepLocalItem {
+        KeepLocalItem {
+            id: id.to_string(),
+            url: format!("https://example.com/{id}"),
+            title: Some(title.to_string()),
+            author: Some("Ada".to_string()),
+            domain: Some("example.com".to_string()),
+            platform: None,
+            word_count,
+            tags: Vec::new(),
+            created_at: 1,
+            status: "unread".to_string(),
+            content_available: true,
+        }
+    }
+
+    #[test]
+    fn import_upserts_one_document_per_item() {
+        let conn = setup_db();
+
+        let first = import_keep_local_document(&conn, &make_item("kl-1", "Essay", 0), "three short words", 1000).unwrap();
+        assert_eq!(first.source, "keep-local");
+        assert_eq!(first.keep_local_id.as_deref(), Some("kl-1"));
+        assert_eq!(first.url.as_deref(), Some("https://example.com/kl-1"));
+        assert_eq!(first.author.as_deref(), Some("Ada"));
+        assert_eq!(first.word_count, 3);
+
+        // Re-importing updates the same row and keeps when it was first added
+        let again = import_keep_local_document(&conn, &make_item("kl-1", "Essay, revised", 250), "new text", 2000).unwrap();
+        assert_eq!(again.id, first.id);
+        assert_eq!(again.title.as_deref(), Some("Essay, revised"));
+        assert_eq!(again.word_count, 250);
+        assert_eq!(again.created_at, 1000);
+        assert_eq!(again.last_opened_at, 2000);
+
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM documents", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 1);
+        let indexed: i64 = conn
+            .query_row("SELECT COUNT(*) FROM documents_fts WHERE documents_fts MATCH 'revised'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(indexed, 1);
+    }
+
+    #[test]
+    fn content_cache_round_trips_and_clears() {
+        let root = std::env::temp_dir().join("margin_test_keep_local_cache");
+        let _ = fs::remove_dir_all(&root);
+        assert_eq!(clear_cache_inner(&root).unwrap(), 0);
+        assert_eq!(read_cached_content(&root, "kl-1"), None);
+
+        write_cached_content(&root, "kl-1", "# Article").unwrap();
+        write_cached_content(&root, "../escape", "elsewhere").unwrap();
+        assert_eq!(read_cached_content(&root, "kl-1").as_deref(), Some("# Article"));
+        assert_eq!(cache_path(&root, "../escape").parent(), Some(root.as_path()));
+        assert_eq!(read_cached_content(&root, "../escape").as_deref(), Some("elsewhere"));
+
+        assert_eq!(clear_cache_inner(&root).unwrap(), 2);
+        assert_eq!(read_cached_content(&root, "kl-1"), None);
+    }
+
     #[test]
     fn alphanumerics_pass_through_unchanged() {
         assert_eq!(urlencoding("abc123"), "abc123");
@@ -1,11 +1,13 @@
 pub mod annotations;
 pub mod corrections;
 pub mod dashboard;
+pub mod diagnostics;
 pub mod documents;
 pub mod files;
 pub mod keep_local;
 pub mod search;
 pub mod seed_rules;
+pub mod settings;
 pub mod snapshots;
 pub mod tabs;
 pub mod writing_rules;
@@ -17,3 +19,71 @@ pub(crate) fn now_millis() -> i64 {
         .unwrap_or_default()
         .as_millis() as i64
 }
+
+/// Maps `f` over `items` on at most `threads` scoped worker threads, preserving input order.
+/// With one thread (or one item) this runs inline on the caller's thread.
+pub(crate) fn parallel_map<T, R, F>(items: Vec<T>, threads: usize, f: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+{
+    let threads = threads.clamp(1, items.len().max(1));
+    if threads == 1 {
+        return items.into_iter().map(f).collect();
+    }
+
+    let total = items.len();
+    let queue = std::sync::Mutex::new(items.into_iter().enumerate());
+    let results = std::sync::Mutex::new(Vec::with_capacity(total));
+
+    std::thread::scope(|s| {
+        for _ in 0..threads {
+            s.spawn(|| loop {
+                let next = queue.lock().unwrap_or_else(|e| e.into_inner()).next();
+                let Some((idx, item)) = next else { break };
+                let out = f(item);
+                results.lock().unwrap_or_else(|e| e.into_inner()).push((idx, out));
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap_or_else(|e| e.into_inner());
+    results.sort_by_key(|(idx, _)| *idx);
+    results.into_iter().map(|(_, out)| out).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn max_concurrency(threads: usize) -> usize {
+        let active = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        parallel_map((0..16).collect(), threads, |_: i32| {
+            let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            active.fetch_sub(1, Ordering::SeqCst);
+        });
+        peak.load(Ordering::SeqCst)
+    }
+
+    #[test]
+    fn parallel_map_preserves_order() {
+        let out = parallel_map((0..50).collect(), 4, |n: i32| n * 2);
+        assert_eq!(out, (0..50).map(|n| n * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn parallel_map_respects_thread_limit() {
+        assert_eq!(max_concurrency(1), 1);
+        assert!(max_concurrency(3) <= 3);
+    }
+
+    #[test]
+    fn parallel_map_treats_zero_threads_as_one() {
+        assert_eq!(max_concurrency(0), 1);
+    }
+}
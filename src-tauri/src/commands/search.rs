@@ -1,7 +1,16 @@
+use crate::commands::documents::read_text_file_guarded;
+use crate::commands::keep_local::cached_content;
+use crate::commands::settings::{get_setting, index_read_timeout, scan_threads, set_setting, workspace_dir};
 use crate::commands::now_millis;
-use crate::db::migrations::DbPool;
+use crate::db::migrations::{DbPool, CREATE_DOCUMENTS_FTS};
+use crate::front_matter::parse_front_matter;
 use rusqlite::Connection;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
 
 #[derive(serde::Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -10,6 +19,90 @@ pub struct SearchResult {
     pub title: String,
     pub snippet: String,
     pub rank: f64,
+    /// `None` for documents without a file on disk (e.g. keep-local items).
+    pub file_path: Option<String>,
+    pub source: Option<String>,
+}
+
+/// How `snippet()` renders search results.
+#[derive(Debug, PartialEq)]
+struct SnippetOptions {
+    tokens: i32,
+    open_tag: String,
+    close_tag: String,
+}
+
+impl Default for SnippetOptions {
+    fn default() -> Self {
+        SnippetOptions {
+            tokens: 32,
+            open_tag: "<mark>".to_string(),
+            close_tag: "</mark>".to_string(),
+        }
+    }
+}
+
+impl SnippetOptions {
+    /// Unset values keep the defaults; `tokens` is clamped to the 1–64 range
+    /// FTS5 accepts. Tags containing quotes are rejected.
+    fn new(tokens: Option<i32>, open_tag: Option<String>, close_tag: Option<String>) -> Result<Self, String> {
+        let defaults = SnippetOptions::default();
+        let open_tag = open_tag.unwrap_or(defaults.open_tag);
+        let close_tag = close_tag.unwrap_or(defaults.close_tag);
+        if [&open_tag, &close_tag].iter().any(|tag| tag.contains(['"', '\''])) {
+            return Err("Snippet tags cannot contain quotes".to_string());
+        }
+        Ok(SnippetOptions {
+            tokens: tokens.unwrap_or(defaults.tokens).clamp(1, 64),
+            open_tag,
+            close_tag,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct SearchOptions {
+    /// Accept phrase / boolean syntax (see `advanced_fts_query`).
+    advanced: bool,
+    /// Blend recency and open count into the BM25 rank. Off means pure relevance.
+    frecency: bool,
+    /// Match whole tokens only instead of prefixes.
+    whole_word: bool,
+    snippet: SnippetOptions,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            advanced: false,
+            frecency: true,
+            whole_word: false,
+            snippet: SnippetOptions::default(),
+        }
+    }
+}
+
+/// One hit inside a single document. `offset` and `length` are in UTF-16
+/// units of the document's original content, like editor positions, so they
+/// point at the match itself regardless of the ellipses added to `snippet`.
+#[derive(serde::Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentMatch {
+    pub offset: usize,
+    pub length: usize,
+    pub snippet: String,
+}
+
+/// A margin note matching a search, with the passage it annotates.
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteSearchResult {
+    pub note_id: String,
+    pub snippet: String,
+    pub highlight_id: String,
+    pub highlight_text: String,
+    pub document_id: String,
+    pub document_title: Option<String>,
 }
 
 #[derive(serde::Serialize)]
@@ -27,16 +120,21 @@ pub struct IndexAllResult {
     pub errors: usize,
 }
 
-/// Search all .md files on the machine using macOS Spotlight (mdfind).
-/// Matches filename OR content.
-#[tauri::command]
-pub fn search_files_on_disk(query: String, limit: Option<usize>) -> Result<Vec<FileSearchResult>, String> {
-    let limit = limit.unwrap_or(20);
+/// The most recent `index_all_documents` result, persisted in `settings`.
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexSummary {
+    pub indexed: usize,
+    pub skipped: usize,
+    pub errors: usize,
+    pub finished_at: i64,
+}
 
-    if query.trim().is_empty() {
-        return Ok(Vec::new());
-    }
+const LAST_INDEX_SUMMARY_KEY: &str = "last_index_summary";
 
+/// Search all .md files on the machine using macOS Spotlight (mdfind).
+/// Matches filename OR content. `None` when mdfind isn't available.
+fn spotlight_search(query: &str, limit: usize) -> Option<Vec<FileSearchResult>> {
     // Strip single quotes to prevent mdfind query injection
     let safe_query = query.replace('\'', "");
 
@@ -46,13 +144,10 @@ pub fn search_files_on_disk(query: String, limit: Option<usize>) -> Result<Vec<F
         safe_query, safe_query
     );
 
-    let output = Command::new("mdfind")
-        .arg(&mdfind_query)
-        .output()
-        .map_err(|e| format!("Failed to run mdfind: {e}"))?;
+    let output = Command::new("mdfind").arg(&mdfind_query).output().ok()?;
 
     if !output.status.success() {
-        return Ok(Vec::new());
+        return Some(Vec::new());
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
@@ -72,18 +167,97 @@ pub fn search_files_on_disk(query: String, limit: Option<usize>) -> Result<Vec<F
         })
         .collect();
 
-    Ok(results)
+    Some(results)
+}
+
+/// How deep the fallback walk descends below its root.
+const DISK_SEARCH_MAX_DEPTH: usize = 12;
+
+/// Fallback for `spotlight_search`: walks `root` for markdown files whose
+/// name or content contains `query` (case-insensitive), skipping hidden
+/// entries like `collect_markdown_entries` does.
+fn walk_search(root: &Path, query: &str, limit: usize) -> Vec<FileSearchResult> {
+    let needle = query.trim().to_lowercase();
+    let mut results = Vec::new();
+    let mut stack = vec![(root.to_path_buf(), 0)];
+
+    while let Some((dir, depth)) = stack.pop() {
+        let Ok(read_dir) = std::fs::read_dir(&dir) else { continue };
+        let mut entries: Vec<_> = read_dir.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+        entries.sort();
+
+        for path in entries {
+            if path.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.')) {
+                continue;
+            }
+            if path.is_dir() {
+                if depth < DISK_SEARCH_MAX_DEPTH {
+                    stack.push((path, depth + 1));
+                }
+                continue;
+            }
+            let is_markdown = path.extension().is_some_and(|ext| {
+                let ext = ext.to_string_lossy().to_lowercase();
+                ext == "md" || ext == "markdown"
+            });
+            if !is_markdown {
+                continue;
+            }
+
+            let filename = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let matches = filename.to_lowercase().contains(&needle)
+                || matches!(read_text_file_guarded(&path), Ok(Some(text)) if text.to_lowercase().contains(&needle));
+            if matches {
+                results.push(FileSearchResult {
+                    path: path.to_string_lossy().to_string(),
+                    filename,
+                });
+                if results.len() >= limit {
+                    return results;
+                }
+            }
+        }
+    }
+
+    results
+}
+
+/// Searches markdown files on disk: Spotlight where it exists, otherwise a
+/// walk of the workspace (or the documents / home folder).
+#[tauri::command]
+pub fn search_files_on_disk(
+    state: tauri::State<'_, DbPool>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<FileSearchResult>, String> {
+    let limit = limit.unwrap_or(20);
+
+    if query.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    if let Some(results) = spotlight_search(&query, limit) {
+        return Ok(results);
+    }
+
+    let workspace = {
+        let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+        workspace_dir(&conn)?
+    };
+    let root = workspace
+        .map(PathBuf::from)
+        .or_else(dirs::document_dir)
+        .or_else(dirs::home_dir)
+        .ok_or_else(|| "Could not determine a folder to search".to_string())?;
+    Ok(walk_search(&root, &query, limit))
 }
 
 fn ensure_fts_table(conn: &Connection) -> Result<(), String> {
-    conn.execute_batch(
-        "CREATE VIRTUAL TABLE IF NOT EXISTS documents_fts USING fts5(
-            title, content, document_id UNINDEXED,
-            prefix='2,3',
-            tokenize='unicode61 remove_diacritics 2'
-        );",
-    )
-    .map_err(|e| format!("Failed to create FTS table: {e}"))
+    conn.execute_batch(CREATE_DOCUMENTS_FTS)
+        .map_err(|e| format!("Failed to create FTS table: {e}"))
 }
 
 /// Sanitize a user query for FTS5: strip operators, escape quotes, append * for prefix matching.
@@ -145,10 +319,117 @@ fn truncate_to_char_boundary(s: &str, max: usize) -> &str {
     &s[..boundary]
 }
 
-fn index_document_inner(conn: &Connection, document_id: &str, title: &str, content: &str) -> Result<(), String> {
+/// Keeps only the characters a bare FTS5 term may contain.
+fn clean_fts_term(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+        .collect()
+}
+
+/// Builds an FTS5 query from advanced syntax: `"exact phrase"`, `AND` / `OR`
+/// between operands, and `-term` / `-"phrase"` exclusions. Bare terms keep
+/// prefix matching. Returns `None` for anything malformed (unbalanced quotes,
+/// dangling operators, only exclusions) so the caller can fall back to
+/// `sanitize_fts_query` instead of letting FTS5 throw.
+fn advanced_fts_query(query: &str) -> Option<String> {
+    let mut positive: Vec<String> = Vec::new();
+    let mut excluded: Vec<String> = Vec::new();
+    let mut expect_operand = true;
+    let mut chars = query.trim().chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let Some(&first) = chars.peek() else { break };
+        let negate = first == '-';
+        if negate {
+            chars.next();
+        }
+
+        let operand = if chars.next_if_eq(&'"').is_some() {
+            let mut phrase = String::new();
+            let mut closed = false;
+            for c in chars.by_ref() {
+                if c == '"' {
+                    closed = true;
+                    break;
+                }
+                phrase.push(c);
+            }
+            let words: Vec<String> = phrase.split_whitespace().map(clean_fts_term).filter(|w| !w.is_empty()).collect();
+            if !closed || words.is_empty() {
+                return None;
+            }
+            format!("\"{}\"", words.join(" "))
+        } else {
+            let mut word = String::new();
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != '"') {
+                word.push(c);
+            }
+            match word.as_str() {
+                "AND" | "OR" if !negate => {
+                    if expect_operand {
+                        return None;
+                    }
+                    positive.push(word);
+                    expect_operand = true;
+                    continue;
+                }
+                "NOT" | "NEAR" => return None,
+                _ => {}
+            }
+            let cleaned = clean_fts_term(&word);
+            if cleaned.is_empty() {
+                return None;
+            }
+            format!("\"{cleaned}\"*")
+        };
+
+        if negate {
+            // `a OR -b` leaves the OR without a right-hand side
+            if expect_operand && !positive.is_empty() {
+                return None;
+            }
+            excluded.push(operand);
+        } else {
+            positive.push(operand);
+            expect_operand = false;
+        }
+    }
+
+    if positive.is_empty() || expect_operand {
+        return None;
+    }
+    let expr = positive.join(" ");
+    if excluded.is_empty() {
+        Some(expr)
+    } else {
+        Some(format!("({expr}) NOT {}", excluded.join(" NOT ")))
+    }
+}
+
+/// The FTS5 query for user input: advanced syntax when asked for and valid,
+/// otherwise the sanitized form. Whole-word mode drops the prefix stars, so
+/// "pro" no longer matches "programming".
+fn fts_query_for(query: &str, options: &SearchOptions) -> String {
+    let fts_query = options
+        .advanced
+        .then(|| advanced_fts_query(query))
+        .flatten()
+        .unwrap_or_else(|| sanitize_fts_query(query));
+    if options.whole_word {
+        // Terms are cleaned to alphanumerics, so `"*` only ever marks a prefix term
+        fts_query.replace("\"*", "\"")
+    } else {
+        fts_query
+    }
+}
+
+pub(crate) fn index_document_inner(conn: &Connection, document_id: &str, title: &str, content: &str) -> Result<(), String> {
     ensure_fts_table(conn)?;
 
-    let content = truncate_to_char_boundary(content, MAX_INDEX_CHARS);
+    // Index the prose, not the YAML metadata above it
+    let (_, body) = parse_front_matter(content);
+    let content = truncate_to_char_boundary(body, MAX_INDEX_CHARS);
 
     conn.execute(
         "DELETE FROM documents_fts WHERE document_id = ?1",
@@ -165,41 +446,277 @@ fn index_document_inner(conn: &Connection, document_id: &str, title: &str, conte
     Ok(())
 }
 
-fn search_documents_inner(conn: &Connection, query: &str, limit: i32) -> Result<Vec<SearchResult>, String> {
-    ensure_fts_table(conn)?;
 
+/// Searches margin note content (`notes_fts`, maintained by triggers).
+fn search_notes_inner(conn: &Connection, query: &str, limit: i32) -> Result<Vec<NoteSearchResult>, String> {
     let fts_query = sanitize_fts_query(query);
     if fts_query.is_empty() {
         return Ok(Vec::new());
     }
 
-    // Join with documents table for frecency blending.
-    // BM25 returns negative scores (more negative = better match).
-    // Frecency boost: access_count / (1 + days_old * 0.1) — decays over time.
-    // We subtract the frecency boost to make good matches rank even lower (better).
     let mut stmt = conn
         .prepare(
-            "SELECT f.document_id, f.title,
-                    snippet(documents_fts, 1, '<mark>', '</mark>', '\u{2026}', 32) as snippet,
-                    bm25(documents_fts, 10.0, 1.0) as bm25_rank
-             FROM documents_fts f
-             LEFT JOIN documents d ON d.id = f.document_id
-             WHERE documents_fts MATCH ?1
-             ORDER BY bm25(documents_fts, 10.0, 1.0)
-                      - (COALESCE(d.access_count, 0) * 1.0 /
-                         (1.0 + MAX(0, julianday('now') - julianday(datetime(COALESCE(d.last_opened_at, 0) / 1000, 'unixepoch'))) * 0.1))
-                      * 0.3
+            "SELECT mn.id, snippet(notes_fts, 0, '<mark>', '</mark>', '\u{2026}', 32),
+                    h.id, h.text_content, d.id, d.title
+             FROM notes_fts n
+             JOIN margin_notes mn ON mn.id = n.note_id
+             JOIN highlights h ON h.id = mn.highlight_id
+             JOIN documents d ON d.id = h.document_id
+             WHERE notes_fts MATCH ?1 AND h.deleted_at IS NULL
+             ORDER BY bm25(notes_fts), mn.id
              LIMIT ?2",
         )
-        .map_err(|e| format!("Failed to prepare search query: {e}"))?;
+        .map_err(|e| format!("Failed to prepare note search: {e}"))?;
 
     let results = stmt
         .query_map(rusqlite::params![fts_query, limit], |row| {
+            Ok(NoteSearchResult {
+                note_id: row.get(0)?,
+                snippet: row.get(1)?,
+                highlight_id: row.get(2)?,
+                highlight_text: row.get(3)?,
+                document_id: row.get(4)?,
+                document_title: row.get(5)?,
+            })
+        })
+        .map_err(|e| format!("Note search failed: {e}"))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to collect note results: {e}"));
+    results
+}
+
+/// Characters of context kept on each side of a hit in `search_in_document`.
+const MATCH_CONTEXT_CHARS: usize = 60;
+
+/// Markers `highlight()` wraps around hits. Control characters never show up
+/// in indexed prose, so they can be split out again unambiguously.
+const HIT_OPEN: char = '\u{1}';
+const HIT_CLOSE: char = '\u{2}';
+
+/// The FTS5 query for `search_in_document`: every word becomes a quoted prefix
+/// term, ANDed as in library search. Quoting already keeps "and", "or", "not"
+/// and "near" from acting as operators, so unlike `sanitize_fts_query` they
+/// stay searchable.
+fn document_fts_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(clean_fts_term)
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("\"{word}\"*"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A document's indexed content and the byte ranges of the hits in it.
+type IndexedHits = (String, Vec<Range<usize>>);
+
+/// Runs `fts_query` against one document's indexed content and returns that
+/// content with the byte ranges the tokenizer matched. `None` when the
+/// document isn't indexed or doesn't match.
+fn indexed_hits(conn: &Connection, document_id: &str, fts_query: &str) -> Result<Option<IndexedHits>, String> {
+    let highlighted: String = match conn.query_row(
+        "SELECT highlight(documents_fts, 1, ?3, ?4) FROM documents_fts
+         WHERE documents_fts MATCH ?1 AND document_id = ?2",
+        rusqlite::params![fts_query, document_id, HIT_OPEN.to_string(), HIT_CLOSE.to_string()],
+        |row| row.get(0),
+    ) {
+        Ok(highlighted) => highlighted,
+        Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+        Err(e) => return Err(format!("Search query failed: {e}")),
+    };
+
+    let mut content = String::with_capacity(highlighted.len());
+    let mut hits = Vec::new();
+    let mut start = 0;
+    for c in highlighted.chars() {
+        match c {
+            HIT_OPEN => start = content.len(),
+            HIT_CLOSE => hits.push(start..content.len()),
+            _ => content.push(c),
+        }
+    }
+    Ok(Some((content, hits)))
+}
+
+/// A hit at bytes `range` of `text`, with offsets converted to UTF-16 units as
+/// the editor counts them.
+fn document_match(text: &str, range: Range<usize>) -> DocumentMatch {
+    let before = &text[..range.start];
+    let from = before
+        .char_indices()
+        .rev()
+        .nth(MATCH_CONTEXT_CHARS - 1)
+        .map_or(0, |(i, _)| i);
+    let after = &text[range.end..];
+    let to = range.end + after.char_indices().nth(MATCH_CONTEXT_CHARS).map_or(after.len(), |(i, _)| i);
+
+    let mut snippet = String::new();
+    if from > 0 {
+        snippet.push('\u{2026}');
+    }
+    snippet.push_str(&text[from..range.start]);
+    snippet.push_str("<mark>");
+    snippet.push_str(&text[range.clone()]);
+    snippet.push_str("</mark>");
+    snippet.push_str(&text[range.end..to]);
+    if to < text.len() {
+        snippet.push('\u{2026}');
+    }
+    DocumentMatch {
+        offset: before.encode_utf16().count(),
+        length: text[range].encode_utf16().count(),
+        snippet,
+    }
+}
+
+/// Where a document's original text lives: its file, or for keep-local items
+/// the content cache.
+struct TextSource {
+    file_path: Option<String>,
+    keep_local_id: Option<String>,
+}
+
+/// `None` when there is no document row.
+fn document_text_source(conn: &Connection, document_id: &str) -> Result<Option<TextSource>, String> {
+    match conn.query_row(
+        "SELECT file_path, keep_local_id FROM documents WHERE id = ?1",
+        [document_id],
+        |row| {
+            Ok(TextSource {
+                file_path: row.get(0)?,
+                keep_local_id: row.get(1)?,
+            })
+        },
+    ) {
+        Ok(source) => Ok(Some(source)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(format!("Failed to look up document: {e}")),
+    }
+}
+
+/// File I/O only, so callers can run it off the DB lock.
+fn read_original_content(source: &TextSource) -> Option<String> {
+    match (&source.file_path, &source.keep_local_id) {
+        (Some(path), _) => read_text_file_guarded(Path::new(path)).ok().flatten(),
+        (None, Some(item_id)) => cached_content(item_id),
+        (None, None) => None,
+    }
+}
+
+/// All FTS hits for `query` inside one document, in reading order. No
+/// frecency: there is only one document.
+///
+/// With the document's `original` text, an index entry that no longer matches
+/// it is refreshed first, and offsets point into `original` (front matter
+/// included). Without it they point into the indexed text. Either way only
+/// the indexed part (up to `MAX_INDEX_CHARS` of the body) is searched.
+fn search_in_document_inner(
+    conn: &Connection,
+    document_id: &str,
+    query: &str,
+    original: Option<&str>,
+) -> Result<Vec<DocumentMatch>, String> {
+    ensure_fts_table(conn)?;
+
+    let fts_query = document_fts_query(query);
+    if fts_query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Byte offset of the indexed text within `original`
+    let mut body_start = 0;
+    if let Some(original) = original {
+        let (_, body) = parse_front_matter(original);
+        let indexed = truncate_to_char_boundary(body, MAX_INDEX_CHARS);
+        if fetch_indexed_content(conn, document_id)?.as_deref() != Some(indexed) {
+            let title: Option<String> = conn
+                .query_row("SELECT title FROM documents WHERE id = ?1", [document_id], |row| row.get(0))
+                .map_err(|e| format!("Failed to look up document: {e}"))?;
+            index_document_inner(conn, document_id, title.as_deref().unwrap_or("Untitled"), original)?;
+            conn.execute(
+                "UPDATE documents SET indexed_at = ?1 WHERE id = ?2",
+                rusqlite::params![now_millis(), document_id],
+            )
+            .map_err(|e| format!("Failed to update indexed_at: {e}"))?;
+        }
+        body_start = original.len() - body.len();
+    }
+
+    let Some((indexed, hits)) = indexed_hits(conn, document_id, &fts_query)? else {
+        return Ok(Vec::new());
+    };
+    let text = original.unwrap_or(&indexed);
+    Ok(hits
+        .into_iter()
+        .map(|hit| document_match(text, body_start + hit.start..body_start + hit.end))
+        .collect())
+}
+
+fn search_documents_inner(conn: &Connection, query: &str, limit: i32, options: &SearchOptions) -> Result<Vec<SearchResult>, String> {
+    ensure_fts_table(conn)?;
+
+    let fts_query = fts_query_for(query, options);
+    if fts_query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // BM25 returns negative scores (more negative = better match). Title is
+    // weighted 10x in both modes.
+    // Frecency boost: access_count / (1 + days_old * 0.1) — decays over time.
+    // We subtract the frecency boost to make good matches rank even lower (better).
+    let order_by = if options.frecency {
+        "bm25(documents_fts, 10.0, 1.0)
+                  - (COALESCE(d.access_count, 0) * 1.0 /
+                     (1.0 + MAX(0, julianday('now') - julianday(datetime(COALESCE(d.last_opened_at, 0) / 1000, 'unixepoch'))) * 0.1))
+                  * 0.3"
+    } else {
+        "bm25_rank, f.document_id"
+    };
+    let sql = format!(
+        "SELECT f.document_id, f.title,
+                snippet(documents_fts, 1, ?3, ?4, '\u{2026}', ?5) as snippet,
+                bm25(documents_fts, 10.0, 1.0) as bm25_rank,
+                d.file_path, d.source
+         FROM documents_fts f
+         LEFT JOIN documents d ON d.id = f.document_id
+         WHERE documents_fts MATCH ?1
+         ORDER BY {order_by}
+         LIMIT ?2"
+    );
+    query_search_results(conn, &sql, &fts_query, limit, &options.snippet)
+}
+
+/// Pure BM25 ranking (title weighted as in `search_documents_inner`) with
+/// frecency turned off, so results don't shift as documents are opened.
+/// Ties break on document id to keep the order stable.
+fn search_documents_raw_inner(conn: &Connection, query: &str, limit: i32) -> Result<Vec<SearchResult>, String> {
+    search_documents_inner(conn, query, limit, &SearchOptions { frecency: false, ..Default::default() })
+}
+
+/// Runs a search statement whose parameters are `?1` query, `?2` limit, and
+/// `?3`..`?5` the snippet's open tag, close tag, and token count. Columns are
+/// id, title, snippet, rank, file_path, source.
+fn query_search_results(
+    conn: &Connection,
+    sql: &str,
+    fts_query: &str,
+    limit: i32,
+    snippet: &SnippetOptions,
+) -> Result<Vec<SearchResult>, String> {
+    let mut stmt = conn
+        .prepare(sql)
+        .map_err(|e| format!("Failed to prepare search query: {e}"))?;
+
+    let params = rusqlite::params![fts_query, limit, snippet.open_tag, snippet.close_tag, snippet.tokens];
+    let results = stmt
+        .query_map(params, |row| {
             Ok(SearchResult {
                 document_id: row.get(0)?,
                 title: row.get(1)?,
                 snippet: row.get(2)?,
                 rank: row.get::<_, f64>(3)?,
+                file_path: row.get(4)?,
+                source: row.get(5)?,
             })
         })
         .map_err(|e| format!("Search query failed: {e}"))?
@@ -208,7 +725,36 @@ fn search_documents_inner(conn: &Connection, query: &str, limit: i32) -> Result<
     results
 }
 
-fn remove_document_index_inner(conn: &Connection, document_id: &str) -> Result<(), String> {
+/// Retitles an indexed document in place; a no-op when it isn't indexed.
+pub(crate) fn set_indexed_title(conn: &Connection, document_id: &str, title: &str) -> Result<(), String> {
+    ensure_fts_table(conn)?;
+
+    conn.execute(
+        "UPDATE documents_fts SET title = ?1 WHERE document_id = ?2",
+        rusqlite::params![title, document_id],
+    )
+    .map_err(|e| format!("Failed to update indexed title: {e}"))?;
+
+    Ok(())
+}
+
+/// Exactly what `index_document_inner` stored (after truncation), or `None`
+/// when the document isn't indexed.
+fn fetch_indexed_content(conn: &Connection, document_id: &str) -> Result<Option<String>, String> {
+    ensure_fts_table(conn)?;
+
+    match conn.query_row(
+        "SELECT content FROM documents_fts WHERE document_id = ?1",
+        rusqlite::params![document_id],
+        |row| row.get(0),
+    ) {
+        Ok(content) => Ok(Some(content)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(format!("Failed to read indexed content: {e}")),
+    }
+}
+
+pub(crate) fn remove_document_index_inner(conn: &Connection, document_id: &str) -> Result<(), String> {
     ensure_fts_table(conn)?;
 
     conn.execute(
@@ -220,6 +766,91 @@ fn remove_document_index_inner(conn: &Connection, document_id: &str) -> Result<(
     Ok(())
 }
 
+/// Refreshes the index entry of the document tracked at `file_path` from its
+/// `content`, read beforehand off the DB lock (see `read_file_for_index`).
+/// Returns `false` when no document row points at `file_path`.
+pub(crate) fn index_file_inner(conn: &Connection, file_path: &str, content: &str) -> Result<bool, String> {
+    let doc: Option<(String, Option<String>)> = match conn.query_row(
+        "SELECT id, title FROM documents WHERE file_path = ?1",
+        rusqlite::params![file_path],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    ) {
+        Ok(doc) => Some(doc),
+        Err(rusqlite::Error::QueryReturnedNoRows) => None,
+        Err(e) => return Err(format!("Failed to look up document: {e}")),
+    };
+    let Some((doc_id, title)) = doc else {
+        return Ok(false);
+    };
+
+    index_document_inner(conn, &doc_id, title.as_deref().unwrap_or("Untitled"), content)?;
+    conn.execute(
+        "UPDATE documents SET indexed_at = ?1 WHERE id = ?2",
+        rusqlite::params![now_millis(), doc_id],
+    )
+    .map_err(|e| format!("Failed to update indexed_at: {e}"))?;
+    Ok(true)
+}
+
+/// Reads a file for indexing with `read_text_file_guarded` on a worker
+/// thread, giving up after `timeout` so a stalled mount can't hold up the
+/// caller. `Ok(None)` for files too large or binary to index.
+pub(crate) fn read_file_for_index(file_path: &str, timeout: Duration) -> Result<Option<String>, String> {
+    let mut read = None;
+    run_with_deadlines(
+        vec![file_path.to_string()],
+        1,
+        timeout,
+        |path| read_text_file_guarded(Path::new(&path)),
+        |_, result| read = result,
+    );
+    read.unwrap_or_else(|| Err(format!("Timed out reading {file_path}")))
+}
+
+/// `index_file_inner` for a file that still has to be read: the read happens
+/// off the DB lock, which is only taken for the short index write. Returns
+/// `false` for untracked paths and for files too large or binary to index.
+pub(crate) fn index_file_from_disk(pool: &DbPool, file_path: &str) -> Result<bool, String> {
+    let timeout = {
+        let conn = pool.0.lock().unwrap_or_else(|e| e.into_inner());
+        index_read_timeout(&conn)
+    };
+    let Some(content) = read_file_for_index(file_path, timeout)? else {
+        return Ok(false);
+    };
+    let conn = pool.0.lock().unwrap_or_else(|e| e.into_inner());
+    index_file_inner(&conn, file_path, &content)
+}
+
+/// Re-reads one document's file and refreshes its index entry, without
+/// touching the rest of the library. The lock is held only for the lookup
+/// and the index write, not the read. A missing file leaves the index as is.
+fn reindex_document_inner(pool: &DbPool, document_id: &str) -> Result<(), String> {
+    let (file_path, timeout) = {
+        let conn = pool.0.lock().unwrap_or_else(|e| e.into_inner());
+        let file_path: Option<String> = conn
+            .query_row(
+                "SELECT file_path FROM documents WHERE id = ?1",
+                rusqlite::params![document_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => "Document not found".to_string(),
+                other => format!("Failed to look up document: {other}"),
+            })?;
+        (file_path.ok_or_else(|| "Document has no file on disk".to_string())?, index_read_timeout(&conn))
+    };
+    if !Path::new(&file_path).is_file() {
+        return Err(format!("File not found: {file_path}"));
+    }
+    let content = read_file_for_index(&file_path, timeout)?
+        .ok_or_else(|| format!("File is too large or not text: {file_path}"))?;
+
+    let conn = pool.0.lock().unwrap_or_else(|e| e.into_inner());
+    index_file_inner(&conn, &file_path, &content)?;
+    Ok(())
+}
+
 fn increment_access_count(conn: &Connection, document_id: &str) -> Result<(), String> {
     conn.execute(
         "UPDATE documents SET access_count = COALESCE(access_count, 0) + 1 WHERE id = ?1",
@@ -229,17 +860,154 @@ fn increment_access_count(conn: &Connection, document_id: &str) -> Result<(), St
     Ok(())
 }
 
-#[cfg(test)]
-fn index_all_documents_inner(conn: &Connection) -> Result<IndexAllResult, String> {
-    ensure_fts_table(conn)?;
+fn record_index_summary(conn: &Connection, result: &IndexAllResult, finished_at: i64) -> Result<(), String> {
+    let summary = IndexSummary {
+        indexed: result.indexed,
+        skipped: result.skipped,
+        errors: result.errors,
+        finished_at,
+    };
+    let json = serde_json::to_string(&summary).map_err(|e| e.to_string())?;
+    set_setting(conn, LAST_INDEX_SUMMARY_KEY, &json)
+}
+
+fn fetch_last_index_summary(conn: &Connection) -> Result<Option<IndexSummary>, String> {
+    match get_setting(conn, LAST_INDEX_SUMMARY_KEY)? {
+        // A summary written by an older build that no longer parses is treated as absent
+        Some(json) => Ok(serde_json::from_str(&json).ok()),
+        None => Ok(None),
+    }
+}
 
+/// Runs `work` over `items` on a pool of at most `threads` worker threads and
+/// hands each result to `on_result` on the caller's thread, in completion
+/// order. An item still running `timeout` after a worker picked it up is
+/// reported as `None` and its worker abandoned, left to finish (or hang) on
+/// its own. The pool is never replenished, so a stalled mount can't pile up
+/// threads; once every worker is stuck, the items not yet started are
+/// reported as `None` too.
+fn run_with_deadlines<T, R, F>(
+    items: Vec<T>,
+    threads: usize,
+    timeout: Duration,
+    work: F,
+    mut on_result: impl FnMut(usize, Option<R>),
+) where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> R + Send + Sync + 'static,
+{
+    enum Event<R> {
+        Started(usize),
+        Finished(usize, R),
+    }
+
+    let total = items.len();
+    let threads = threads.clamp(1, total.max(1));
+    let queue = Arc::new(Mutex::new(items.into_iter().enumerate()));
+    let work = Arc::new(work);
+    let (tx, rx) = mpsc::channel();
+    for _ in 0..threads {
+        let (queue, work, tx) = (Arc::clone(&queue), Arc::clone(&work), tx.clone());
+        std::thread::spawn(move || loop {
+            let next = queue.lock().unwrap_or_else(|e| e.into_inner()).next();
+            let Some((idx, item)) = next else { break };
+            // A closed channel means the caller has given up on the pool
+            if tx.send(Event::Started(idx)).is_err() || tx.send(Event::Finished(idx, work(item))).is_err() {
+                break;
+            }
+        });
+    }
+    drop(tx);
+
+    let mut deadlines: HashMap<usize, Instant> = HashMap::new();
+    let mut stuck = 0;
+    let mut reported = 0;
+    while reported < total && stuck < threads {
+        let event = match deadlines.values().min() {
+            Some(deadline) => rx.recv_timeout(deadline.saturating_duration_since(Instant::now())),
+            None => rx.recv().map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+        };
+        match event {
+            Ok(Event::Started(idx)) => {
+                deadlines.insert(idx, Instant::now() + timeout);
+            }
+            Ok(Event::Finished(idx, out)) => {
+                if deadlines.remove(&idx).is_some() {
+                    reported += 1;
+                    on_result(idx, Some(out));
+                } else {
+                    // Already reported as timed out; its worker is back in the pool
+                    stuck -= 1;
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                let now = Instant::now();
+                let expired: Vec<usize> = deadlines.iter().filter(|(_, d)| **d <= now).map(|(idx, _)| *idx).collect();
+                for idx in expired {
+                    deadlines.remove(&idx);
+                    stuck += 1;
+                    reported += 1;
+                    on_result(idx, None);
+                }
+            }
+            // Only when every worker has exited, e.g. after panicking
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    let unfinished: Vec<usize> = queue
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .by_ref()
+        .map(|(idx, _)| idx)
+        .chain(deadlines.into_keys())
+        .collect();
+    for idx in unfinished {
+        on_result(idx, None);
+    }
+}
+
+/// What an indexing worker found on disk for one document.
+enum FileRead {
+    /// Not modified since `indexed_at`
+    Unchanged,
+    Missing,
+    Read(String),
+    Failed(String),
+}
+
+fn read_if_changed(file_path: &str, indexed_at: Option<i64>) -> FileRead {
+    let Ok(meta) = std::fs::metadata(file_path) else {
+        return FileRead::Missing;
+    };
+    if indexed_at.is_some_and(|ia| file_mtime_ms(&meta) <= ia) {
+        return FileRead::Unchanged;
+    }
+    match std::fs::read_to_string(file_path) {
+        Ok(content) => FileRead::Read(content),
+        Err(e) => FileRead::Failed(e.to_string()),
+    }
+}
+
+/// A file-backed document whose file changed after it was last indexed.
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StaleDocument {
+    pub id: String,
+    pub file_path: String,
+    pub title: Option<String>,
+}
+
+/// `(id, file_path, title, indexed_at)`
+type IndexableDocument = (String, String, Option<String>, Option<i64>);
+
+/// Every document with a file on disk.
+fn fetch_indexable_documents(conn: &Connection) -> Result<Vec<IndexableDocument>, String> {
     let mut stmt = conn
-        .prepare(
-            "SELECT id, file_path, title, indexed_at FROM documents WHERE file_path IS NOT NULL",
-        )
+        .prepare("SELECT id, file_path, title, indexed_at FROM documents WHERE file_path IS NOT NULL")
         .map_err(|e| format!("Failed to query documents: {e}"))?;
-
-    let docs: Vec<(String, String, Option<String>, Option<i64>)> = stmt
+    let rows = stmt
         .query_map([], |row| {
             Ok((
                 row.get::<_, String>(0)?,
@@ -248,9 +1016,42 @@ fn index_all_documents_inner(conn: &Connection) -> Result<IndexAllResult, String
                 row.get::<_, Option<i64>>(3)?,
             ))
         })
-        .map_err(|e| format!("Failed to read documents: {e}"))?
-        .filter_map(|r| r.ok())
-        .collect();
+        .map_err(|e| format!("Failed to read documents: {e}"))?;
+    let result: Vec<_> = rows.filter_map(|r| r.ok()).collect();
+    Ok(result)
+}
+
+fn file_mtime_ms(meta: &std::fs::Metadata) -> i64 {
+    meta.modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// Same staleness rule as `index_all_documents`, without indexing anything.
+/// Missing files aren't stale: the indexer would skip them too.
+fn select_stale_documents(docs: Vec<IndexableDocument>) -> Vec<StaleDocument> {
+    docs.into_iter()
+        .filter(|(_, file_path, _, indexed_at)| match std::fs::metadata(file_path) {
+            Ok(meta) => indexed_at.is_none_or(|ia| file_mtime_ms(&meta) > ia),
+            Err(_) => false,
+        })
+        .map(|(id, file_path, title, _)| StaleDocument { id, file_path, title })
+        .collect()
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum IndexOutcome {
+    Indexed,
+    Skipped,
+    Failed,
+}
+
+#[cfg(test)]
+fn index_all_documents_inner(conn: &Connection) -> Result<IndexAllResult, String> {
+    ensure_fts_table(conn)?;
+    let docs = fetch_indexable_documents(conn)?;
 
     let mut indexed = 0;
     let mut skipped = 0;
@@ -261,12 +1062,7 @@ fn index_all_documents_inner(conn: &Connection) -> Result<IndexAllResult, String
     for (doc_id, file_path, title, indexed_at) in &docs {
         // Check file mtime
         let mtime_ms = match std::fs::metadata(file_path) {
-            Ok(meta) => meta
-                .modified()
-                .ok()
-                .and_then(|t| t.duration_since(std::time::SystemTime::UNIX_EPOCH).ok())
-                .map(|d| d.as_millis() as i64)
-                .unwrap_or(0),
+            Ok(meta) => file_mtime_ms(&meta),
             Err(_) => {
                 // File doesn't exist or permission error — skip
                 skipped += 1;
@@ -305,11 +1101,13 @@ fn index_all_documents_inner(conn: &Connection) -> Result<IndexAllResult, String
         }
     }
 
-    Ok(IndexAllResult {
+    let result = IndexAllResult {
         indexed,
         skipped,
         errors,
-    })
+    };
+    record_index_summary(conn, &result, now_millis())?;
+    Ok(result)
 }
 
 // === Tauri command handlers ===
@@ -324,12 +1122,78 @@ pub fn index_document(state: tauri::State<'_, DbPool>, document_id: String, titl
 }
 
 #[tauri::command]
-pub fn search_documents(state: tauri::State<'_, DbPool>, query: String, limit: Option<i32>) -> Result<Vec<SearchResult>, String> {
+#[allow(clippy::too_many_arguments)]
+pub fn search_documents(
+    state: tauri::State<'_, DbPool>,
+    query: String,
+    limit: Option<i32>,
+    advanced: Option<bool>,
+    frecency: Option<bool>,
+    whole_word: Option<bool>,
+    snippet_tokens: Option<i32>,
+    open_tag: Option<String>,
+    close_tag: Option<String>,
+) -> Result<Vec<SearchResult>, String> {
+    let options = SearchOptions {
+        advanced: advanced.unwrap_or(false),
+        frecency: frecency.unwrap_or(true),
+        whole_word: whole_word.unwrap_or(false),
+        snippet: SnippetOptions::new(snippet_tokens, open_tag, close_tag)?,
+    };
     if query.trim().is_empty() {
         return Ok(Vec::new());
     }
     let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
-    search_documents_inner(&conn, &query, limit.unwrap_or(20))
+    search_documents_inner(&conn, &query, limit.unwrap_or(20), &options)
+}
+
+/// Relevance-only search: same sanitization and snippets as `search_documents`,
+/// ranked purely by BM25.
+#[tauri::command]
+pub fn search_documents_raw(state: tauri::State<'_, DbPool>, query: String, limit: Option<i32>) -> Result<Vec<SearchResult>, String> {
+    if query.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    search_documents_raw_inner(&conn, &query, limit.unwrap_or(20))
+}
+
+#[tauri::command]
+pub fn search_in_document(
+    state: tauri::State<'_, DbPool>,
+    document_id: String,
+    query: String,
+) -> Result<Vec<DocumentMatch>, String> {
+    // Read the original text off the DB lock
+    let source = {
+        let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+        document_text_source(&conn, &document_id)?
+    };
+    let original = source.and_then(|source| read_original_content(&source));
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    search_in_document_inner(&conn, &document_id, &query, original.as_deref())
+}
+
+#[tauri::command]
+pub fn search_notes(state: tauri::State<'_, DbPool>, query: String, limit: Option<i32>) -> Result<Vec<NoteSearchResult>, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    search_notes_inner(&conn, &query, limit.unwrap_or(20))
+}
+
+#[tauri::command]
+pub fn index_file(state: tauri::State<'_, DbPool>, path: String) -> Result<bool, String> {
+    index_file_from_disk(&state, &path)
+}
+
+#[tauri::command]
+pub fn reindex_document(state: tauri::State<'_, DbPool>, document_id: String) -> Result<(), String> {
+    reindex_document_inner(&state, &document_id)
+}
+
+#[tauri::command]
+pub fn get_indexed_content(state: tauri::State<'_, DbPool>, document_id: String) -> Result<Option<String>, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    fetch_indexed_content(&conn, &document_id)
 }
 
 #[tauri::command]
@@ -341,85 +1205,98 @@ pub fn remove_document_index(state: tauri::State<'_, DbPool>, document_id: Strin
 #[tauri::command]
 pub fn index_all_documents(state: tauri::State<'_, DbPool>) -> Result<IndexAllResult, String> {
     // Collect document list under lock, then drop lock for file I/O
-    let docs: Vec<(String, String, Option<String>, Option<i64>)> = {
+    let docs = {
         let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
         ensure_fts_table(&conn)?;
-
-        let mut stmt = conn
-            .prepare("SELECT id, file_path, title, indexed_at FROM documents WHERE file_path IS NOT NULL")
-            .map_err(|e| format!("Failed to query documents: {e}"))?;
-        let rows = stmt.query_map([], |row| {
-            Ok((
-                row.get::<_, String>(0)?,
-                row.get::<_, String>(1)?,
-                row.get::<_, Option<String>>(2)?,
-                row.get::<_, Option<i64>>(3)?,
-            ))
-        })
-        .map_err(|e| format!("Failed to read documents: {e}"))?;
-        let result: Vec<_> = rows.filter_map(|r| r.ok()).collect();
-        result
+        fetch_indexable_documents(&conn)?
     }; // lock dropped here
 
     let now_ms = now_millis();
+    let (threads, read_timeout) = {
+        let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+        (scan_threads(&conn), index_read_timeout(&conn))
+    };
+    let reads: Vec<(String, Option<i64>)> = docs.iter().map(|(_, path, _, ia)| (path.clone(), *ia)).collect();
+    let mut outcomes = Vec::with_capacity(docs.len());
+
+    // Stat and read on the bounded worker pool so a stalled mount can't hang the
+    // loop; DB writes happen here, briefly taking the lock for each document
+    run_with_deadlines(
+        reads,
+        threads,
+        read_timeout,
+        |(path, indexed_at)| read_if_changed(&path, indexed_at),
+        |idx, read| {
+            let (doc_id, file_path, title, _) = &docs[idx];
+            let outcome = match read {
+                None => {
+                    eprintln!("index_all: timed out reading {file_path}");
+                    IndexOutcome::Failed
+                }
+                Some(FileRead::Unchanged | FileRead::Missing) => IndexOutcome::Skipped,
+                Some(FileRead::Failed(e)) => {
+                    eprintln!("index_all: failed to read {file_path}: {e}");
+                    IndexOutcome::Failed
+                }
+                Some(FileRead::Read(content)) => {
+                    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+                    let doc_title = title.as_deref().unwrap_or("Untitled");
+                    match index_document_inner(&conn, doc_id, doc_title, &content) {
+                        Ok(()) => {
+                            let _ = conn.execute(
+                                "UPDATE documents SET indexed_at = ?1 WHERE id = ?2",
+                                rusqlite::params![now_ms, doc_id],
+                            );
+                            IndexOutcome::Indexed
+                        }
+                        Err(e) => {
+                            eprintln!("index_all: failed to index {file_path}: {e}");
+                            IndexOutcome::Failed
+                        }
+                    }
+                }
+            };
+            outcomes.push(outcome);
+        },
+    );
 
-    let mut indexed = 0usize;
-    let mut skipped = 0usize;
-    let mut errors = 0usize;
-
-    for (doc_id, file_path, title, indexed_at) in &docs {
-        // Check file mtime — no lock needed
-        let mtime_ms = match std::fs::metadata(file_path) {
-            Ok(meta) => meta
-                .modified()
-                .ok()
-                .and_then(|t| t.duration_since(std::time::SystemTime::UNIX_EPOCH).ok())
-                .map(|d| d.as_millis() as i64)
-                .unwrap_or(0),
-            Err(_) => {
-                skipped += 1;
-                continue;
-            }
-        };
-
-        if let Some(ia) = indexed_at {
-            if mtime_ms <= *ia {
-                skipped += 1;
-                continue;
-            }
-        }
-
-        // Read file — no lock needed
-        let content = match std::fs::read_to_string(file_path) {
-            Ok(c) => c,
-            Err(e) => {
-                eprintln!("index_all: failed to read {file_path}: {e}");
-                errors += 1;
-                continue;
-            }
-        };
+    let count = |o: IndexOutcome| outcomes.iter().filter(|x| **x == o).count();
+    let (indexed, skipped, errors) = (
+        count(IndexOutcome::Indexed),
+        count(IndexOutcome::Skipped),
+        count(IndexOutcome::Failed),
+    );
 
-        // Briefly reacquire lock for DB writes
+    let result = IndexAllResult { indexed, skipped, errors };
+    {
         let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
-        let doc_title = title.as_deref().unwrap_or("Untitled");
-        if let Err(e) = index_document_inner(&conn, doc_id, doc_title, &content) {
-            eprintln!("index_all: failed to index {file_path}: {e}");
-            errors += 1;
-            continue;
+        if let Err(e) = record_index_summary(&conn, &result, now_millis()) {
+            eprintln!("index_all: failed to record summary: {e}");
         }
-        let _ = conn.execute(
-            "UPDATE documents SET indexed_at = ?1 WHERE id = ?2",
-            rusqlite::params![now_ms, doc_id],
-        );
-        indexed += 1;
     }
+    Ok(result)
+}
 
-    Ok(IndexAllResult { indexed, skipped, errors })
+/// Read-only preflight for `index_all_documents`: which documents it would reindex.
+#[tauri::command]
+pub fn get_stale_documents(state: tauri::State<'_, DbPool>) -> Result<Vec<StaleDocument>, String> {
+    let docs = {
+        let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+        fetch_indexable_documents(&conn)?
+    };
+    Ok(select_stale_documents(docs))
+}
+
+#[tauri::command]
+pub fn get_last_index_summary(state: tauri::State<'_, DbPool>) -> Result<Option<IndexSummary>, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    fetch_last_index_summary(&conn)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::db::migrations::migrate_add_settings_table;
 
     fn setup_db() -> Connection {
         let conn = Connection::open_in_memory().unwrap();
@@ -442,6 +1319,7 @@ mod tests {
             );",
         ).unwrap();
         ensure_fts_table(&conn).unwrap();
+        migrate_add_settings_table(&conn).unwrap();
         conn
     }
 
@@ -457,7 +1335,7 @@ mod tests {
         let conn = setup_db();
         index_document_inner(&conn, "d1", "Rust Programming", "Learn systems programming with Rust").unwrap();
 
-        let results = search_documents_inner(&conn, "Rust", 10).unwrap();
+        let results = search_documents_inner(&conn, "Rust", 10, &SearchOptions::default()).unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].document_id, "d1");
         assert_eq!(results[0].title, "Rust Programming");
@@ -468,17 +1346,26 @@ mod tests {
         let conn = setup_db();
         index_document_inner(&conn, "d1", "Title", "The quick brown fox jumps over the lazy dog").unwrap();
 
-        let results = search_documents_inner(&conn, "fox", 10).unwrap();
+        let results = search_documents_inner(&conn, "fox", 10, &SearchOptions::default()).unwrap();
         assert_eq!(results.len(), 1);
         assert!(results[0].snippet.contains("fox"));
     }
 
+    #[test]
+    fn front_matter_is_not_indexed() {
+        let conn = setup_db();
+        index_document_inner(&conn, "d1", "Title", "---\nauthor: Quixote\n---\nwindmills at dawn").unwrap();
+
+        assert!(search_documents_inner(&conn, "Quixote", 10, &SearchOptions::default()).unwrap().is_empty());
+        assert_eq!(search_documents_inner(&conn, "windmills", 10, &SearchOptions::default()).unwrap().len(), 1);
+    }
+
     #[test]
     fn search_no_results_for_missing_term() {
         let conn = setup_db();
         index_document_inner(&conn, "d1", "Title", "Some content here").unwrap();
 
-        let results = search_documents_inner(&conn, "nonexistent", 10).unwrap();
+        let results = search_documents_inner(&conn, "nonexistent", 10, &SearchOptions::default()).unwrap();
         assert!(results.is_empty());
     }
 
@@ -489,7 +1376,7 @@ mod tests {
             index_document_inner(&conn, &format!("d{i}"), &format!("Rust Doc {i}"), "Rust content").unwrap();
         }
 
-        let results = search_documents_inner(&conn, "Rust", 2).unwrap();
+        let results = search_documents_inner(&conn, "Rust", 2, &SearchOptions::default()).unwrap();
         assert_eq!(results.len(), 2);
     }
 
@@ -499,14 +1386,27 @@ mod tests {
         index_document_inner(&conn, "d1", "Old Title", "old content about cats").unwrap();
         index_document_inner(&conn, "d1", "New Title", "new content about dogs").unwrap();
 
-        let results = search_documents_inner(&conn, "cats", 10).unwrap();
+        let results = search_documents_inner(&conn, "cats", 10, &SearchOptions::default()).unwrap();
         assert!(results.is_empty());
 
-        let results = search_documents_inner(&conn, "dogs", 10).unwrap();
+        let results = search_documents_inner(&conn, "dogs", 10, &SearchOptions::default()).unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].title, "New Title");
     }
 
+    #[test]
+    fn indexed_content_matches_what_was_indexed() {
+        let conn = setup_db();
+        assert!(fetch_indexed_content(&conn, "d1").unwrap().is_none());
+
+        index_document_inner(&conn, "d1", "Title", "first version").unwrap();
+        index_document_inner(&conn, "d1", "Title", "second version").unwrap();
+        assert_eq!(fetch_indexed_content(&conn, "d1").unwrap().as_deref(), Some("second version"));
+
+        remove_document_index_inner(&conn, "d1").unwrap();
+        assert!(fetch_indexed_content(&conn, "d1").unwrap().is_none());
+    }
+
     #[test]
     fn remove_document_index_removes_from_search() {
         let conn = setup_db();
@@ -514,7 +1414,7 @@ mod tests {
 
         remove_document_index_inner(&conn, "d1").unwrap();
 
-        let results = search_documents_inner(&conn, "searchable", 10).unwrap();
+        let results = search_documents_inner(&conn, "searchable", 10, &SearchOptions::default()).unwrap();
         assert!(results.is_empty());
     }
 
@@ -531,10 +1431,10 @@ mod tests {
         index_document_inner(&conn, "d2", "Python Guide", "Learn Python programming").unwrap();
         index_document_inner(&conn, "d3", "Cooking", "How to make pasta").unwrap();
 
-        let results = search_documents_inner(&conn, "programming", 10).unwrap();
+        let results = search_documents_inner(&conn, "programming", 10, &SearchOptions::default()).unwrap();
         assert_eq!(results.len(), 2);
 
-        let results = search_documents_inner(&conn, "pasta", 10).unwrap();
+        let results = search_documents_inner(&conn, "pasta", 10, &SearchOptions::default()).unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].document_id, "d3");
     }
@@ -546,7 +1446,7 @@ mod tests {
         let conn = setup_db();
         index_document_inner(&conn, "d1", "Programming Guide", "Learn programming with Rust").unwrap();
 
-        let results = search_documents_inner(&conn, "pro", 10).unwrap();
+        let results = search_documents_inner(&conn, "pro", 10, &SearchOptions::default()).unwrap();
         assert_eq!(results.len(), 1, "prefix 'pro' should match 'programming'");
     }
 
@@ -558,7 +1458,7 @@ mod tests {
         // d2: "Rust" in body only
         index_document_inner(&conn, "d2", "Language Guide", "Learn Rust and be happy").unwrap();
 
-        let results = search_documents_inner(&conn, "Rust", 10).unwrap();
+        let results = search_documents_inner(&conn, "Rust", 10, &SearchOptions::default()).unwrap();
         assert_eq!(results.len(), 2);
         // Title match should rank higher (better BM25 with 10x weight)
         assert_eq!(results[0].document_id, "d1", "title match should rank first");
@@ -569,7 +1469,7 @@ mod tests {
         let conn = setup_db();
         index_document_inner(&conn, "d1", "Café Culture", "The best cafés in Paris").unwrap();
 
-        let results = search_documents_inner(&conn, "cafe", 10).unwrap();
+        let results = search_documents_inner(&conn, "cafe", 10, &SearchOptions::default()).unwrap();
         assert!(!results.is_empty(), "'cafe' should match 'café' with diacritics removal");
     }
 
@@ -578,10 +1478,10 @@ mod tests {
         let conn = setup_db();
         index_document_inner(&conn, "d1", "Title", "Content").unwrap();
 
-        let results = search_documents_inner(&conn, "", 10).unwrap();
+        let results = search_documents_inner(&conn, "", 10, &SearchOptions::default()).unwrap();
         assert!(results.is_empty());
 
-        let results = search_documents_inner(&conn, "   ", 10).unwrap();
+        let results = search_documents_inner(&conn, "   ", 10, &SearchOptions::default()).unwrap();
         assert!(results.is_empty());
     }
 
@@ -591,12 +1491,12 @@ mod tests {
         index_document_inner(&conn, "d1", "C++ Guide", "Learn C++ programming").unwrap();
 
         // These should not crash, even if they return no results
-        let _ = search_documents_inner(&conn, "c++", 10);
-        let _ = search_documents_inner(&conn, "hello-world", 10);
-        let _ = search_documents_inner(&conn, "+++", 10);
-        let _ = search_documents_inner(&conn, "---", 10);
-        let _ = search_documents_inner(&conn, "\"quoted\"", 10);
-        let _ = search_documents_inner(&conn, "OR AND NOT", 10);
+        let _ = search_documents_inner(&conn, "c++", 10, &SearchOptions::default());
+        let _ = search_documents_inner(&conn, "hello-world", 10, &SearchOptions::default());
+        let _ = search_documents_inner(&conn, "+++", 10, &SearchOptions::default());
+        let _ = search_documents_inner(&conn, "---", 10, &SearchOptions::default());
+        let _ = search_documents_inner(&conn, "\"quoted\"", 10, &SearchOptions::default());
+        let _ = search_documents_inner(&conn, "OR AND NOT", 10, &SearchOptions::default());
     }
 
     #[test]
@@ -608,7 +1508,7 @@ mod tests {
         let long_content = format!("{} searchtarget extra filler here", filler);
         index_document_inner(&conn, "d1", "Long Doc", &long_content).unwrap();
 
-        let results = search_documents_inner(&conn, "searchtarget", 10).unwrap();
+        let results = search_documents_inner(&conn, "searchtarget", 10, &SearchOptions::default()).unwrap();
         assert_eq!(results.len(), 1);
     }
 
@@ -623,11 +1523,11 @@ mod tests {
         index_document_inner(&conn, "d1", "Title", &content).unwrap();
 
         // "alpha" is within the truncation window — should be found.
-        let found = search_documents_inner(&conn, "alpha", 10).unwrap();
+        let found = search_documents_inner(&conn, "alpha", 10, &SearchOptions::default()).unwrap();
         assert_eq!(found.len(), 1);
 
         // "uniquewordpastlimit" is past the truncation limit — should not be found.
-        let not_found = search_documents_inner(&conn, "uniquewordpastlimit", 10).unwrap();
+        let not_found = search_documents_inner(&conn, "uniquewordpastlimit", 10, &SearchOptions::default()).unwrap();
         assert_eq!(not_found.len(), 0);
     }
 
@@ -636,7 +1536,7 @@ mod tests {
         let conn = setup_db();
         index_document_inner(&conn, "d1", "Title", "The quick brown fox jumps over the lazy dog").unwrap();
 
-        let results = search_documents_inner(&conn, "fox", 10).unwrap();
+        let results = search_documents_inner(&conn, "fox", 10, &SearchOptions::default()).unwrap();
         assert_eq!(results.len(), 1);
         assert!(results[0].snippet.contains("<mark>"), "snippet should contain <mark> tag");
     }
@@ -671,6 +1571,263 @@ mod tests {
         assert_eq!(result, "\"say\"* \"hello\"*");
     }
 
+    #[test]
+    fn advanced_query_supports_phrases_operators_and_exclusions() {
+        assert_eq!(advanced_fts_query("\"quick brown\" fox").unwrap(), "\"quick brown\" \"fox\"*");
+        assert_eq!(advanced_fts_query("cat OR dog").unwrap(), "\"cat\"* OR \"dog\"*");
+        assert_eq!(
+            advanced_fts_query("cat OR dog -bird").unwrap(),
+            "(\"cat\"* OR \"dog\"*) NOT \"bird\"*"
+        );
+        for malformed in ["\"unclosed", "OR cat", "cat AND", "cat OR -dog", "-dog", "cat NEAR dog"] {
+            assert_eq!(advanced_fts_query(malformed), None, "{malformed}");
+        }
+        // Malformed input falls back to the sanitized query
+        let advanced = SearchOptions { advanced: true, ..Default::default() };
+        assert_eq!(fts_query_for("\"unclosed", &advanced), sanitize_fts_query("\"unclosed"));
+        assert_eq!(fts_query_for("cat OR dog", &SearchOptions::default()), sanitize_fts_query("cat OR dog"));
+    }
+
+    #[test]
+    fn advanced_search_matches_exact_phrases_and_excludes_terms() {
+        let conn = setup_db();
+        index_document_inner(&conn, "d1", "One", "the quick brown fox").unwrap();
+        index_document_inner(&conn, "d2", "Two", "brown and quick dogs").unwrap();
+        index_document_inner(&conn, "d3", "Three", "a quick brown bird").unwrap();
+
+        let ids = |query: &str| -> Vec<String> {
+            let mut ids: Vec<String> = search_documents_inner(&conn, query, 10, &SearchOptions { advanced: true, ..Default::default() })
+                .unwrap()
+                .into_iter()
+                .map(|r| r.document_id)
+                .collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(ids("\"quick brown\""), vec!["d1", "d3"]);
+        assert_eq!(ids("\"quick brown\" -bird"), vec!["d1"]);
+        assert_eq!(ids("fox OR dogs"), vec!["d1", "d2"]);
+    }
+
+    #[test]
+    fn search_in_document_returns_every_hit_with_offsets() {
+        let conn = setup_db();
+        let content = format!("Cats nap. {} Later the cat woke up.", "filler ".repeat(20));
+        index_document_inner(&conn, "d1", "Pets", &content).unwrap();
+        index_document_inner(&conn, "d2", "Other", "cat cat cat").unwrap();
+
+        // Without the original text, offsets point into the indexed content
+        let hits = search_in_document_inner(&conn, "d1", "cat", None).unwrap();
+        assert_eq!(hits.len(), 2);
+        for hit in &hits {
+            let word = &content[hit.offset..hit.offset + hit.length];
+            assert!(word.to_lowercase().starts_with("cat"), "{word}");
+            assert!(hit.snippet.contains(&format!("<mark>{word}</mark>")));
+        }
+        assert_eq!(hits[0].offset, 0);
+        assert!(!hits[0].snippet.starts_with('\u{2026}'));
+        assert!(hits[1].snippet.starts_with('\u{2026}'));
+
+        assert!(search_in_document_inner(&conn, "d1", "dog", None).unwrap().is_empty());
+        assert!(search_in_document_inner(&conn, "missing", "cat", None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn search_in_document_matches_like_library_search() {
+        let conn = setup_db();
+        conn.execute("INSERT INTO documents (id, source, title) VALUES ('d1', 'file', 'Trip')", []).unwrap();
+        let content = "---\ntitle: Trip\n---\nA café in Việt Nam \u{1F600} serves cafe sữa. Do not miss it.";
+        index_document_inner(&conn, "d1", "Trip", content).unwrap();
+
+        let utf16: Vec<u16> = content.encode_utf16().collect();
+        let words = |hits: &[DocumentMatch]| -> Vec<String> {
+            hits.iter()
+                .map(|h| String::from_utf16(&utf16[h.offset..h.offset + h.length]).unwrap())
+                .collect()
+        };
+        let search = |query: &str| search_in_document_inner(&conn, "d1", query, Some(content)).unwrap();
+
+        // The tokenizer folds every diacritic; offsets are UTF-16 and count
+        // the front matter and the emoji before the second hit
+        let hits = search("cafe");
+        assert_eq!(words(&hits), vec!["café", "cafe"]);
+        assert_eq!(hits[0].offset, content.find("café").unwrap());
+        assert_eq!(words(&search("viet")), vec!["Việt"]);
+        assert_eq!(words(&search("sua")), vec!["sữa"]);
+
+        // Terms are ANDed, and operator words are ordinary terms
+        assert!(search("cafe tea").is_empty());
+        assert_eq!(words(&search("not")), vec!["not"]);
+        assert!(search("nam OR").is_empty());
+    }
+
+    #[test]
+    fn search_in_document_refreshes_a_stale_index_entry() {
+        let conn = setup_db();
+        conn.execute("INSERT INTO documents (id, source, title) VALUES ('d1', 'file', 'Notes')", []).unwrap();
+        index_document_inner(&conn, "d1", "Notes", "old words").unwrap();
+
+        let content = "new words about otters";
+        let hits = search_in_document_inner(&conn, "d1", "otter", Some(content)).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].offset, content.find("otters").unwrap());
+        assert_eq!(fetch_indexed_content(&conn, "d1").unwrap().as_deref(), Some(content));
+        let indexed_at: Option<i64> = conn
+            .query_row("SELECT indexed_at FROM documents WHERE id = 'd1'", [], |row| row.get(0))
+            .unwrap();
+        assert!(indexed_at.is_some());
+    }
+
+    #[test]
+    fn whole_word_mode_drops_prefix_matching() {
+        let conn = setup_db();
+        index_document_inner(&conn, "d1", "Code", "programming in Rust").unwrap();
+        index_document_inner(&conn, "d2", "Stance", "the pro and the con").unwrap();
+
+        let whole_word = SearchOptions { whole_word: true, ..Default::default() };
+        assert_eq!(fts_query_for("pro con", &whole_word), "\"pro\" \"con\"");
+
+        let mut prefix: Vec<String> = search_documents_inner(&conn, "pro", 10, &SearchOptions::default())
+            .unwrap()
+            .into_iter()
+            .map(|r| r.document_id)
+            .collect();
+        prefix.sort();
+        assert_eq!(prefix, vec!["d1", "d2"]);
+
+        let exact = search_documents_inner(&conn, "pro", 10, &whole_word).unwrap();
+        assert_eq!(exact.len(), 1);
+        assert_eq!(exact[0].document_id, "d2");
+        // Multiple terms are still tokenized separately
+        assert_eq!(search_documents_inner(&conn, "pro con", 10, &whole_word).unwrap().len(), 1);
+        assert!(search_documents_inner(&conn, "programming pro", 10, &whole_word).unwrap().is_empty());
+    }
+
+    #[test]
+    fn snippet_options_validate_and_shape_snippets() {
+        assert_eq!(SnippetOptions::new(None, None, None).unwrap(), SnippetOptions::default());
+        assert_eq!(SnippetOptions::new(Some(0), None, None).unwrap().tokens, 1);
+        assert_eq!(SnippetOptions::new(Some(500), None, None).unwrap().tokens, 64);
+        assert!(SnippetOptions::new(None, Some("<b class=\"x\">".to_string()), None).is_err());
+        assert!(SnippetOptions::new(None, None, Some("'".to_string())).is_err());
+
+        let conn = setup_db();
+        let content = format!("{} needle {}", "before ".repeat(30), "after ".repeat(30));
+        index_document_inner(&conn, "d1", "Doc", &content).unwrap();
+        let options = SearchOptions {
+            snippet: SnippetOptions::new(Some(4), Some("[[".to_string()), Some("]]".to_string())).unwrap(),
+            ..Default::default()
+        };
+        let results = search_documents_inner(&conn, "needle", 10, &options).unwrap();
+        assert!(results[0].snippet.contains("[[needle]]"));
+        assert!(results[0].snippet.split_whitespace().count() <= 6);
+    }
+
+    #[test]
+    fn walk_search_matches_names_and_content_and_skips_hidden() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("notes/deep")).unwrap();
+        std::fs::create_dir_all(root.join(".trash")).unwrap();
+        std::fs::write(root.join("Garden Plans.md"), "tomatoes").unwrap();
+        std::fs::write(root.join("notes/deep/log.markdown"), "Planted the GARDEN today").unwrap();
+        std::fs::write(root.join("notes/other.md"), "nothing here").unwrap();
+        std::fs::write(root.join("notes/garden.txt"), "garden").unwrap();
+        std::fs::write(root.join(".trash/garden.md"), "garden").unwrap();
+
+        let results = walk_search(root, "garden", 10);
+        let mut names: Vec<&str> = results.iter().map(|r| r.filename.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["Garden Plans", "log"]);
+
+        assert_eq!(walk_search(root, "garden", 1).len(), 1);
+    }
+
+    #[test]
+    fn note_search_follows_note_edits_and_deletes() {
+        let conn = setup_db();
+        conn.execute_batch(
+            "CREATE TABLE highlights (
+                id TEXT PRIMARY KEY,
+                document_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+                text_content TEXT NOT NULL,
+                deleted_at INTEGER
+            );
+            CREATE TABLE margin_notes (
+                id TEXT PRIMARY KEY,
+                highlight_id TEXT NOT NULL REFERENCES highlights(id) ON DELETE CASCADE,
+                content TEXT NOT NULL
+            );
+            INSERT INTO documents (id, source, title) VALUES ('d1', 'file', 'Essay');
+            INSERT INTO highlights (id, document_id, text_content) VALUES ('h1', 'd1', 'the passage');
+            INSERT INTO margin_notes (id, highlight_id, content) VALUES ('n0', 'h1', 'written before indexing');",
+        )
+        .unwrap();
+        crate::db::migrations::migrate_add_notes_fts(&conn).unwrap();
+
+        // Existing notes are backfilled
+        assert_eq!(search_notes_inner(&conn, "indexing", 10).unwrap().len(), 1);
+
+        conn.execute("INSERT INTO margin_notes (id, highlight_id, content) VALUES ('n1', 'h1', 'an epiphany about rhythm')", [])
+            .unwrap();
+        let results = search_notes_inner(&conn, "epiph", 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].note_id, "n1");
+        assert_eq!(results[0].highlight_text, "the passage");
+        assert_eq!(results[0].document_title.as_deref(), Some("Essay"));
+        assert!(results[0].snippet.contains("<mark>epiphany</mark>"));
+
+        conn.execute("UPDATE margin_notes SET content = 'about cadence' WHERE id = 'n1'", []).unwrap();
+        assert!(search_notes_inner(&conn, "epiphany", 10).unwrap().is_empty());
+        assert_eq!(search_notes_inner(&conn, "cadence", 10).unwrap().len(), 1);
+
+        // Cascading delete from the highlight clears the index too
+        conn.execute("DELETE FROM highlights WHERE id = 'h1'", []).unwrap();
+        assert!(search_notes_inner(&conn, "cadence", 10).unwrap().is_empty());
+        let indexed: i64 = conn.query_row("SELECT COUNT(*) FROM notes_fts", [], |r| r.get(0)).unwrap();
+        assert_eq!(indexed, 0);
+    }
+
+    #[test]
+    fn deleting_a_document_removes_it_from_search() {
+        let conn = setup_db();
+        crate::db::migrations::migrate_add_documents_fts_delete_trigger(&conn).unwrap();
+        conn.execute("INSERT INTO documents (id, source, title) VALUES ('d1', 'file', 'Gone')", []).unwrap();
+        conn.execute("INSERT INTO documents (id, source, title) VALUES ('d2', 'file', 'Kept')", []).unwrap();
+        index_document_inner(&conn, "d1", "Gone", "ephemeral words").unwrap();
+        index_document_inner(&conn, "d2", "Kept", "lasting words").unwrap();
+
+        conn.execute("DELETE FROM documents WHERE id = 'd1'", []).unwrap();
+
+        assert!(search_documents_inner(&conn, "ephemeral", 10, &SearchOptions::default()).unwrap().is_empty());
+        assert_eq!(search_documents_inner(&conn, "words", 10, &SearchOptions::default()).unwrap().len(), 1);
+        assert_eq!(fetch_indexed_content(&conn, "d1").unwrap(), None);
+    }
+
+    #[test]
+    fn search_results_carry_file_path_and_source() {
+        let conn = setup_db();
+        conn.execute_batch(
+            "INSERT INTO documents (id, source, file_path, title) VALUES ('d1', 'file', '/notes/a.md', 'A');
+             INSERT INTO documents (id, source, keep_local_id, title) VALUES ('d2', 'keep-local', 'k1', 'B');",
+        )
+        .unwrap();
+        index_document_inner(&conn, "d1", "A", "shared term").unwrap();
+        index_document_inner(&conn, "d2", "B", "shared term").unwrap();
+
+        for results in [
+            search_documents_inner(&conn, "shared", 10, &SearchOptions::default()).unwrap(),
+            search_documents_raw_inner(&conn, "shared", 10).unwrap(),
+        ] {
+            let a = results.iter().find(|r| r.document_id == "d1").unwrap();
+            assert_eq!(a.file_path.as_deref(), Some("/notes/a.md"));
+            assert_eq!(a.source.as_deref(), Some("file"));
+            let b = results.iter().find(|r| r.document_id == "d2").unwrap();
+            assert_eq!(b.file_path, None);
+            assert_eq!(b.source.as_deref(), Some("keep-local"));
+        }
+    }
+
     // === Step 3: Frecency tests ===
 
     #[test]
@@ -696,7 +1853,7 @@ mod tests {
         index_document_inner(&conn, "d1", "Recent Rust", "Learn Rust systems").unwrap();
         index_document_inner(&conn, "d2", "Old Rust", "Learn Rust systems").unwrap();
 
-        let results = search_documents_inner(&conn, "Rust", 10).unwrap();
+        let results = search_documents_inner(&conn, "Rust", 10, &SearchOptions::default()).unwrap();
         assert_eq!(results.len(), 2);
         // With frecency boosting, recently opened should rank higher
         assert_eq!(results[0].document_id, "d1");
@@ -724,11 +1881,67 @@ mod tests {
         index_document_inner(&conn, "d1", "Frequent Rust", "Learn Rust basics").unwrap();
         index_document_inner(&conn, "d2", "Rare Rust", "Learn Rust basics").unwrap();
 
-        let results = search_documents_inner(&conn, "Rust", 10).unwrap();
+        let results = search_documents_inner(&conn, "Rust", 10, &SearchOptions::default()).unwrap();
         assert_eq!(results.len(), 2);
         assert_eq!(results[0].document_id, "d1");
     }
 
+    #[test]
+    fn raw_search_ignores_frecency() {
+        let conn = setup_db_with_documents();
+        let now = now_millis();
+        conn.execute(
+            "INSERT INTO documents (id, source, title, last_opened_at, created_at, access_count) VALUES
+             ('d1', 'file', 'Notes', ?1, 1000, 500),
+             ('d2', 'file', 'Rust', 1000, 1000, 0)",
+            [now],
+        ).unwrap();
+        index_document_inner(&conn, "d1", "Notes", "A passing mention of Rust").unwrap();
+        index_document_inner(&conn, "d2", "Rust", "Rust ownership, Rust lifetimes, Rust traits").unwrap();
+
+        let raw = search_documents_raw_inner(&conn, "Rust", 10).unwrap();
+        let raw_ids: Vec<&str> = raw.iter().map(|r| r.document_id.as_str()).collect();
+        assert_eq!(raw_ids, vec!["d2", "d1"]);
+        assert!(raw[0].snippet.contains("<mark>"));
+
+        // Opening the weaker match more doesn't move it
+        conn.execute("UPDATE documents SET access_count = 5000 WHERE id = 'd1'", []).unwrap();
+        let again: Vec<String> = search_documents_raw_inner(&conn, "Rust", 10)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.document_id)
+            .collect();
+        assert_eq!(again, vec!["d2", "d1"]);
+
+        // Whereas blended search lets frecency win
+        assert_eq!(search_documents_inner(&conn, "Rust", 10, &SearchOptions::default()).unwrap()[0].document_id, "d1");
+    }
+
+    #[test]
+    fn relevance_only_search_ignores_access_count() {
+        let conn = setup_db_with_documents();
+        let now = now_millis();
+        conn.execute(
+            "INSERT INTO documents (id, source, title, last_opened_at, created_at, access_count) VALUES
+             ('d1', 'file', 'Diary', ?1, 1000, 10000),
+             ('d2', 'file', 'Compilers', 1000, 1000, 0)",
+            [now],
+        ).unwrap();
+        index_document_inner(&conn, "d1", "Diary", "Today I read about parsers").unwrap();
+        index_document_inner(&conn, "d2", "Compilers", "parsers, parsers everywhere: parsers for every grammar").unwrap();
+
+        let relevance = SearchOptions { frecency: false, ..Default::default() };
+        let ids: Vec<String> = search_documents_inner(&conn, "parsers", 10, &relevance)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.document_id)
+            .collect();
+        assert_eq!(ids, vec!["d2", "d1"]);
+
+        let blended = search_documents_inner(&conn, "parsers", 10, &SearchOptions::default()).unwrap();
+        assert_eq!(blended[0].document_id, "d1");
+    }
+
     #[test]
     fn frecency_score_decays_over_time() {
         let conn = setup_db_with_documents();
@@ -752,12 +1965,155 @@ mod tests {
         index_document_inner(&conn, "d1", "New Rust", "Learn Rust now").unwrap();
         index_document_inner(&conn, "d2", "Stale Rust", "Learn Rust now").unwrap();
 
-        let results = search_documents_inner(&conn, "Rust", 10).unwrap();
+        let results = search_documents_inner(&conn, "Rust", 10, &SearchOptions::default()).unwrap();
         assert_eq!(results.len(), 2);
         // Recent doc should rank higher despite lower access count (decay suppresses stale)
         assert_eq!(results[0].document_id, "d1");
     }
 
+    // === run_with_deadlines tests ===
+
+    fn collect_with_deadlines<T, R, F>(items: Vec<T>, threads: usize, timeout: Duration, work: F) -> Vec<Option<R>>
+    where
+        T: Send + 'static,
+        R: Send + 'static,
+        F: Fn(T) -> R + Send + Sync + 'static,
+    {
+        let mut results: Vec<(usize, Option<R>)> = Vec::new();
+        run_with_deadlines(items, threads, timeout, work, |idx, out| results.push((idx, out)));
+        results.sort_by_key(|(idx, _)| *idx);
+        results.into_iter().map(|(_, out)| out).collect()
+    }
+
+    #[test]
+    fn run_with_deadlines_runs_items_in_parallel() {
+        // Four items can only pass a four-party barrier if four workers run them at once
+        let barrier = Arc::new(std::sync::Barrier::new(4));
+        let out = collect_with_deadlines((0..4).collect(), 4, Duration::from_secs(5), move |i: i32| {
+            barrier.wait();
+            i * 10
+        });
+        assert_eq!(out, vec![Some(0), Some(10), Some(20), Some(30)]);
+    }
+
+    #[test]
+    fn run_with_deadlines_times_out_stalled_items_only() {
+        // Item 0 stands in for a read blocked on an unresponsive network mount
+        let started = Instant::now();
+        let out = collect_with_deadlines((0..5).collect(), 2, Duration::from_millis(100), |i: u64| {
+            if i == 0 {
+                std::thread::sleep(Duration::from_secs(10));
+            }
+            i
+        });
+        assert_eq!(out, vec![None, Some(1), Some(2), Some(3), Some(4)]);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn run_with_deadlines_gives_up_when_every_worker_is_stuck() {
+        let started = Instant::now();
+        let out = collect_with_deadlines((0..3).collect(), 1, Duration::from_millis(50), |i: u64| {
+            if i == 0 {
+                std::thread::sleep(Duration::from_secs(10));
+            }
+            i
+        });
+        // The lone worker never comes back, so nothing after it starts
+        assert_eq!(out, vec![None, None, None]);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn read_if_changed_skips_unchanged_and_missing_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("note.md");
+        std::fs::write(&path, "words").unwrap();
+        let path = path.to_string_lossy().to_string();
+
+        assert!(matches!(read_if_changed(&path, None), FileRead::Read(c) if c == "words"));
+        assert!(matches!(read_if_changed(&path, Some(i64::MAX)), FileRead::Unchanged));
+        assert!(matches!(read_if_changed(&path, Some(0)), FileRead::Read(_)));
+        let missing = dir.path().join("gone.md").to_string_lossy().to_string();
+        assert!(matches!(read_if_changed(&missing, None), FileRead::Missing));
+    }
+
+    // === index_file tests ===
+
+    #[test]
+    fn index_file_indexes_tracked_document() {
+        let conn = setup_db_with_documents();
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("note.md");
+        std::fs::write(&file_path, "Fresh words about ferrets").unwrap();
+        conn.execute(
+            "INSERT INTO documents (id, source, file_path, title, last_opened_at, created_at)
+             VALUES ('d1', 'file', ?1, 'Note', 1000, 1000)",
+            [file_path.to_str().unwrap()],
+        ).unwrap();
+
+        assert!(index_file_inner(&conn, file_path.to_str().unwrap(), "Fresh words about ferrets").unwrap());
+        let results = search_documents_inner(&conn, "ferrets", 10, &SearchOptions::default()).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].document_id, "d1");
+    }
+
+    #[test]
+    fn reindex_document_refreshes_one_file_and_reports_missing_ones() {
+        let conn = setup_db();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("note.md");
+        std::fs::write(&path, "first draft").unwrap();
+        let path_str = path.to_string_lossy().to_string();
+        conn.execute(
+            "INSERT INTO documents (id, source, file_path, title) VALUES ('d1', 'file', ?1, 'Note')",
+            [&path_str],
+        ).unwrap();
+        conn.execute("INSERT INTO documents (id, source, title) VALUES ('d2', 'keep-local', 'Web')", []).unwrap();
+        let pool = DbPool(Mutex::new(conn));
+        let indexed = |id: &str| fetch_indexed_content(&pool.0.lock().unwrap(), id).unwrap();
+
+        reindex_document_inner(&pool, "d1").unwrap();
+        assert_eq!(indexed("d1").as_deref(), Some("first draft"));
+        let indexed_at: Option<i64> = pool.0.lock().unwrap()
+            .query_row("SELECT indexed_at FROM documents WHERE id = 'd1'", [], |r| r.get(0))
+            .unwrap();
+        assert!(indexed_at.is_some());
+
+        std::fs::write(&path, "second draft").unwrap();
+        reindex_document_inner(&pool, "d1").unwrap();
+        assert_eq!(indexed("d1").as_deref(), Some("second draft"));
+
+        std::fs::remove_file(&path).unwrap();
+        let err = reindex_document_inner(&pool, "d1").unwrap_err();
+        assert!(err.contains("File not found"), "{err}");
+        assert_eq!(indexed("d1").as_deref(), Some("second draft"));
+
+        assert!(reindex_document_inner(&pool, "d2").unwrap_err().contains("no file"));
+        assert!(reindex_document_inner(&pool, "nope").unwrap_err().contains("not found"));
+    }
+
+    #[test]
+    fn index_file_ignores_untracked_path() {
+        let conn = setup_db_with_documents();
+        assert!(!index_file_inner(&conn, "/not/tracked.md", "words").unwrap());
+    }
+
+    #[test]
+    fn read_file_for_index_guards_size_and_binary_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let text = dir.path().join("note.md");
+        std::fs::write(&text, "caf\u{e9} notes").unwrap();
+        let binary = dir.path().join("blob.md");
+        std::fs::write(&binary, b"abc\0def").unwrap();
+        let timeout = Duration::from_secs(5);
+
+        let read = |path: &Path| read_file_for_index(&path.to_string_lossy(), timeout);
+        assert_eq!(read(&text).unwrap().as_deref(), Some("caf\u{e9} notes"));
+        assert_eq!(read(&binary).unwrap(), None);
+        assert!(read(&dir.path().join("gone.md")).is_err());
+    }
+
     // === Step 5: Background indexing tests ===
 
     #[test]
@@ -779,7 +2135,7 @@ mod tests {
         assert_eq!(result.errors, 0);
 
         // Verify it's searchable
-        let results = search_documents_inner(&conn, "Rust", 10).unwrap();
+        let results = search_documents_inner(&conn, "Rust", 10, &SearchOptions::default()).unwrap();
         assert_eq!(results.len(), 1);
     }
 
@@ -802,6 +2158,35 @@ mod tests {
         assert_eq!(result.skipped, 1);
     }
 
+    #[test]
+    fn stale_documents_are_changed_or_never_indexed() {
+        let conn = setup_db_with_documents();
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["fresh.md", "changed.md", "new.md"] {
+            std::fs::write(dir.path().join(name), "content").unwrap();
+        }
+        let path = |name: &str| dir.path().join(name).to_string_lossy().to_string();
+        conn.execute(
+            "INSERT INTO documents (id, source, file_path, title, last_opened_at, created_at, indexed_at) VALUES
+             ('fresh', 'file', ?1, 'Fresh', 1000, 1000, 9999999999999),
+             ('changed', 'file', ?2, 'Changed', 1000, 1000, 0),
+             ('new', 'file', ?3, 'New', 1000, 1000, NULL),
+             ('gone', 'file', '/nonexistent/path/doc.md', 'Gone', 1000, 1000, NULL)",
+            rusqlite::params![path("fresh.md"), path("changed.md"), path("new.md")],
+        ).unwrap();
+
+        let stale = select_stale_documents(fetch_indexable_documents(&conn).unwrap());
+        let mut ids: Vec<&str> = stale.iter().map(|d| d.id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["changed", "new"]);
+
+        // Nothing is indexed by the preflight
+        let indexed: i64 = conn
+            .query_row("SELECT COUNT(*) FROM documents WHERE indexed_at IS NULL", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(indexed, 2);
+    }
+
     #[test]
     fn index_all_handles_missing_files() {
         let conn = setup_db_with_documents();
@@ -844,7 +2229,7 @@ mod tests {
         let result = index_all_documents_inner(&conn).unwrap();
         assert_eq!(result.indexed, 1);
 
-        let results = search_documents_inner(&conn, "Python", 10).unwrap();
+        let results = search_documents_inner(&conn, "Python", 10, &SearchOptions::default()).unwrap();
         assert_eq!(results.len(), 1);
     }
 
@@ -867,6 +2252,39 @@ mod tests {
         assert_eq!(result.indexed, 3);
     }
 
+    #[test]
+    fn index_all_records_last_summary() {
+        let conn = setup_db_with_documents();
+        assert!(fetch_last_index_summary(&conn).unwrap().is_none());
+
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("doc.md");
+        std::fs::write(&file_path, "Content").unwrap();
+        conn.execute(
+            "INSERT INTO documents (id, source, file_path, title, last_opened_at, created_at)
+             VALUES ('d1', 'file', ?1, 'Doc', 1000, 1000)",
+            [file_path.to_str().unwrap()],
+        ).unwrap();
+        conn.execute(
+            "INSERT INTO documents (id, source, file_path, title, last_opened_at, created_at)
+             VALUES ('d2', 'file', '/nonexistent/path/doc.md', 'Missing', 1000, 1000)",
+            [],
+        ).unwrap();
+
+        index_all_documents_inner(&conn).unwrap();
+        let summary = fetch_last_index_summary(&conn).unwrap().unwrap();
+        assert_eq!(summary.indexed, 1);
+        assert_eq!(summary.skipped, 1);
+        assert_eq!(summary.errors, 0);
+        assert!(summary.finished_at > 0);
+
+        // A second run overwrites the stored summary
+        index_all_documents_inner(&conn).unwrap();
+        let summary = fetch_last_index_summary(&conn).unwrap().unwrap();
+        assert_eq!(summary.indexed, 0);
+        assert_eq!(summary.skipped, 2);
+    }
+
     // === Increment access count test ===
 
     #[test]
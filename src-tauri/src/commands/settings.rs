@@ -0,0 +1,325 @@
+use crate::commands::now_millis;
+use crate::db::migrations::{validate_busy_timeout, DbPool, BUSY_TIMEOUT_KEY};
+use rusqlite::Connection;
+use std::collections::HashMap;
+
+const SCAN_THREADS_KEY: &str = "scan_threads";
+const INDEX_READ_TIMEOUT_KEY: &str = "index_read_timeout_ms";
+const DEFAULT_INDEX_READ_TIMEOUT_MS: u64 = 10_000;
+const NOTE_TEMPLATES_KEY: &str = "note_templates";
+const DEFAULT_HIGHLIGHT_COLOR_KEY: &str = "default_highlight_color";
+const WORKSPACE_DIR_KEY: &str = "workspace_dir";
+/// Mirrors `HIGHLIGHT_COLORS` in `src/lib/highlight-colors.ts`.
+pub(crate) const HIGHLIGHT_PALETTE: [&str; 5] = ["yellow", "green", "blue", "pink", "orange"];
+
+// === Inner functions (testable with &Connection) ===
+
+/// Reads a raw setting value, or `None` when the key has never been set.
+pub(crate) fn get_setting(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+    let result = conn.query_row(
+        "SELECT value FROM settings WHERE key = ?1",
+        rusqlite::params![key],
+        |row| row.get(0),
+    );
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Inserts or replaces a setting value.
+pub(crate) fn set_setting(conn: &Connection, key: &str, value: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(key) DO UPDATE SET
+            value = excluded.value,
+            updated_at = excluded.updated_at",
+        rusqlite::params![key, value, now_millis()],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn default_scan_threads() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+/// Worker count for disk scans (directory walks, `index_all_documents`).
+/// Falls back to the machine's available parallelism when unset or invalid.
+pub(crate) fn scan_threads(conn: &Connection) -> usize {
+    get_setting(conn, SCAN_THREADS_KEY)
+        .ok()
+        .flatten()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|n| *n >= 1)
+        .unwrap_or_else(default_scan_threads)
+}
+
+fn set_scan_threads_inner(conn: &Connection, threads: usize) -> Result<(), String> {
+    if threads < 1 {
+        return Err("scan_threads must be at least 1".to_string());
+    }
+    set_setting(conn, SCAN_THREADS_KEY, &threads.to_string())
+}
+
+/// Per-file budget for stat/read during `index_all_documents`.
+pub(crate) fn index_read_timeout(conn: &Connection) -> std::time::Duration {
+    let ms = get_setting(conn, INDEX_READ_TIMEOUT_KEY)
+        .ok()
+        .flatten()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|ms| *ms > 0)
+        .unwrap_or(DEFAULT_INDEX_READ_TIMEOUT_MS);
+    std::time::Duration::from_millis(ms)
+}
+
+fn set_index_read_timeout_inner(conn: &Connection, timeout_ms: u64) -> Result<(), String> {
+    if timeout_ms == 0 {
+        return Err("index_read_timeout_ms must be greater than 0".to_string());
+    }
+    set_setting(conn, INDEX_READ_TIMEOUT_KEY, &timeout_ms.to_string())
+}
+
+fn busy_timeout_ms(conn: &Connection) -> Result<u32, String> {
+    conn.query_row("PRAGMA busy_timeout", [], |row| row.get(0))
+        .map_err(|e| e.to_string())
+}
+
+/// Stores the timeout for future launches and applies it to the live connection.
+fn set_busy_timeout_inner(conn: &Connection, timeout_ms: u32) -> Result<(), String> {
+    let timeout_ms = validate_busy_timeout(timeout_ms)?;
+    set_setting(conn, BUSY_TIMEOUT_KEY, &timeout_ms.to_string())?;
+    conn.execute_batch(&format!("PRAGMA busy_timeout={timeout_ms};"))
+        .map_err(|e| e.to_string())
+}
+
+/// Note templates keyed by highlight color, stored as one JSON object.
+fn fetch_note_templates(conn: &Connection) -> Result<HashMap<String, String>, String> {
+    match get_setting(conn, NOTE_TEMPLATES_KEY)? {
+        Some(json) => serde_json::from_str(&json).map_err(|e| format!("Invalid note templates: {e}")),
+        None => Ok(HashMap::new()),
+    }
+}
+
+pub(crate) fn note_template_for(conn: &Connection, color: &str) -> Result<Option<String>, String> {
+    Ok(fetch_note_templates(conn)?.remove(color))
+}
+
+/// An empty or missing template removes the color's entry.
+fn set_note_template_inner(conn: &Connection, color: &str, template: Option<&str>) -> Result<(), String> {
+    let mut templates = fetch_note_templates(conn)?;
+    match template.filter(|t| !t.trim().is_empty()) {
+        Some(t) => {
+            templates.insert(color.to_string(), t.to_string());
+        }
+        None => {
+            templates.remove(color);
+        }
+    }
+    let json = serde_json::to_string(&templates).map_err(|e| e.to_string())?;
+    set_setting(conn, NOTE_TEMPLATES_KEY, &json)
+}
+
+/// Color for highlights created without one. A stored value that has fallen
+/// out of the palette is ignored.
+pub(crate) fn default_highlight_color(conn: &Connection) -> String {
+    get_setting(conn, DEFAULT_HIGHLIGHT_COLOR_KEY)
+        .ok()
+        .flatten()
+        .filter(|c| HIGHLIGHT_PALETTE.contains(&c.as_str()))
+        .unwrap_or_else(|| HIGHLIGHT_PALETTE[0].to_string())
+}
+
+fn set_default_highlight_color_inner(conn: &Connection, color: &str) -> Result<(), String> {
+    if !HIGHLIGHT_PALETTE.contains(&color) {
+        return Err(format!("Unknown highlight color '{color}'"));
+    }
+    set_setting(conn, DEFAULT_HIGHLIGHT_COLOR_KEY, color)
+}
+
+/// Folder most recently opened as the workspace root.
+pub(crate) fn workspace_dir(conn: &Connection) -> Result<Option<String>, String> {
+    get_setting(conn, WORKSPACE_DIR_KEY)
+}
+
+pub(crate) fn set_workspace_dir(conn: &Connection, dir: &str) -> Result<(), String> {
+    set_setting(conn, WORKSPACE_DIR_KEY, dir)
+}
+
+// === Tauri command handlers ===
+
+#[tauri::command]
+pub async fn get_scan_threads(state: tauri::State<'_, DbPool>) -> Result<usize, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    Ok(scan_threads(&conn))
+}
+
+#[tauri::command]
+pub async fn set_scan_threads(state: tauri::State<'_, DbPool>, threads: usize) -> Result<(), String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    set_scan_threads_inner(&conn, threads)
+}
+
+#[tauri::command]
+pub async fn get_index_read_timeout_ms(state: tauri::State<'_, DbPool>) -> Result<u64, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    Ok(index_read_timeout(&conn).as_millis() as u64)
+}
+
+#[tauri::command]
+pub async fn set_index_read_timeout_ms(state: tauri::State<'_, DbPool>, timeout_ms: u64) -> Result<(), String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    set_index_read_timeout_inner(&conn, timeout_ms)
+}
+
+#[tauri::command]
+pub async fn get_busy_timeout_ms(state: tauri::State<'_, DbPool>) -> Result<u32, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    busy_timeout_ms(&conn)
+}
+
+#[tauri::command]
+pub async fn set_busy_timeout_ms(state: tauri::State<'_, DbPool>, timeout_ms: u32) -> Result<(), String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    set_busy_timeout_inner(&conn, timeout_ms)
+}
+
+#[tauri::command]
+pub async fn get_note_templates(state: tauri::State<'_, DbPool>) -> Result<HashMap<String, String>, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    fetch_note_templates(&conn)
+}
+
+#[tauri::command]
+pub async fn set_note_template(
+    state: tauri::State<'_, DbPool>,
+    color: String,
+    template: Option<String>,
+) -> Result<(), String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    set_note_template_inner(&conn, &color, template.as_deref())
+}
+
+#[tauri::command]
+pub async fn get_default_highlight_color(state: tauri::State<'_, DbPool>) -> Result<String, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    Ok(default_highlight_color(&conn))
+}
+
+#[tauri::command]
+pub async fn set_default_highlight_color(state: tauri::State<'_, DbPool>, color: String) -> Result<(), String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    set_default_highlight_color_inner(&conn, &color)
+}
+
+#[tauri::command]
+pub async fn get_workspace(state: tauri::State<'_, DbPool>) -> Result<Option<String>, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    workspace_dir(&conn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations::{migrate_add_settings_table, DEFAULT_BUSY_TIMEOUT_MS};
+
+    fn setup_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate_add_settings_table(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn get_missing_setting_returns_none() {
+        let conn = setup_db();
+        assert!(get_setting(&conn, "nope").unwrap().is_none());
+    }
+
+    #[test]
+    fn set_setting_overwrites_existing_value() {
+        let conn = setup_db();
+        set_setting(&conn, "k", "one").unwrap();
+        set_setting(&conn, "k", "two").unwrap();
+
+        assert_eq!(get_setting(&conn, "k").unwrap().as_deref(), Some("two"));
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM settings", [], |r| r.get(0)).unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn scan_threads_defaults_to_available_parallelism() {
+        let conn = setup_db();
+        assert_eq!(scan_threads(&conn), default_scan_threads());
+        assert!(scan_threads(&conn) >= 1);
+    }
+
+    #[test]
+    fn scan_threads_uses_stored_value() {
+        let conn = setup_db();
+        set_scan_threads_inner(&conn, 1).unwrap();
+        assert_eq!(scan_threads(&conn), 1);
+    }
+
+    #[test]
+    fn scan_threads_rejects_zero() {
+        let conn = setup_db();
+        assert!(set_scan_threads_inner(&conn, 0).is_err());
+        // A zero written behind our back is ignored rather than honored
+        set_setting(&conn, SCAN_THREADS_KEY, "0").unwrap();
+        assert_eq!(scan_threads(&conn), default_scan_threads());
+    }
+
+    #[test]
+    fn index_read_timeout_defaults_and_overrides() {
+        let conn = setup_db();
+        assert_eq!(index_read_timeout(&conn).as_millis() as u64, DEFAULT_INDEX_READ_TIMEOUT_MS);
+
+        set_index_read_timeout_inner(&conn, 250).unwrap();
+        assert_eq!(index_read_timeout(&conn).as_millis(), 250);
+        assert!(set_index_read_timeout_inner(&conn, 0).is_err());
+    }
+
+    #[test]
+    fn busy_timeout_is_validated_stored_and_applied() {
+        let conn = setup_db();
+        assert!(set_busy_timeout_inner(&conn, 0).is_err());
+        assert_ne!(busy_timeout_ms(&conn).unwrap(), DEFAULT_BUSY_TIMEOUT_MS + 1);
+
+        set_busy_timeout_inner(&conn, DEFAULT_BUSY_TIMEOUT_MS + 1).unwrap();
+        assert_eq!(busy_timeout_ms(&conn).unwrap(), DEFAULT_BUSY_TIMEOUT_MS + 1);
+        assert_eq!(
+            get_setting(&conn, BUSY_TIMEOUT_KEY).unwrap(),
+            Some((DEFAULT_BUSY_TIMEOUT_MS + 1).to_string())
+        );
+    }
+
+    #[test]
+    fn note_templates_set_and_clear_per_color() {
+        let conn = setup_db();
+        assert!(note_template_for(&conn, "red").unwrap().is_none());
+
+        set_note_template_inner(&conn, "red", Some("Fix this: ")).unwrap();
+        set_note_template_inner(&conn, "blue", Some("Question: ")).unwrap();
+        assert_eq!(note_template_for(&conn, "red").unwrap().as_deref(), Some("Fix this: "));
+
+        set_note_template_inner(&conn, "red", Some("  ")).unwrap();
+        assert!(note_template_for(&conn, "red").unwrap().is_none());
+        assert_eq!(fetch_note_templates(&conn).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn default_highlight_color_validates_against_palette() {
+        let conn = setup_db();
+        assert_eq!(default_highlight_color(&conn), "yellow");
+
+        set_default_highlight_color_inner(&conn, "blue").unwrap();
+        assert_eq!(default_highlight_color(&conn), "blue");
+
+        assert!(set_default_highlight_color_inner(&conn, "mauve").is_err());
+        set_setting(&conn, DEFAULT_HIGHLIGHT_COLOR_KEY, "mauve").unwrap();
+        assert_eq!(default_highlight_color(&conn), "yellow");
+    }
+}
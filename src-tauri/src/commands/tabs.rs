@@ -9,6 +9,15 @@ pub struct PersistedTab {
     pub tab_order: i64,
     pub is_active: bool,
     pub created_at: i64,
+    /// Pinned tabs sort first and survive `close_unpinned_tabs`.
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TabSummary {
+    pub tab_count: i64,
+    pub active_document_id: Option<String>,
 }
 
 // === Inner functions (testable with &Connection) ===
@@ -16,9 +25,9 @@ pub struct PersistedTab {
 fn fetch_open_tabs(conn: &Connection) -> Result<Vec<PersistedTab>, String> {
     let mut stmt = conn
         .prepare(
-            "SELECT id, document_id, tab_order, is_active, created_at
+            "SELECT id, document_id, tab_order, is_active, created_at, pinned
              FROM open_tabs
-             ORDER BY tab_order ASC",
+             ORDER BY pinned DESC, tab_order ASC",
         )
         .map_err(|e| e.to_string())?;
 
@@ -30,6 +39,7 @@ fn fetch_open_tabs(conn: &Connection) -> Result<Vec<PersistedTab>, String> {
                 tab_order: row.get("tab_order")?,
                 is_active: row.get::<_, i64>("is_active")? != 0,
                 created_at: row.get("created_at")?,
+                pinned: row.get::<_, i64>("pinned")? != 0,
             })
         })
         .map_err(|e| e.to_string())?
@@ -38,6 +48,27 @@ fn fetch_open_tabs(conn: &Connection) -> Result<Vec<PersistedTab>, String> {
     results
 }
 
+fn fetch_tab_summary(conn: &Connection) -> Result<TabSummary, String> {
+    let tab_count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM open_tabs", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+
+    let active_document_id = match conn.query_row(
+        "SELECT document_id FROM open_tabs WHERE is_active = 1 ORDER BY tab_order ASC LIMIT 1",
+        [],
+        |row| row.get(0),
+    ) {
+        Ok(id) => Some(id),
+        Err(rusqlite::Error::QueryReturnedNoRows) => None,
+        Err(e) => return Err(e.to_string()),
+    };
+
+    Ok(TabSummary {
+        tab_count,
+        active_document_id,
+    })
+}
+
 fn persist_open_tabs(conn: &Connection, tabs: &[PersistedTab]) -> Result<(), String> {
     let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
 
@@ -46,8 +77,8 @@ fn persist_open_tabs(conn: &Connection, tabs: &[PersistedTab]) -> Result<(), Str
 
     let mut stmt = tx
         .prepare(
-            "INSERT INTO open_tabs (id, document_id, tab_order, is_active, created_at)
-             VALUES (?1, ?2, ?3, ?4, ?5)",
+            "INSERT INTO open_tabs (id, document_id, tab_order, is_active, created_at, pinned)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
         )
         .map_err(|e| e.to_string())?;
 
@@ -58,6 +89,7 @@ fn persist_open_tabs(conn: &Connection, tabs: &[PersistedTab]) -> Result<(), Str
             tab.tab_order,
             tab.is_active as i64,
             tab.created_at,
+            tab.pinned as i64,
         ])
         .map_err(|e| e.to_string())?;
     }
@@ -68,6 +100,86 @@ fn persist_open_tabs(conn: &Connection, tabs: &[PersistedTab]) -> Result<(), Str
     Ok(())
 }
 
+/// Rewrites `tab_order` as 0..n following `tabs`.
+fn renumber_open_tabs(conn: &Connection, tabs: &[PersistedTab]) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare("UPDATE open_tabs SET tab_order = ?1 WHERE id = ?2")
+        .map_err(|e| e.to_string())?;
+    for (order, tab) in tabs.iter().enumerate() {
+        stmt.execute(rusqlite::params![order as i64, tab.id])
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Returns the number of tabs closed. The pinned tabs left behind are
+/// renumbered from 0, and the first becomes active if the active tab was closed.
+fn close_unpinned_tabs_inner(conn: &Connection) -> Result<usize, String> {
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+
+    let closed = tx
+        .execute("DELETE FROM open_tabs WHERE pinned = 0", [])
+        .map_err(|e| e.to_string())?;
+
+    let remaining = fetch_open_tabs(&tx)?;
+    renumber_open_tabs(&tx, &remaining)?;
+    match remaining.first() {
+        Some(first) if !remaining.iter().any(|t| t.is_active) => {
+            tx.execute("UPDATE open_tabs SET is_active = 1 WHERE id = ?1", [&first.id])
+                .map_err(|e| e.to_string())?;
+        }
+        _ => {}
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(closed)
+}
+
+/// Moves one tab to position `new_order` in the displayed list (pinned tabs
+/// first), clamped so it stays within its own pinned or unpinned group. The
+/// tab takes the `tab_order` of the tab at that position and only the tabs
+/// in between shift by one; every other row is left untouched.
+fn move_tab_inner(conn: &Connection, tab_id: &str, new_order: i64) -> Result<(), String> {
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+
+    let tabs = fetch_open_tabs(&tx)?;
+    let old_index = tabs
+        .iter()
+        .position(|t| t.id == tab_id)
+        .ok_or_else(|| format!("Tab not found: {tab_id}"))?;
+    let pinned_count = tabs.iter().filter(|t| t.pinned).count() as i64;
+    let (first, last) = if tabs[old_index].pinned {
+        (0, pinned_count - 1)
+    } else {
+        (pinned_count, tabs.len() as i64 - 1)
+    };
+    let old_order = tabs[old_index].tab_order;
+    let target = tabs[new_order.clamp(first, last) as usize].tab_order;
+
+    // Tabs of the other group caught in the range shift too, which keeps
+    // every group's relative order (and so the display) intact
+    if target > old_order {
+        tx.execute(
+            "UPDATE open_tabs SET tab_order = tab_order - 1 WHERE tab_order > ?1 AND tab_order <= ?2",
+            rusqlite::params![old_order, target],
+        )
+        .map_err(|e| e.to_string())?;
+    } else if target < old_order {
+        tx.execute(
+            "UPDATE open_tabs SET tab_order = tab_order + 1 WHERE tab_order >= ?1 AND tab_order < ?2",
+            rusqlite::params![target, old_order],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    tx.execute(
+        "UPDATE open_tabs SET tab_order = ?1 WHERE id = ?2",
+        rusqlite::params![target, tab_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    tx.commit().map_err(|e| e.to_string())
+}
+
 // === Tauri command handlers ===
 
 #[tauri::command]
@@ -76,12 +188,31 @@ pub async fn get_open_tabs(state: tauri::State<'_, DbPool>) -> Result<Vec<Persis
     fetch_open_tabs(&conn)
 }
 
+#[tauri::command]
+pub async fn get_tab_summary(state: tauri::State<'_, DbPool>) -> Result<TabSummary, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    fetch_tab_summary(&conn)
+}
+
 #[tauri::command]
 pub async fn save_open_tabs(state: tauri::State<'_, DbPool>, tabs: Vec<PersistedTab>) -> Result<(), String> {
     let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
     persist_open_tabs(&conn, &tabs)
 }
 
+/// Reorders a single tab; `save_open_tabs` remains the way to replace the whole set.
+#[tauri::command]
+pub async fn move_tab(state: tauri::State<'_, DbPool>, tab_id: String, new_order: i64) -> Result<(), String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    move_tab_inner(&conn, &tab_id, new_order)
+}
+
+#[tauri::command]
+pub async fn close_unpinned_tabs(state: tauri::State<'_, DbPool>) -> Result<usize, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    close_unpinned_tabs_inner(&conn)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -106,7 +237,8 @@ mod tests {
              document_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
              tab_order INTEGER NOT NULL,
              is_active INTEGER NOT NULL DEFAULT 0,
-             created_at INTEGER NOT NULL
+             created_at INTEGER NOT NULL,
+             pinned INTEGER NOT NULL DEFAULT 0
          );"
     }
 
@@ -133,6 +265,7 @@ mod tests {
             tab_order: order,
             is_active: active,
             created_at: 1000,
+            pinned: false,
         }
     }
 
@@ -218,4 +351,184 @@ mod tests {
         let fetched = fetch_open_tabs(&conn).unwrap();
         assert!(!fetched[0].is_active);
     }
+
+    fn tab_orders(conn: &Connection) -> Vec<(String, i64)> {
+        fetch_open_tabs(conn).unwrap().into_iter().map(|t| (t.id, t.tab_order)).collect()
+    }
+
+    fn persist_five_tabs(conn: &Connection) {
+        let tabs: Vec<PersistedTab> = (0..5)
+            .map(|i| {
+                let doc_id = format!("doc{i}");
+                insert_doc(conn, &doc_id);
+                make_tab(&format!("t{i}"), &doc_id, i, i == 0)
+            })
+            .collect();
+        persist_open_tabs(conn, &tabs).unwrap();
+    }
+
+    fn expected(ids: &[&str]) -> Vec<(String, i64)> {
+        ids.iter().enumerate().map(|(i, id)| (id.to_string(), i as i64)).collect()
+    }
+
+    #[test]
+    fn move_tab_forward_and_back_keeps_orders_contiguous() {
+        let conn = setup_db();
+        persist_five_tabs(&conn);
+
+        move_tab_inner(&conn, "t1", 3).unwrap();
+        assert_eq!(tab_orders(&conn), expected(&["t0", "t2", "t3", "t1", "t4"]));
+
+        move_tab_inner(&conn, "t4", 0).unwrap();
+        assert_eq!(tab_orders(&conn), expected(&["t4", "t0", "t2", "t3", "t1"]));
+
+        // Moving to its own position is a no-op
+        move_tab_inner(&conn, "t2", 2).unwrap();
+        assert_eq!(tab_orders(&conn), expected(&["t4", "t0", "t2", "t3", "t1"]));
+    }
+
+    #[test]
+    fn move_tab_leaves_tabs_outside_the_range_alone() {
+        let conn = setup_db();
+        persist_five_tabs(&conn);
+        // Records every row an UPDATE touches
+        conn.execute_batch(
+            "CREATE TABLE touched (id TEXT);
+             CREATE TRIGGER log_tab_updates AFTER UPDATE ON open_tabs
+             BEGIN INSERT INTO touched VALUES (NEW.id); END;",
+        )
+        .unwrap();
+
+        move_tab_inner(&conn, "t1", 3).unwrap();
+        assert_eq!(tab_orders(&conn), expected(&["t0", "t2", "t3", "t1", "t4"]));
+        let mut touched: Vec<String> = conn
+            .prepare("SELECT id FROM touched")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        touched.sort();
+        assert_eq!(touched, vec!["t1", "t2", "t3"]);
+    }
+
+    #[test]
+    fn move_tab_clamps_and_rejects_unknown_tab() {
+        let conn = setup_db();
+        persist_five_tabs(&conn);
+
+        move_tab_inner(&conn, "t0", 99).unwrap();
+        assert_eq!(tab_orders(&conn), expected(&["t1", "t2", "t3", "t4", "t0"]));
+        move_tab_inner(&conn, "t3", -4).unwrap();
+        assert_eq!(tab_orders(&conn), expected(&["t3", "t1", "t2", "t4", "t0"]));
+
+        // The active flag travels with the tab and nothing else changes
+        let tabs = fetch_open_tabs(&conn).unwrap();
+        assert!(tabs.iter().find(|t| t.id == "t0").unwrap().is_active);
+        assert_eq!(tabs.iter().filter(|t| t.is_active).count(), 1);
+
+        let err = move_tab_inner(&conn, "missing", 0).unwrap_err();
+        assert!(err.contains("Tab not found"), "{err}");
+    }
+
+    #[test]
+    fn move_tab_stays_within_its_pinned_group() {
+        let conn = setup_db();
+        persist_five_tabs(&conn);
+        conn.execute("UPDATE open_tabs SET pinned = 1 WHERE id IN ('t3', 't4')", []).unwrap();
+        // Displayed as t3, t4 (pinned), then t0, t1, t2
+        assert_eq!(tab_orders(&conn), vec![
+            ("t3".to_string(), 3), ("t4".to_string(), 4),
+            ("t0".to_string(), 0), ("t1".to_string(), 1), ("t2".to_string(), 2),
+        ]);
+
+        let orders = |pairs: &[(&str, i64)]| -> Vec<(String, i64)> {
+            pairs.iter().map(|(id, order)| (id.to_string(), *order)).collect()
+        };
+
+        // Positions are display positions; the pinned rows aren't touched
+        move_tab_inner(&conn, "t2", 2).unwrap();
+        assert_eq!(tab_orders(&conn), orders(&[("t3", 3), ("t4", 4), ("t2", 0), ("t0", 1), ("t1", 2)]));
+
+        // An unpinned tab can't be moved in among the pinned ones, or vice versa
+        move_tab_inner(&conn, "t1", 0).unwrap();
+        assert_eq!(tab_orders(&conn), orders(&[("t3", 3), ("t4", 4), ("t1", 0), ("t2", 1), ("t0", 2)]));
+        move_tab_inner(&conn, "t3", 99).unwrap();
+        assert_eq!(tab_orders(&conn), orders(&[("t4", 3), ("t3", 4), ("t1", 0), ("t2", 1), ("t0", 2)]));
+    }
+
+    #[test]
+    fn pinned_tabs_sort_first_and_survive_close_unpinned() {
+        let conn = setup_db();
+        insert_doc(&conn, "doc1");
+        insert_doc(&conn, "doc2");
+        insert_doc(&conn, "doc3");
+
+        let pinned = PersistedTab { pinned: true, ..make_tab("t3", "doc3", 2, false) };
+        persist_open_tabs(&conn, &[
+            make_tab("t1", "doc1", 0, true),
+            make_tab("t2", "doc2", 1, false),
+            pinned,
+        ]).unwrap();
+
+        let fetched = fetch_open_tabs(&conn).unwrap();
+        let ids: Vec<&str> = fetched.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["t3", "t1", "t2"]);
+        assert!(fetched[0].pinned);
+        assert!(!fetched[1].pinned);
+
+        assert_eq!(close_unpinned_tabs_inner(&conn).unwrap(), 2);
+        let remaining = fetch_open_tabs(&conn).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, "t3");
+        assert!(remaining[0].pinned);
+        // Renumbered from 0 and, with the active tab gone, made active
+        assert_eq!(remaining[0].tab_order, 0);
+        assert!(remaining[0].is_active);
+        assert_eq!(fetch_tab_summary(&conn).unwrap().active_document_id.as_deref(), Some("doc3"));
+    }
+
+    #[test]
+    fn close_unpinned_keeps_an_active_pinned_tab() {
+        let conn = setup_db();
+        for i in 0..4 {
+            insert_doc(&conn, &format!("doc{i}"));
+        }
+        persist_open_tabs(&conn, &[
+            PersistedTab { pinned: true, ..make_tab("p1", "doc0", 3, false) },
+            make_tab("t1", "doc1", 0, false),
+            PersistedTab { pinned: true, ..make_tab("p2", "doc2", 5, true) },
+            make_tab("t2", "doc3", 1, false),
+        ]).unwrap();
+
+        assert_eq!(close_unpinned_tabs_inner(&conn).unwrap(), 2);
+        let remaining: Vec<(String, i64, bool)> = fetch_open_tabs(&conn)
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.id, t.tab_order, t.is_active))
+            .collect();
+        assert_eq!(remaining, vec![("p1".to_string(), 0, false), ("p2".to_string(), 1, true)]);
+    }
+
+    #[test]
+    fn tab_summary_counts_tabs_and_finds_active() {
+        let conn = setup_db();
+        insert_doc(&conn, "doc1");
+        insert_doc(&conn, "doc2");
+        insert_doc(&conn, "doc3");
+
+        let empty = fetch_tab_summary(&conn).unwrap();
+        assert_eq!(empty.tab_count, 0);
+        assert!(empty.active_document_id.is_none());
+
+        persist_open_tabs(&conn, &[
+            make_tab("t1", "doc1", 0, false),
+            make_tab("t2", "doc2", 1, true),
+            make_tab("t3", "doc3", 2, false),
+        ]).unwrap();
+
+        let summary = fetch_tab_summary(&conn).unwrap();
+        assert_eq!(summary.tab_count, 3);
+        assert_eq!(summary.active_document_id.as_deref(), Some("doc2"));
+    }
 }
@@ -1,7 +1,9 @@
 use crate::commands::corrections::CorrectionRecord;
 use crate::commands::now_millis;
+use crate::commands::settings::set_setting;
 use crate::db::migrations::DbPool;
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension};
+use uuid::Uuid;
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -51,6 +53,76 @@ const RULES_SELECT: &str =
             reviewed_at, register
      FROM writing_rules";
 
+/// Values accepted by the `writing_rules.severity` CHECK constraint.
+const SEVERITIES: &[&str] = &["must-fix", "should-fix", "nice-to-fix"];
+
+/// Writing types with a label in the exported rules markdown.
+const WRITING_TYPES: &[&str] = &[
+    "general", "email", "prd", "blog", "cover-letter", "resume", "slack", "pitch", "outreach",
+];
+
+/// Minimum severity the guard hook was last exported with. The CLI reads it
+/// whenever it re-exports without `--min-severity` (e.g. after `margin rules add`),
+/// so a narrowed guard doesn't fall back to the default.
+const GUARD_MIN_SEVERITY_KEY: &str = "guard_min_severity";
+
+fn validate_severity(severity: &str) -> Result<(), String> {
+    if SEVERITIES.contains(&severity) {
+        Ok(())
+    } else {
+        Err(format!("Invalid severity '{severity}' (expected one of: {})", SEVERITIES.join(", ")))
+    }
+}
+
+fn validate_writing_type(writing_type: &str) -> Result<(), String> {
+    if WRITING_TYPES.contains(&writing_type) {
+        Ok(())
+    } else {
+        Err(format!("Invalid writing type '{writing_type}' (expected one of: {})", WRITING_TYPES.join(", ")))
+    }
+}
+
+#[derive(serde::Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WritingTypeLabel {
+    pub value: String,
+    pub label: String,
+}
+
+/// Filter values for the rules UI: what the table holds plus the canonical type labels.
+#[derive(serde::Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WritingRuleFacets {
+    pub writing_types: Vec<String>,
+    pub categories: Vec<String>,
+    pub type_labels: Vec<WritingTypeLabel>,
+}
+
+fn fetch_distinct(conn: &Connection, column: &str) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare(&format!("SELECT DISTINCT {column} FROM writing_rules ORDER BY {column}"))?;
+    let rows = stmt.query_map([], |row| row.get(0))?;
+    rows.collect()
+}
+
+fn fetch_rule_facets(conn: &Connection) -> rusqlite::Result<WritingRuleFacets> {
+    Ok(WritingRuleFacets {
+        writing_types: fetch_distinct(conn, "writing_type")?,
+        categories: fetch_distinct(conn, "category")?,
+        type_labels: WRITING_TYPES
+            .iter()
+            .map(|wt| WritingTypeLabel {
+                value: wt.to_string(),
+                label: writing_type_label(wt).to_string(),
+            })
+            .collect(),
+    })
+}
+
+fn fetch_rule(conn: &Connection, id: &str) -> rusqlite::Result<Option<WritingRule>> {
+    conn.query_row(&format!("{RULES_SELECT} WHERE id = ?1"), [id], rule_from_row)
+        .optional()
+}
+
 fn fetch_writing_rules(
     conn: &Connection,
     writing_type: Option<&str>,
@@ -93,7 +165,6 @@ where
     groups
 }
 
-#[cfg(test)]
 fn writing_type_label(wt: &str) -> &str {
     match wt {
         "general" => "General",
@@ -561,15 +632,169 @@ if __name__ == "__main__":
     )
 }
 
+/// VS Code settings that surface must-fix kill words while drafting: Code Spell
+/// Checker flags them and TODO Highlight paints them.
+fn vscode_settings(rules: &[WritingRule]) -> serde_json::Map<String, serde_json::Value> {
+    let kill_words: Vec<&str> = rules
+        .iter()
+        .filter(|r| r.severity == "must-fix" && r.category == "kill-words")
+        .map(|r| r.rule_text.as_str())
+        .collect();
+
+    let keywords: Vec<serde_json::Value> = kill_words
+        .iter()
+        .map(|word| {
+            serde_json::json!({
+                "text": word,
+                "color": "#fff",
+                "backgroundColor": "rgba(220, 38, 38, 0.7)",
+                "overviewRulerColor": "rgba(220, 38, 38, 0.7)",
+            })
+        })
+        .collect();
+
+    let mut settings = serde_json::Map::new();
+    settings.insert("cSpell.flagWords".to_string(), serde_json::json!(kill_words));
+    settings.insert("todohighlight.isCaseSensitive".to_string(), serde_json::json!(false));
+    settings.insert("todohighlight.keywords".to_string(), serde_json::json!(keywords));
+    settings
+}
+
+#[cfg(test)]
+fn generate_vscode_settings_json(rules: &[WritingRule]) -> String {
+    serde_json::to_string_pretty(&vscode_settings(rules)).unwrap_or_else(|_| "{}".to_string())
+}
+
+/// Merges the kill-word keys into `.vscode/settings.json`, keeping every other
+/// setting. A file that isn't a plain JSON object (for example one with
+/// comments) is left alone and reported rather than overwritten.
+fn write_vscode_settings(rules: &[WritingRule], path: &std::path::Path) -> Result<(), String> {
+    let mut settings = match std::fs::read_to_string(path) {
+        Ok(existing) if existing.trim().is_empty() => serde_json::Map::new(),
+        Ok(existing) => match serde_json::from_str::<serde_json::Value>(&existing) {
+            Ok(serde_json::Value::Object(map)) => map,
+            _ => return Err(format!("{} is not a plain JSON object; leaving it unchanged", path.display())),
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => serde_json::Map::new(),
+        Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+    };
+    settings.extend(vscode_settings(rules));
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {e}"))?;
+    }
+    let json = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
 
 #[derive(serde::Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExportResult {
-    pub markdown_path: String,
-    pub hook_path: String,
+    pub markdown_path: Option<String>,
+    pub hook_path: Option<String>,
+    pub vscode_path: Option<String>,
     pub rule_count: usize,
 }
 
+/// Which profile files an export writes; both unless the caller narrows it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ExportTargets {
+    markdown: bool,
+    hook: bool,
+}
+
+impl ExportTargets {
+    const ALL: ExportTargets = ExportTargets { markdown: true, hook: true };
+
+    fn parse(targets: Option<&[String]>) -> Result<Self, String> {
+        let Some(names) = targets.filter(|t| !t.is_empty()) else {
+            return Ok(Self::ALL);
+        };
+        let mut selected = ExportTargets { markdown: false, hook: false };
+        for name in names {
+            match name.as_str() {
+                "markdown" => selected.markdown = true,
+                "hook" => selected.hook = true,
+                other => return Err(format!("Unknown export target '{other}' (expected markdown or hook)")),
+            }
+        }
+        Ok(selected)
+    }
+
+    /// Value for `margin export profile --targets`.
+    fn cli_arg(&self) -> String {
+        [(self.markdown, "markdown"), (self.hook, "hook")]
+            .iter()
+            .filter(|(on, _)| *on)
+            .map(|(_, name)| *name)
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// A rule authored in Margin; the id, timestamps, and signal count are filled in on insert.
+#[derive(serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NewWritingRule {
+    pub writing_type: String,
+    pub category: String,
+    pub rule_text: String,
+    pub when_to_apply: Option<String>,
+    pub why: Option<String>,
+    pub severity: Option<String>,
+    pub example_before: Option<String>,
+    pub example_after: Option<String>,
+    pub source: Option<String>,
+    pub notes: Option<String>,
+    pub register: Option<String>,
+}
+
+fn create_rule(conn: &Connection, rule: &NewWritingRule) -> Result<WritingRule, String> {
+    validate_writing_type(&rule.writing_type)?;
+    let severity = rule.severity.as_deref().unwrap_or("should-fix");
+    validate_severity(severity)?;
+    if rule.rule_text.trim().is_empty() {
+        return Err("Rule text cannot be empty".to_string());
+    }
+    if let Some(error) = pattern_error(&rule.category, rule.example_before.as_deref()) {
+        return Err(format!("Invalid pattern: {error}"));
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let now = now_millis();
+    conn.execute(
+        "INSERT INTO writing_rules
+            (id, writing_type, category, rule_text, when_to_apply, why, severity,
+             example_before, example_after, source, signal_count, notes, created_at, updated_at, register)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, 1, ?11, ?12, ?12, ?13)",
+        rusqlite::params![
+            id,
+            rule.writing_type,
+            rule.category,
+            rule.rule_text,
+            rule.when_to_apply,
+            rule.why,
+            severity,
+            rule.example_before,
+            rule.example_after,
+            rule.source.as_deref().unwrap_or("manual"),
+            rule.notes,
+            now,
+            rule.register,
+        ],
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::SqliteFailure(err, _) if err.code == rusqlite::ErrorCode::ConstraintViolation => {
+            "A rule with this text already exists for this writing type and category".to_string()
+        }
+        other => other.to_string(),
+    })?;
+
+    fetch_rule(conn, &id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Rule not found".to_string())
+}
+
 fn update_rule(
     conn: &Connection,
     id: &str,
@@ -638,6 +863,18 @@ fn update_rule(
     Ok(())
 }
 
+/// Records one more correction backing the rule, which moves it up in `fetch_writing_rules`.
+pub(crate) fn increment_signal(conn: &Connection, id: &str) -> rusqlite::Result<()> {
+    let rows = conn.execute(
+        "UPDATE writing_rules SET signal_count = signal_count + 1, updated_at = ?1 WHERE id = ?2",
+        rusqlite::params![now_millis(), id],
+    )?;
+    if rows == 0 {
+        return Err(rusqlite::Error::QueryReturnedNoRows);
+    }
+    Ok(())
+}
+
 fn delete_rule(conn: &Connection, id: &str) -> rusqlite::Result<()> {
     let rows = conn.execute("DELETE FROM writing_rules WHERE id = ?1", [id])?;
     if rows == 0 {
@@ -646,6 +883,63 @@ fn delete_rule(conn: &Connection, id: &str) -> rusqlite::Result<()> {
     Ok(())
 }
 
+#[derive(serde::Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InvalidRulePattern {
+    pub rule_id: String,
+    pub pattern: String,
+    pub error: String,
+}
+
+/// Categories whose `example_before` the guard hook compiles as a regex.
+const REGEX_PATTERN_CATEGORIES: &[&str] = &["ai-slop", "heading-patterns"];
+
+/// Why the guard hook couldn't compile a rule's `example_before`, or `None`
+/// when it compiles or the category isn't matched as a regex. The hook runs
+/// Python's `re`; lookaround and backreferences, which Rust's `regex` lacks,
+/// are checked as plain groups instead of being reported.
+fn pattern_error(category: &str, example_before: Option<&str>) -> Option<String> {
+    if !REGEX_PATTERN_CATEGORIES.contains(&category) {
+        return None;
+    }
+    // Escapes are matched whole so `\(?=` stays a literal paren
+    let python_only = regex::Regex::new(r"(\\[1-9])|(\\.)|\(\?<?[=!]").expect("valid python syntax regex");
+    let pattern = python_only.replace_all(example_before?, |caps: &regex::Captures| {
+        if caps.get(1).is_some() {
+            "(?:)".to_string()
+        } else if let Some(escape) = caps.get(2) {
+            escape.as_str().to_string()
+        } else {
+            "(?:".to_string()
+        }
+    });
+    regex::Regex::new(&pattern).err().map(|e| e.to_string())
+}
+
+/// Compiles every guard-hook regex pattern and returns the ones that fail.
+fn find_invalid_patterns(rules: &[WritingRule]) -> Vec<InvalidRulePattern> {
+    rules
+        .iter()
+        .filter_map(|r| {
+            let error = pattern_error(&r.category, r.example_before.as_deref())?;
+            Some(InvalidRulePattern {
+                rule_id: r.id.clone(),
+                pattern: r.example_before.clone().unwrap_or_default(),
+                error,
+            })
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub async fn create_writing_rule(
+    state: tauri::State<'_, DbPool>,
+    rule: NewWritingRule,
+) -> Result<WritingRule, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    create_rule(&conn, &rule)
+}
+
 #[tauri::command]
 pub async fn update_writing_rule(
     state: tauri::State<'_, DbPool>,
@@ -657,7 +951,10 @@ pub async fn update_writing_rule(
     example_before: Option<String>,
     example_after: Option<String>,
     notes: Option<String>,
-) -> Result<(), String> {
+) -> Result<WritingRule, String> {
+    if let Some(severity) = severity.as_deref() {
+        validate_severity(severity)?;
+    }
     let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
     update_rule(
         &conn,
@@ -670,7 +967,22 @@ pub async fn update_writing_rule(
         example_after.as_deref(),
         notes.as_deref(),
     )
-    .map_err(|e| e.to_string())
+    .map_err(|e| e.to_string())?;
+    fetch_rule(&conn, &id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Rule not found".to_string())
+}
+
+#[tauri::command]
+pub async fn increment_writing_rule_signal(
+    state: tauri::State<'_, DbPool>,
+    id: String,
+) -> Result<WritingRule, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    increment_signal(&conn, &id).map_err(|e| e.to_string())?;
+    fetch_rule(&conn, &id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Rule not found".to_string())
 }
 
 #[tauri::command]
@@ -682,6 +994,26 @@ pub async fn delete_writing_rule(
     delete_rule(&conn, &id).map_err(|e| e.to_string())
 }
 
+/// Validates a single rule being authored, or every stored rule when `rule` is omitted.
+#[tauri::command]
+pub async fn validate_writing_rule(
+    state: tauri::State<'_, DbPool>,
+    rule: Option<WritingRule>,
+) -> Result<Vec<InvalidRulePattern>, String> {
+    if let Some(rule) = rule {
+        return Ok(find_invalid_patterns(std::slice::from_ref(&rule)));
+    }
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    let rules = fetch_writing_rules(&conn, None).map_err(|e| e.to_string())?;
+    Ok(find_invalid_patterns(&rules))
+}
+
+#[tauri::command]
+pub async fn get_writing_rule_facets(state: tauri::State<'_, DbPool>) -> Result<WritingRuleFacets, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    fetch_rule_facets(&conn).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn get_writing_rules(
     state: tauri::State<'_, DbPool>,
@@ -692,15 +1024,37 @@ pub async fn get_writing_rules(
 }
 
 /// Delegate file generation to the `margin` CLI (single-writer pattern).
-/// The CLI reads from SQLite and writes both ~/.margin/writing-rules.md
-/// and ~/.claude/hooks/writing_guard.py.
-fn run_cli_export() -> Result<(String, String), String> {
+/// The CLI reads from SQLite and writes ~/.margin/writing-rules.md and/or
+/// ~/.claude/hooks/writing_guard.py; only the paths it was asked for come back.
+/// `min_severity` narrows the hook's rules and makes must-fix violations deny;
+/// without it the CLI falls back to the stored `GUARD_MIN_SEVERITY_KEY`.
+fn run_cli_export(
+    targets: ExportTargets,
+    min_severity: Option<&str>,
+) -> Result<(Option<String>, Option<String>), String> {
     let home = dirs::home_dir().ok_or("Could not determine home directory")?;
+    run_cli_export_with(std::path::Path::new("margin"), &home, targets, min_severity)
+}
+
+/// `run_cli_export` with the CLI and home directory given, so tests can stand
+/// in a fake CLI. `HOME` is passed through so the CLI writes where the
+/// returned paths point.
+fn run_cli_export_with(
+    program: &std::path::Path,
+    home: &std::path::Path,
+    targets: ExportTargets,
+    min_severity: Option<&str>,
+) -> Result<(Option<String>, Option<String>), String> {
     let md_path = home.join(".margin").join("writing-rules.md");
     let hook_path = home.join(".claude").join("hooks").join("writing_guard.py");
 
-    let output = std::process::Command::new("margin")
-        .args(["export", "profile"])
+    let mut command = std::process::Command::new(program);
+    command.env("HOME", home);
+    command.args(["export", "profile", "--targets", &targets.cli_arg()]);
+    if let Some(severity) = min_severity {
+        command.args(["--min-severity", severity]);
+    }
+    let output = command
         .output()
         .map_err(|e| format!("Failed to run `margin export profile`: {e}"))?;
 
@@ -710,14 +1064,32 @@ fn run_cli_export() -> Result<(String, String), String> {
     }
 
     Ok((
-        md_path.to_string_lossy().to_string(),
-        hook_path.to_string_lossy().to_string(),
+        targets.markdown.then(|| md_path.to_string_lossy().to_string()),
+        targets.hook.then(|| hook_path.to_string_lossy().to_string()),
     ))
 }
 
+/// Stores the guard's minimum severity for later CLI re-exports. `None` keeps
+/// the stored value; an empty string goes back to the default guard.
+fn remember_min_severity(conn: &Connection, min_severity: Option<&str>) -> Result<(), String> {
+    match min_severity {
+        None => Ok(()),
+        Some("") => set_setting(conn, GUARD_MIN_SEVERITY_KEY, ""),
+        Some(severity) => {
+            validate_severity(severity)?;
+            set_setting(conn, GUARD_MIN_SEVERITY_KEY, severity)
+        }
+    }
+}
+
 /// Shared inner logic: delegate file writing to CLI, read rules + corrections for return values.
-fn do_export(state: &DbPool) -> Result<(Vec<WritingRule>, Vec<CorrectionRecord>, String, String), String> {
-    let (markdown_path, hook_path) = run_cli_export()?;
+#[allow(clippy::type_complexity)]
+fn do_export(
+    state: &DbPool,
+    targets: ExportTargets,
+    min_severity: Option<&str>,
+) -> Result<(Vec<WritingRule>, Vec<CorrectionRecord>, Option<String>, Option<String>), String> {
+    let (markdown_path, hook_path) = run_cli_export(targets, min_severity)?;
 
     let (rules, corrections) = {
         let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
@@ -732,19 +1104,32 @@ fn do_export(state: &DbPool) -> Result<(Vec<WritingRule>, Vec<CorrectionRecord>,
 #[tauri::command]
 pub async fn export_writing_rules(
     state: tauri::State<'_, DbPool>,
+    targets: Option<Vec<String>>,
+    min_severity: Option<String>,
+    vscode_path: Option<String>,
 ) -> Result<ExportResult, String> {
-    let (rules, _, markdown_path, hook_path) = do_export(&state)?;
+    let targets = ExportTargets::parse(targets.as_deref())?;
+    {
+        let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+        remember_min_severity(&conn, min_severity.as_deref())?;
+    }
+    let min_severity = min_severity.as_deref().filter(|s| !s.is_empty());
+    let (rules, _, markdown_path, hook_path) = do_export(&state, targets, min_severity)?;
+    if let Some(path) = vscode_path.as_deref() {
+        write_vscode_settings(&rules, std::path::Path::new(path))?;
+    }
 
     Ok(ExportResult {
         markdown_path,
         hook_path,
+        vscode_path,
         rule_count: rules.len(),
     })
 }
 
 fn fetch_all_corrections_for_profile(conn: &Connection) -> rusqlite::Result<Vec<CorrectionRecord>> {
     let mut stmt = conn.prepare(
-        "SELECT original_text, notes_json, highlight_color, document_title, document_id, created_at, writing_type, polarity
+        "SELECT original_text, notes_json, highlight_color, document_title, document_id, created_at, writing_type, polarity, category
          FROM corrections
          WHERE session_id != '__backfilled__'
          ORDER BY created_at DESC",
@@ -761,6 +1146,7 @@ fn fetch_all_corrections_for_profile(conn: &Connection) -> rusqlite::Result<Vec<
             created_at: row.get(5)?,
             writing_type: row.get(6)?,
             polarity: row.get(7)?,
+            category: row.get(8)?,
         })
     })?;
 
@@ -860,14 +1246,15 @@ pub async fn mark_rules_unreviewed(
 pub async fn export_voice_profile(
     state: tauri::State<'_, DbPool>,
 ) -> Result<VoiceProfileExportResult, String> {
-    let (rules, corrections, markdown_path, _) = do_export(&state)?;
+    let targets = ExportTargets { markdown: true, hook: false };
+    let (rules, corrections, markdown_path, _) = do_export(&state, targets, None)?;
 
     let positive_count = corrections.iter().filter(|c| c.polarity.as_deref() == Some("positive")).count();
     let corrective_count = corrections.iter().filter(|c| c.polarity.as_deref() == Some("corrective")).count();
     let unclassified_count = corrections.len() - positive_count - corrective_count;
 
     Ok(VoiceProfileExportResult {
-        path: markdown_path,
+        path: markdown_path.unwrap_or_default(),
         positive_count,
         corrective_count,
         unclassified_count,
@@ -875,10 +1262,187 @@ pub async fn export_voice_profile(
     })
 }
 
+/// Writes every rule as a pretty JSON array (the format `import_writing_rules` reads).
+fn export_rules_json(conn: &Connection, path: &std::path::Path) -> Result<usize, String> {
+    let rules = fetch_writing_rules(conn, None).map_err(|e| e.to_string())?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {e}"))?;
+    }
+    let json = serde_json::to_string_pretty(&rules).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    Ok(rules.len())
+}
+
+#[tauri::command]
+pub async fn export_writing_rules_json(state: tauri::State<'_, DbPool>, path: String) -> Result<usize, String> {
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    export_rules_json(&conn, std::path::Path::new(&path))
+}
+
+/// A `WritingRule` as found in an import file; only the rule's content is required.
+#[derive(serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct ImportedWritingRule {
+    id: Option<String>,
+    writing_type: String,
+    category: String,
+    rule_text: String,
+    when_to_apply: Option<String>,
+    why: Option<String>,
+    severity: Option<String>,
+    example_before: Option<String>,
+    example_after: Option<String>,
+    source: Option<String>,
+    signal_count: Option<i64>,
+    notes: Option<String>,
+    created_at: Option<i64>,
+    reviewed_at: Option<i64>,
+    register: Option<String>,
+}
+
+#[derive(serde::Serialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleImportResult {
+    pub inserted: usize,
+    pub updated: usize,
+    pub skipped: usize,
+}
+
+/// Existing rule an imported one should overwrite: same id, else the same
+/// writing type, category and text (the table's UNIQUE key).
+fn find_import_target(conn: &Connection, rule: &ImportedWritingRule) -> rusqlite::Result<Option<String>> {
+    if let Some(id) = rule.id.as_deref() {
+        let found: Option<String> = conn
+            .query_row("SELECT id FROM writing_rules WHERE id = ?1", [id], |row| row.get(0))
+            .optional()?;
+        if found.is_some() {
+            return Ok(found);
+        }
+    }
+    conn.query_row(
+        "SELECT id FROM writing_rules WHERE writing_type = ?1 AND category = ?2 AND rule_text = ?3",
+        [&rule.writing_type, &rule.category, &rule.rule_text],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+fn upsert_imported_rule(conn: &Connection, rule: &ImportedWritingRule, now: i64) -> Result<bool, String> {
+    validate_writing_type(&rule.writing_type)?;
+    let severity = rule.severity.as_deref().unwrap_or("should-fix");
+    validate_severity(severity)?;
+    if rule.rule_text.trim().is_empty() {
+        return Err("Rule text cannot be empty".to_string());
+    }
+
+    match find_import_target(conn, rule).map_err(|e| e.to_string())? {
+        Some(id) => {
+            conn.execute(
+                "UPDATE writing_rules SET
+                    category = ?1, rule_text = ?2, when_to_apply = ?3, why = ?4, severity = ?5,
+                    example_before = ?6, example_after = ?7, notes = ?8, register = ?9,
+                    signal_count = COALESCE(?10, signal_count),
+                    reviewed_at = COALESCE(?11, reviewed_at),
+                    updated_at = ?12,
+                    writing_type = ?14,
+                    source = COALESCE(?15, source),
+                    created_at = COALESCE(?16, created_at)
+                 WHERE id = ?13",
+                rusqlite::params![
+                    rule.category,
+                    rule.rule_text,
+                    rule.when_to_apply,
+                    rule.why,
+                    severity,
+                    rule.example_before,
+                    rule.example_after,
+                    rule.notes,
+                    rule.register,
+                    rule.signal_count,
+                    rule.reviewed_at,
+                    now,
+                    id,
+                    rule.writing_type,
+                    rule.source,
+                    rule.created_at,
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(false)
+        }
+        None => {
+            let id = rule.id.clone().unwrap_or_else(|| Uuid::new_v4().to_string());
+            conn.execute(
+                "INSERT INTO writing_rules
+                    (id, writing_type, category, rule_text, when_to_apply, why, severity,
+                     example_before, example_after, source, signal_count, notes,
+                     created_at, updated_at, reviewed_at, register)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+                rusqlite::params![
+                    id,
+                    rule.writing_type,
+                    rule.category,
+                    rule.rule_text,
+                    rule.when_to_apply,
+                    rule.why,
+                    severity,
+                    rule.example_before,
+                    rule.example_after,
+                    rule.source.as_deref().unwrap_or("import"),
+                    rule.signal_count.unwrap_or(1),
+                    rule.notes,
+                    rule.created_at.unwrap_or(now),
+                    now,
+                    rule.reviewed_at,
+                    rule.register,
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(true)
+        }
+    }
+}
+
+/// Upserts every well-formed rule in a JSON array; malformed entries are logged and skipped.
+fn import_rules_json(conn: &Connection, json: &str) -> Result<RuleImportResult, String> {
+    let entries: Vec<serde_json::Value> =
+        serde_json::from_str(json).map_err(|e| format!("Expected a JSON array of rules: {e}"))?;
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let now = now_millis();
+    let mut result = RuleImportResult::default();
+    for (i, entry) in entries.into_iter().enumerate() {
+        let outcome = serde_json::from_value::<ImportedWritingRule>(entry)
+            .map_err(|e| e.to_string())
+            .and_then(|rule| upsert_imported_rule(&tx, &rule, now));
+        match outcome {
+            Ok(true) => result.inserted += 1,
+            Ok(false) => result.updated += 1,
+            Err(e) => {
+                eprintln!("import_writing_rules: skipping entry {i}: {e}");
+                result.skipped += 1;
+            }
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn import_writing_rules(
+    state: tauri::State<'_, DbPool>,
+    path: String,
+) -> Result<RuleImportResult, String> {
+    let json = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {path}: {e}"))?;
+    let conn = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    import_rules_json(&conn, &json)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::db::migrations::migrate_add_writing_rules_table;
+    use crate::commands::settings::get_setting;
+    use crate::db::migrations::{migrate_add_settings_table, migrate_add_writing_rules_table};
     use rusqlite::Connection;
 
     fn setup_db() -> Connection {
@@ -917,6 +1481,7 @@ mod tests {
                 original_text TEXT NOT NULL,
                 notes_json TEXT NOT NULL,
                 document_title TEXT,
+                category TEXT,
                 highlight_color TEXT NOT NULL,
                 created_at INTEGER NOT NULL,
                 writing_type TEXT,
@@ -1206,6 +1771,237 @@ mod tests {
         assert!(new_ts > old_ts);
     }
 
+    // --- create_rule tests ---
+
+    fn new_rule(writing_type: &str, rule_text: &str, severity: Option<&str>) -> NewWritingRule {
+        NewWritingRule {
+            writing_type: writing_type.to_string(),
+            category: "tone".to_string(),
+            rule_text: rule_text.to_string(),
+            when_to_apply: None,
+            why: Some("Clarity".to_string()),
+            severity: severity.map(str::to_string),
+            example_before: None,
+            example_after: None,
+            source: None,
+            notes: None,
+            register: None,
+        }
+    }
+
+    #[test]
+    fn create_rule_fills_id_timestamps_and_defaults() {
+        let conn = setup_db();
+        let rule = create_rule(&conn, &new_rule("email", "Be brief", None)).unwrap();
+
+        assert!(!rule.id.is_empty());
+        assert_eq!(rule.severity, "should-fix");
+        assert_eq!(rule.source, "manual");
+        assert_eq!(rule.signal_count, 1);
+        assert_eq!(rule.why.as_deref(), Some("Clarity"));
+        assert!(rule.created_at > 0);
+        assert_eq!(rule.created_at, rule.updated_at);
+
+        let stored = fetch_writing_rules(&conn, Some("email")).unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].id, rule.id);
+    }
+
+    #[test]
+    fn create_rule_rejects_unknown_severity_and_writing_type() {
+        let conn = setup_db();
+        let err = create_rule(&conn, &new_rule("email", "Be brief", Some("critical"))).unwrap_err();
+        assert!(err.contains("Invalid severity"));
+        let err = create_rule(&conn, &new_rule("tweet", "Be brief", None)).unwrap_err();
+        assert!(err.contains("Invalid writing type"));
+        assert!(fetch_writing_rules(&conn, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn create_rule_reports_duplicates() {
+        let conn = setup_db();
+        create_rule(&conn, &new_rule("email", "Be brief", None)).unwrap();
+        let err = create_rule(&conn, &new_rule("email", "Be brief", None)).unwrap_err();
+        assert!(err.contains("already exists"));
+    }
+
+    #[test]
+    fn fetch_rule_returns_updated_row() {
+        let conn = setup_db();
+        insert_rule(&conn, "r1", "general", "tone", "Be direct", "should-fix");
+        update_rule(&conn, "r1", None, Some("must-fix"), None, None, None, None, None).unwrap();
+
+        let rule = fetch_rule(&conn, "r1").unwrap().unwrap();
+        assert_eq!(rule.severity, "must-fix");
+        assert!(fetch_rule(&conn, "missing").unwrap().is_none());
+        assert!(validate_severity("urgent").is_err());
+    }
+
+    // --- increment_signal tests ---
+
+    #[test]
+    fn increment_signal_bumps_count_and_reorders() {
+        let conn = setup_db();
+        insert_rule(&conn, "r1", "general", "tone", "Be direct", "should-fix");
+        insert_rule(&conn, "r2", "general", "tone", "Be brief", "should-fix");
+        conn.execute("UPDATE writing_rules SET signal_count = 2 WHERE id = 'r1'", []).unwrap();
+        assert_eq!(fetch_writing_rules(&conn, Some("general")).unwrap()[0].id, "r1");
+
+        increment_signal(&conn, "r2").unwrap();
+        increment_signal(&conn, "r2").unwrap();
+
+        let rules = fetch_writing_rules(&conn, Some("general")).unwrap();
+        assert_eq!(rules[0].id, "r2");
+        assert_eq!(rules[0].signal_count, 3);
+        assert!(rules[0].updated_at > 1000);
+        assert!(increment_signal(&conn, "missing").is_err());
+    }
+
+    // --- import_rules_json tests ---
+
+    #[test]
+    fn import_rules_upserts_by_id_then_by_text() {
+        let conn = setup_db();
+        insert_rule(&conn, "r1", "general", "tone", "Be direct", "should-fix");
+        insert_rule(&conn, "r2", "email", "tone", "Be brief", "should-fix");
+
+        let json = r#"[
+            {"id": "r1", "writingType": "general", "category": "tone", "ruleText": "Be very direct", "severity": "must-fix", "signalCount": 7},
+            {"writingType": "email", "category": "tone", "ruleText": "Be brief", "why": "Busy readers"},
+            {"id": "r9", "writingType": "slack", "category": "tone", "ruleText": "Skip greetings"},
+            {"writingType": "email", "category": "tone", "ruleText": "Sign off", "severity": "urgent"},
+            {"writingType": "email", "ruleText": "No category"},
+            "not a rule"
+        ]"#;
+        let result = import_rules_json(&conn, json).unwrap();
+        assert_eq!(result, RuleImportResult { inserted: 1, updated: 2, skipped: 3 });
+
+        let r1 = fetch_rule(&conn, "r1").unwrap().unwrap();
+        assert_eq!(r1.rule_text, "Be very direct");
+        assert_eq!(r1.severity, "must-fix");
+        assert_eq!(r1.signal_count, 7);
+        let r2 = fetch_rule(&conn, "r2").unwrap().unwrap();
+        assert_eq!(r2.why.as_deref(), Some("Busy readers"));
+        assert_eq!(r2.signal_count, 1);
+        let r9 = fetch_rule(&conn, "r9").unwrap().unwrap();
+        assert_eq!(r9.source, "import");
+        assert_eq!(fetch_writing_rules(&conn, None).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn exported_rules_json_round_trips_through_import() {
+        let conn = setup_db();
+        insert_rule(&conn, "r1", "general", "tone", "Be direct", "must-fix");
+        insert_rule(&conn, "r2", "email", "tone", "Be brief", "should-fix");
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("rules.json");
+        assert_eq!(export_rules_json(&conn, &path).unwrap(), 2);
+
+        let json = std::fs::read_to_string(&path).unwrap();
+        let parsed: Vec<WritingRule> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.len(), 2);
+
+        let other = setup_db();
+        let result = import_rules_json(&other, &json).unwrap();
+        assert_eq!(result, RuleImportResult { inserted: 2, updated: 0, skipped: 0 });
+        assert_eq!(fetch_rule(&other, "r1").unwrap().unwrap().severity, "must-fix");
+    }
+
+    #[test]
+    fn import_rules_update_writing_type_and_match_on_category() {
+        let conn = setup_db();
+        insert_rule(&conn, "r1", "general", "tone", "Be direct", "should-fix");
+        insert_rule(&conn, "r2", "general", "style", "Be direct", "should-fix");
+        insert_rule(&conn, "r3", "general", "tone", "Be brief", "should-fix");
+
+        let json = r#"[
+            {"id": "r3", "writingType": "email", "category": "tone", "ruleText": "Be brief"},
+            {"writingType": "general", "category": "style", "ruleText": "Be direct", "why": "Clarity"}
+        ]"#;
+        let result = import_rules_json(&conn, json).unwrap();
+        assert_eq!(result, RuleImportResult { inserted: 0, updated: 2, skipped: 0 });
+
+        assert_eq!(fetch_rule(&conn, "r3").unwrap().unwrap().writing_type, "email");
+        // The text matched r2 through its category, not r1
+        assert_eq!(fetch_rule(&conn, "r2").unwrap().unwrap().why.as_deref(), Some("Clarity"));
+        let r1 = fetch_rule(&conn, "r1").unwrap().unwrap();
+        assert_eq!(r1.category, "tone");
+        assert_eq!(r1.why, None);
+    }
+
+    #[test]
+    fn import_rules_rejects_non_array() {
+        let conn = setup_db();
+        assert!(import_rules_json(&conn, r#"{"rules": []}"#).is_err());
+    }
+
+    // --- generate_vscode_settings_json tests ---
+
+    #[test]
+    fn vscode_settings_list_must_fix_kill_words() {
+        let conn = setup_db();
+        insert_rule(&conn, "r1", "general", "kill-words", "leverage", "must-fix");
+        insert_rule(&conn, "r2", "general", "kill-words", "utilize", "should-fix");
+        insert_rule(&conn, "r3", "general", "tone", "Be direct", "must-fix");
+        let rules = fetch_writing_rules(&conn, None).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".vscode").join("settings.json");
+        write_vscode_settings(&rules, &path).unwrap();
+
+        let settings: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(settings["cSpell.flagWords"], serde_json::json!(["leverage"]));
+        let keywords = settings["todohighlight.keywords"].as_array().unwrap();
+        assert_eq!(keywords.len(), 1);
+        assert_eq!(keywords[0]["text"], "leverage");
+    }
+
+    #[test]
+    fn vscode_settings_merge_into_existing_file() {
+        let conn = setup_db();
+        insert_rule(&conn, "r1", "general", "kill-words", "leverage", "must-fix");
+        let rules = fetch_writing_rules(&conn, None).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        std::fs::write(&path, r#"{"editor.fontSize": 15, "cSpell.flagWords": ["stale"]}"#).unwrap();
+        write_vscode_settings(&rules, &path).unwrap();
+
+        let settings: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(settings["editor.fontSize"], 15);
+        assert_eq!(settings["cSpell.flagWords"], serde_json::json!(["leverage"]));
+
+        // JSON with comments can't be merged safely, so it is not touched
+        let commented = "{\n  // mine\n  \"editor.tabSize\": 2\n}";
+        std::fs::write(&path, commented).unwrap();
+        assert!(write_vscode_settings(&rules, &path).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), commented);
+    }
+
+    #[test]
+    fn vscode_settings_empty_rules_is_valid_json() {
+        let settings: serde_json::Value = serde_json::from_str(&generate_vscode_settings_json(&[])).unwrap();
+        assert_eq!(settings["cSpell.flagWords"], serde_json::json!([]));
+    }
+
+    // --- fetch_rule_facets tests ---
+
+    #[test]
+    fn rule_facets_list_distinct_values_and_labels() {
+        let conn = setup_db();
+        insert_rule(&conn, "r1", "general", "tone", "Be direct", "should-fix");
+        insert_rule(&conn, "r2", "email", "tone", "Be brief", "should-fix");
+        insert_rule(&conn, "r3", "email", "kill-words", "leverage", "must-fix");
+
+        let facets = fetch_rule_facets(&conn).unwrap();
+        assert_eq!(facets.writing_types, vec!["email", "general"]);
+        assert_eq!(facets.categories, vec!["kill-words", "tone"]);
+        assert_eq!(facets.type_labels.len(), WRITING_TYPES.len());
+        let blog = facets.type_labels.iter().find(|l| l.value == "blog").unwrap();
+        assert_eq!(blog.label, "Blog / essay");
+    }
+
     // --- delete_rule tests ---
 
     #[test]
@@ -1314,6 +2110,7 @@ mod tests {
                 created_at: 1000,
                 writing_type: None,
                 polarity: Some("positive".to_string()),
+                category: None,
             },
             CorrectionRecord {
                 original_text: "Bad pattern here.".to_string(),
@@ -1324,6 +2121,7 @@ mod tests {
                 created_at: 2000,
                 writing_type: None,
                 polarity: Some("corrective".to_string()),
+                category: None,
             },
         ];
 
@@ -1351,6 +2149,7 @@ mod tests {
                 created_at: 1000,
                 writing_type: None,
                 polarity: Some("positive".to_string()),
+                category: None,
             },
             CorrectionRecord {
                 original_text: "Corrective text.".to_string(),
@@ -1361,6 +2160,7 @@ mod tests {
                 created_at: 2000,
                 writing_type: None,
                 polarity: Some("corrective".to_string()),
+                category: None,
             },
         ];
 
@@ -1382,6 +2182,7 @@ mod tests {
             created_at: 1000,
             writing_type: None,
             polarity: Some("positive".to_string()),
+            category: None,
         }];
 
         // Should not panic — truncation must respect char boundaries
@@ -1567,4 +2368,159 @@ mod tests {
         assert!(md.contains("## An uncomfortable timeline"));
     }
 
+    // --- find_invalid_patterns tests ---
+
+    #[test]
+    fn valid_slop_and_heading_patterns_pass() {
+        let conn = setup_db();
+        insert_full_rule(
+            &conn, "r1", "general", "ai-slop", "No negative parallelism", "must-fix",
+            None, None, Some(r"[Nn]ot (just|only) .+, but"), None, 1,
+        );
+        insert_full_rule(
+            &conn, "r2", "general", "heading-patterns", "No headings starting with The", "must-fix",
+            None, None, Some(r"(?:^|:\s*)[Tt]he\b"), None, 1,
+        );
+
+        let rules = fetch_writing_rules(&conn, None).unwrap();
+        assert!(find_invalid_patterns(&rules).is_empty());
+    }
+
+    #[test]
+    fn invalid_slop_pattern_is_reported() {
+        let conn = setup_db();
+        insert_full_rule(
+            &conn, "r1", "general", "ai-slop", "Broken pattern", "must-fix",
+            None, None, Some("(unclosed"), None, 1,
+        );
+
+        let rules = fetch_writing_rules(&conn, None).unwrap();
+        let invalid = find_invalid_patterns(&rules);
+        assert_eq!(invalid.len(), 1);
+        assert_eq!(invalid[0].rule_id, "r1");
+        assert_eq!(invalid[0].pattern, "(unclosed");
+        assert!(!invalid[0].error.is_empty());
+    }
+
+    #[test]
+    fn python_lookaround_and_backreferences_are_not_flagged() {
+        let conn = setup_db();
+        insert_full_rule(
+            &conn, "r1", "general", "ai-slop", "No 'delve' unless quoted", "must-fix",
+            None, None, Some(r#"(?<!")\bdelve\b(?!")"#), None, 1,
+        );
+        insert_full_rule(
+            &conn, "r2", "general", "ai-slop", "No doubled words", "must-fix",
+            None, None, Some(r"\b(\w+) \1\b(?=\s)"), None, 1,
+        );
+        // Lookaround doesn't hide other syntax errors, and escapes stay literal
+        insert_full_rule(
+            &conn, "r3", "general", "ai-slop", "Broken after lookahead", "must-fix",
+            None, None, Some(r"(?=x)(unclosed"), None, 1,
+        );
+        insert_full_rule(
+            &conn, "r4", "general", "ai-slop", "Escaped paren", "must-fix",
+            None, None, Some(r"\(?=\)"), None, 1,
+        );
+
+        let rules = fetch_writing_rules(&conn, None).unwrap();
+        let invalid: Vec<String> = find_invalid_patterns(&rules).into_iter().map(|p| p.rule_id).collect();
+        assert_eq!(invalid, vec!["r3"]);
+    }
+
+    #[test]
+    fn create_rule_rejects_invalid_patterns() {
+        let conn = setup_db();
+        let broken = NewWritingRule {
+            category: "ai-slop".to_string(),
+            example_before: Some("(unclosed".to_string()),
+            ..new_rule("general", "Broken", None)
+        };
+        let err = create_rule(&conn, &broken).unwrap_err();
+        assert!(err.starts_with("Invalid pattern"), "{err}");
+        assert!(fetch_writing_rules(&conn, None).unwrap().is_empty());
+
+        let lookbehind = NewWritingRule {
+            category: "ai-slop".to_string(),
+            example_before: Some(r"(?<!not )\bdelve\b".to_string()),
+            ..new_rule("general", "No delve", None)
+        };
+        create_rule(&conn, &lookbehind).unwrap();
+    }
+
+    #[test]
+    fn non_regex_categories_are_not_compiled() {
+        let conn = setup_db();
+        // Auto-synthesized rules are substring-matched, so regex metacharacters are fine
+        insert_full_rule(
+            &conn, "r1", "general", "auto-synthesized", "Literal text", "must-fix",
+            None, None, Some("(unclosed"), None, 1,
+        );
+
+        let rules = fetch_writing_rules(&conn, None).unwrap();
+        assert!(find_invalid_patterns(&rules).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn markdown_only_export_writes_no_hook() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let home = tempfile::tempdir().unwrap();
+        // Stands in for `margin export profile --targets <list>`, writing only what it's asked for
+        let cli = home.path().join("fake-margin");
+        std::fs::write(
+            &cli,
+            "#!/bin/sh\n\
+             case \"$4\" in *markdown*) mkdir -p \"$HOME/.margin\" && echo rules > \"$HOME/.margin/writing-rules.md\";; esac\n\
+             case \"$4\" in *hook*) mkdir -p \"$HOME/.claude/hooks\" && echo guard > \"$HOME/.claude/hooks/writing_guard.py\";; esac\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&cli, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let targets = ExportTargets::parse(Some(&["markdown".to_string()])).unwrap();
+        let (markdown_path, hook_path) = run_cli_export_with(&cli, home.path(), targets, None).unwrap();
+
+        let expected_md = home.path().join(".margin").join("writing-rules.md");
+        assert_eq!(markdown_path.as_deref(), Some(expected_md.to_string_lossy().as_ref()));
+        assert!(expected_md.exists());
+        assert_eq!(hook_path, None);
+        assert!(!home.path().join(".claude").join("hooks").join("writing_guard.py").exists());
+    }
+
+    #[test]
+    fn remember_min_severity_keeps_the_last_choice() {
+        let conn = setup_db();
+        migrate_add_settings_table(&conn).unwrap();
+        let stored = |conn: &Connection| get_setting(conn, GUARD_MIN_SEVERITY_KEY).unwrap();
+
+        remember_min_severity(&conn, Some("must-fix")).unwrap();
+        assert_eq!(stored(&conn).as_deref(), Some("must-fix"));
+
+        // Plain re-exports leave it alone
+        remember_min_severity(&conn, None).unwrap();
+        assert_eq!(stored(&conn).as_deref(), Some("must-fix"));
+
+        assert!(remember_min_severity(&conn, Some("urgent")).is_err());
+        assert_eq!(stored(&conn).as_deref(), Some("must-fix"));
+
+        remember_min_severity(&conn, Some("")).unwrap();
+        assert_eq!(stored(&conn).as_deref(), Some(""));
+    }
+
+    #[test]
+    fn export_targets_default_to_both() {
+        assert_eq!(ExportTargets::parse(None).unwrap(), ExportTargets::ALL);
+        assert_eq!(ExportTargets::parse(Some(&[])).unwrap(), ExportTargets::ALL);
+        assert_eq!(ExportTargets::ALL.cli_arg(), "markdown,hook");
+    }
+
+    #[test]
+    fn markdown_only_export_skips_hook() {
+        let targets = ExportTargets::parse(Some(&["markdown".to_string()])).unwrap();
+        assert!(targets.markdown);
+        assert!(!targets.hook);
+        assert_eq!(targets.cli_arg(), "markdown");
+        assert!(ExportTargets::parse(Some(&["pdf".to_string()])).is_err());
+    }
 }
@@ -4,9 +4,14 @@ use std::io::BufRead;
 use std::path::PathBuf;
 use std::sync::Mutex;
 
-fn db_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+/// `~/.margin`, where the database and exported artifacts live.
+pub(crate) fn data_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
     let home = dirs::home_dir().ok_or("Could not determine home directory")?;
-    let margin_dir = home.join(".margin");
+    Ok(home.join(".margin"))
+}
+
+fn db_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let margin_dir = data_dir()?;
     fs::create_dir_all(&margin_dir)?;
     Ok(margin_dir.join("margin.db"))
 }
@@ -21,10 +26,36 @@ impl DbPool {
     }
 }
 
-fn apply_pragmas(conn: &Connection) -> Result<(), Box<dyn std::error::Error>> {
+/// Settings key for the SQLite busy timeout, read once at `init_db`.
+pub(crate) const BUSY_TIMEOUT_KEY: &str = "busy_timeout_ms";
+pub(crate) const DEFAULT_BUSY_TIMEOUT_MS: u32 = 5000;
+pub(crate) const MAX_BUSY_TIMEOUT_MS: u32 = 60_000;
+
+pub(crate) fn validate_busy_timeout(ms: u32) -> Result<u32, String> {
+    if ms == 0 || ms > MAX_BUSY_TIMEOUT_MS {
+        return Err(format!("busy_timeout_ms must be between 1 and {MAX_BUSY_TIMEOUT_MS}"));
+    }
+    Ok(ms)
+}
+
+/// The stored busy timeout, or the default when unset, invalid, or when the
+/// settings table doesn't exist yet (first launch runs this before migrations).
+fn configured_busy_timeout(conn: &Connection) -> u32 {
+    conn.query_row(
+        "SELECT value FROM settings WHERE key = ?1",
+        [BUSY_TIMEOUT_KEY],
+        |row| row.get::<_, String>(0),
+    )
+    .ok()
+    .and_then(|v| v.parse::<u32>().ok())
+    .and_then(|ms| validate_busy_timeout(ms).ok())
+    .unwrap_or(DEFAULT_BUSY_TIMEOUT_MS)
+}
+
+fn apply_pragmas(conn: &Connection, busy_timeout_ms: u32) -> Result<(), Box<dyn std::error::Error>> {
     conn.execute_batch("PRAGMA journal_mode=WAL;")?;
     conn.execute_batch("PRAGMA foreign_keys=ON;")?;
-    conn.execute_batch("PRAGMA busy_timeout=5000;")?;
+    conn.execute_batch(&format!("PRAGMA busy_timeout={busy_timeout_ms};"))?;
     conn.execute_batch("PRAGMA synchronous=NORMAL;")?;
     Ok(())
 }
@@ -33,7 +64,8 @@ pub fn init_db() -> Result<DbPool, Box<dyn std::error::Error>> {
     let path = db_path()?;
     let conn = Connection::open(&path)?;
 
-    apply_pragmas(&conn)?;
+    let busy_timeout_ms = configured_busy_timeout(&conn);
+    apply_pragmas(&conn, busy_timeout_ms)?;
 
     conn.execute_batch(
         "CREATE TABLE IF NOT EXISTS documents (
@@ -150,6 +182,42 @@ pub fn init_db() -> Result<DbPool, Box<dyn std::error::Error>> {
     // Migration: create dashboard tables (test_runs, test_run_types)
     migrate_add_dashboard_tables(&conn)?;
 
+    // Migration: create settings key/value table
+    migrate_add_settings_table(&conn)?;
+
+    // Migration: create correction_rule_links table
+    migrate_add_correction_rule_links_table(&conn)?;
+
+    // Migration: create margin note search index
+    migrate_add_notes_fts(&conn)?;
+
+    // Migration: drop a document's search index row when the document is deleted
+    migrate_add_documents_fts_delete_trigger(&conn)?;
+
+    // Migration: create highlight_tags table
+    migrate_add_highlight_tags_table(&conn)?;
+
+    // Migration: add parent_note_id column to margin_notes
+    migrate_margin_notes_add_parent(&conn)?;
+
+    // Migration: add deleted_at column to highlights
+    migrate_highlights_add_deleted_at(&conn)?;
+
+    // Migration: add archived column to documents
+    migrate_documents_add_archived(&conn)?;
+
+    // Migration: add pinned_at column to documents
+    migrate_documents_add_pinned_at(&conn)?;
+
+    // Migration: add reading_progress column to documents
+    migrate_documents_add_reading_progress(&conn)?;
+
+    // Migration: add custom_title column to documents
+    migrate_documents_add_custom_title(&conn)?;
+
+    // Migration: add pinned column to open_tabs
+    migrate_open_tabs_add_pinned(&conn)?;
+
     // Cleanup: mark stale running test runs as failed (from previous crashes)
     let _ = conn.execute(
         "UPDATE test_runs SET status = 'failed' WHERE status = 'running'",
@@ -242,6 +310,20 @@ fn migrate_corrections_drop_fks(conn: &Connection) -> Result<(), Box<dyn std::er
     Ok(())
 }
 
+/// Splits a rotation suffix off a JSONL file stem: `corrections-2026-02-23-002` sorts as
+/// (`corrections-2026-02-23`, 2) so it lands after `corrections-2026-02-23` (part 1).
+fn jsonl_sort_key(path: &std::path::Path) -> (String, u32) {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+    if let Some((base, part)) = stem.rsplit_once('-') {
+        if part.len() == 3 && part.bytes().all(|b| b.is_ascii_digit()) {
+            if let Ok(n) = part.parse::<u32>() {
+                return (base.to_string(), n);
+            }
+        }
+    }
+    (stem.to_string(), 1)
+}
+
 /// Import corrections from JSONL files in `dir` that are missing from the DB.
 /// Files are processed in sorted order (oldest first) so newer entries win on conflict.
 /// Returns the number of corrections imported.
@@ -253,13 +335,14 @@ fn backfill_corrections_from_dir(conn: &Connection, dir: &std::path::Path) -> us
         return 0;
     };
 
-    // Sort JSONL files by name (date-based filenames → chronological order)
+    // Sort JSONL files by name (date-based filenames → chronological order),
+    // keeping rotated parts after the file they continue
     let mut paths: Vec<_> = entries
         .flatten()
         .map(|e| e.path())
         .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("jsonl"))
         .collect();
-    paths.sort();
+    paths.sort_by_cached_key(|p| jsonl_sort_key(p));
 
     let mut imported = 0;
     for path in &paths {
@@ -331,7 +414,7 @@ mod tests {
     #[test]
     fn db_pool_sets_wal_mode() {
         let conn = Connection::open_in_memory().unwrap();
-        apply_pragmas(&conn).unwrap();
+        apply_pragmas(&conn, DEFAULT_BUSY_TIMEOUT_MS).unwrap();
         let mode: String = conn.query_row("PRAGMA journal_mode", [], |r| r.get(0)).unwrap();
         // In-memory databases report "memory" instead of "wal", but the pragma doesn't error.
         // For a real file DB it would be "wal". Just verify it doesn't fail.
@@ -341,7 +424,7 @@ mod tests {
     #[test]
     fn db_pool_sets_foreign_keys() {
         let conn = Connection::open_in_memory().unwrap();
-        apply_pragmas(&conn).unwrap();
+        apply_pragmas(&conn, DEFAULT_BUSY_TIMEOUT_MS).unwrap();
         let fk: i64 = conn.query_row("PRAGMA foreign_keys", [], |r| r.get(0)).unwrap();
         assert_eq!(fk, 1);
     }
@@ -349,15 +432,36 @@ mod tests {
     #[test]
     fn db_pool_sets_busy_timeout() {
         let conn = Connection::open_in_memory().unwrap();
-        apply_pragmas(&conn).unwrap();
+        apply_pragmas(&conn, DEFAULT_BUSY_TIMEOUT_MS).unwrap();
         let timeout: i64 = conn.query_row("PRAGMA busy_timeout", [], |r| r.get(0)).unwrap();
         assert_eq!(timeout, 5000);
     }
 
+    #[test]
+    fn configured_busy_timeout_is_applied() {
+        let conn = Connection::open_in_memory().unwrap();
+        // No settings table yet: default
+        assert_eq!(configured_busy_timeout(&conn), DEFAULT_BUSY_TIMEOUT_MS);
+
+        migrate_add_settings_table(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO settings (key, value, updated_at) VALUES (?1, '12000', 0)",
+            [BUSY_TIMEOUT_KEY],
+        ).unwrap();
+        apply_pragmas(&conn, configured_busy_timeout(&conn)).unwrap();
+        let timeout: i64 = conn.query_row("PRAGMA busy_timeout", [], |r| r.get(0)).unwrap();
+        assert_eq!(timeout, 12000);
+
+        // Out-of-range values fall back to the default
+        conn.execute("UPDATE settings SET value = '0' WHERE key = ?1", [BUSY_TIMEOUT_KEY]).unwrap();
+        assert_eq!(configured_busy_timeout(&conn), DEFAULT_BUSY_TIMEOUT_MS);
+        assert!(validate_busy_timeout(MAX_BUSY_TIMEOUT_MS + 1).is_err());
+    }
+
     #[test]
     fn db_pool_connection_is_reusable() {
         let conn = Connection::open_in_memory().unwrap();
-        apply_pragmas(&conn).unwrap();
+        apply_pragmas(&conn, DEFAULT_BUSY_TIMEOUT_MS).unwrap();
         let pool = DbPool::new(conn);
 
         // First use
@@ -418,6 +522,29 @@ mod tests {
         migrate_documents_add_frecency_columns(&conn).unwrap();
     }
 
+    #[test]
+    fn pinned_at_migration_folds_in_and_drops_pinned_flag() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE documents (
+                id TEXT PRIMARY KEY,
+                last_opened_at INTEGER NOT NULL,
+                pinned INTEGER NOT NULL DEFAULT 0
+            );
+            INSERT INTO documents VALUES ('pinned', 500, 1), ('plain', 600, 0);",
+        ).unwrap();
+
+        migrate_documents_add_pinned_at(&conn).unwrap();
+        migrate_documents_add_pinned_at(&conn).unwrap();
+
+        let pinned_at = |id: &str| -> Option<i64> {
+            conn.query_row("SELECT pinned_at FROM documents WHERE id = ?1", [id], |r| r.get(0)).unwrap()
+        };
+        assert_eq!(pinned_at("pinned"), Some(500));
+        assert_eq!(pinned_at("plain"), None);
+        assert!(conn.prepare("SELECT pinned FROM documents").is_err());
+    }
+
     fn corrections_table_sql() -> &'static str {
         "CREATE TABLE corrections (
             id TEXT PRIMARY KEY,
@@ -526,6 +653,26 @@ mod tests {
         assert_eq!(count(&conn), 2); // both rows exist — corrections are events
     }
 
+    #[test]
+    fn jsonl_files_sort_with_rotated_parts_after_their_base() {
+        let mut names = vec![
+            "corrections-2026-02-24.jsonl",
+            "corrections-2026-02-23-010.jsonl",
+            "corrections-2026-02-23-002.jsonl",
+            "corrections-2026-02-23.jsonl",
+        ];
+        names.sort_by_cached_key(|n| jsonl_sort_key(std::path::Path::new(n)));
+        assert_eq!(
+            names,
+            vec![
+                "corrections-2026-02-23.jsonl",
+                "corrections-2026-02-23-002.jsonl",
+                "corrections-2026-02-23-010.jsonl",
+                "corrections-2026-02-24.jsonl",
+            ]
+        );
+    }
+
     #[test]
     fn backfill_ignores_non_jsonl_files() {
         let conn = setup_db();
@@ -1288,3 +1435,243 @@ fn migrate_writing_rules_add_register(conn: &Connection) -> Result<(), Box<dyn s
 
     Ok(())
 }
+
+/// Adds an `archived` flag to the documents table if it doesn't exist.
+fn migrate_documents_add_archived(conn: &Connection) -> Result<(), Box<dyn std::error::Error>> {
+    let has_column: bool = {
+        let mut stmt = conn.prepare("PRAGMA table_info(documents)")?;
+        let columns: Vec<String> = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .filter_map(|r| r.ok())
+            .collect();
+        columns.iter().any(|c| c == "archived")
+    };
+
+    if !has_column {
+        conn.execute_batch("ALTER TABLE documents ADD COLUMN archived INTEGER NOT NULL DEFAULT 0;")?;
+    }
+
+    Ok(())
+}
+
+/// Adds a `pinned_at` timestamp to the documents table if it doesn't exist;
+/// a document is pinned exactly when it is set. The older `pinned` flag, if
+/// present, is folded in (pinned documents take their last-opened time) and
+/// dropped.
+fn migrate_documents_add_pinned_at(conn: &Connection) -> Result<(), Box<dyn std::error::Error>> {
+    let columns: Vec<String> = {
+        let mut stmt = conn.prepare("PRAGMA table_info(documents)")?;
+        let columns = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .filter_map(|r| r.ok())
+            .collect();
+        columns
+    };
+
+    if !columns.iter().any(|c| c == "pinned_at") {
+        conn.execute_batch("ALTER TABLE documents ADD COLUMN pinned_at INTEGER;")?;
+    }
+    if columns.iter().any(|c| c == "pinned") {
+        conn.execute_batch(
+            "UPDATE documents SET pinned_at = COALESCE(pinned_at, last_opened_at) WHERE pinned = 1;
+             ALTER TABLE documents DROP COLUMN pinned;",
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Adds a `reading_progress` fraction (0.0–1.0) to the documents table if it doesn't exist.
+fn migrate_documents_add_reading_progress(conn: &Connection) -> Result<(), Box<dyn std::error::Error>> {
+    let has_column: bool = {
+        let mut stmt = conn.prepare("PRAGMA table_info(documents)")?;
+        let columns: Vec<String> = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .filter_map(|r| r.ok())
+            .collect();
+        columns.iter().any(|c| c == "reading_progress")
+    };
+
+    if !has_column {
+        conn.execute_batch("ALTER TABLE documents ADD COLUMN reading_progress REAL NOT NULL DEFAULT 0;")?;
+    }
+
+    Ok(())
+}
+
+/// Adds a `custom_title` flag to the documents table if it doesn't exist. Set
+/// when the user renames a document so reopening doesn't restore the old title.
+fn migrate_documents_add_custom_title(conn: &Connection) -> Result<(), Box<dyn std::error::Error>> {
+    let has_column: bool = {
+        let mut stmt = conn.prepare("PRAGMA table_info(documents)")?;
+        let columns: Vec<String> = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .filter_map(|r| r.ok())
+            .collect();
+        columns.iter().any(|c| c == "custom_title")
+    };
+
+    if !has_column {
+        conn.execute_batch("ALTER TABLE documents ADD COLUMN custom_title INTEGER NOT NULL DEFAULT 0;")?;
+    }
+
+    Ok(())
+}
+
+/// Adds a `pinned` flag to the open_tabs table if it doesn't exist.
+fn migrate_open_tabs_add_pinned(conn: &Connection) -> Result<(), Box<dyn std::error::Error>> {
+    let has_column: bool = {
+        let mut stmt = conn.prepare("PRAGMA table_info(open_tabs)")?;
+        let columns: Vec<String> = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .filter_map(|r| r.ok())
+            .collect();
+        columns.iter().any(|c| c == "pinned")
+    };
+
+    if !has_column {
+        conn.execute_batch("ALTER TABLE open_tabs ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;")?;
+    }
+
+    Ok(())
+}
+
+/// Creates the `correction_rule_links` table if it doesn't exist. Links key on
+/// `highlight_id` because corrections have no FKs and may span sessions.
+pub fn migrate_add_correction_rule_links_table(conn: &Connection) -> Result<(), Box<dyn std::error::Error>> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS correction_rule_links (
+            highlight_id TEXT NOT NULL,
+            rule_id TEXT NOT NULL REFERENCES writing_rules(id) ON DELETE CASCADE,
+            created_at INTEGER NOT NULL,
+            PRIMARY KEY (highlight_id, rule_id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_correction_rule_links_rule ON correction_rule_links(rule_id);",
+    )?;
+    Ok(())
+}
+
+/// Free-form labels on highlights, removed along with their highlight.
+pub fn migrate_add_highlight_tags_table(conn: &Connection) -> Result<(), Box<dyn std::error::Error>> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS highlight_tags (
+            highlight_id TEXT NOT NULL REFERENCES highlights(id) ON DELETE CASCADE,
+            tag TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            PRIMARY KEY (highlight_id, tag)
+        );
+        CREATE INDEX IF NOT EXISTS idx_highlight_tags_tag ON highlight_tags(tag);",
+    )?;
+    Ok(())
+}
+
+/// Adds a nullable `parent_note_id` to margin_notes so notes can reply to
+/// each other. Deleting a note cascades to its replies.
+fn migrate_margin_notes_add_parent(conn: &Connection) -> Result<(), Box<dyn std::error::Error>> {
+    let has_column: bool = {
+        let mut stmt = conn.prepare("PRAGMA table_info(margin_notes)")?;
+        let columns: Vec<String> = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .filter_map(|r| r.ok())
+            .collect();
+        columns.iter().any(|c| c == "parent_note_id")
+    };
+
+    if !has_column {
+        conn.execute_batch(
+            "ALTER TABLE margin_notes ADD COLUMN parent_note_id TEXT REFERENCES margin_notes(id) ON DELETE CASCADE;
+             CREATE INDEX IF NOT EXISTS idx_margin_notes_parent ON margin_notes(parent_note_id);",
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Adds a nullable `deleted_at` to highlights for soft deletes. Rows are
+/// only removed (cascading to notes and tags) when purged.
+fn migrate_highlights_add_deleted_at(conn: &Connection) -> Result<(), Box<dyn std::error::Error>> {
+    let has_column: bool = {
+        let mut stmt = conn.prepare("PRAGMA table_info(highlights)")?;
+        let columns: Vec<String> = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .filter_map(|r| r.ok())
+            .collect();
+        columns.iter().any(|c| c == "deleted_at")
+    };
+
+    if !has_column {
+        conn.execute_batch("ALTER TABLE highlights ADD COLUMN deleted_at INTEGER;")?;
+    }
+
+    Ok(())
+}
+
+/// Full-text index over document title and content, shared with
+/// `search::ensure_fts_table`.
+pub(crate) const CREATE_DOCUMENTS_FTS: &str = "CREATE VIRTUAL TABLE IF NOT EXISTS documents_fts USING fts5(
+    title, content, document_id UNINDEXED,
+    prefix='2,3',
+    tokenize='unicode61 remove_diacritics 2'
+);";
+
+/// Deletes a document's `documents_fts` row whenever the document row goes,
+/// whichever code path deletes it, so search never returns orphaned hits.
+pub fn migrate_add_documents_fts_delete_trigger(conn: &Connection) -> Result<(), Box<dyn std::error::Error>> {
+    conn.execute_batch(CREATE_DOCUMENTS_FTS)?;
+    conn.execute_batch(
+        "CREATE TRIGGER IF NOT EXISTS documents_fts_delete AFTER DELETE ON documents BEGIN
+            DELETE FROM documents_fts WHERE document_id = old.id;
+        END;",
+    )?;
+    Ok(())
+}
+
+/// Creates `notes_fts`, a full-text index over margin note content kept in
+/// sync by triggers (cascading deletes included). Existing notes are
+/// backfilled the first time the table is created.
+pub fn migrate_add_notes_fts(conn: &Connection) -> Result<(), Box<dyn std::error::Error>> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'notes_fts')",
+        [],
+        |row| row.get(0),
+    )?;
+
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS notes_fts USING fts5(
+            content, note_id UNINDEXED,
+            prefix='2,3',
+            tokenize='unicode61 remove_diacritics 2'
+        );
+
+        CREATE TRIGGER IF NOT EXISTS margin_notes_fts_insert AFTER INSERT ON margin_notes BEGIN
+            INSERT INTO notes_fts (content, note_id) VALUES (new.content, new.id);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS margin_notes_fts_update AFTER UPDATE OF content ON margin_notes BEGIN
+            DELETE FROM notes_fts WHERE note_id = old.id;
+            INSERT INTO notes_fts (content, note_id) VALUES (new.content, new.id);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS margin_notes_fts_delete AFTER DELETE ON margin_notes BEGIN
+            DELETE FROM notes_fts WHERE note_id = old.id;
+        END;",
+    )?;
+
+    if !exists {
+        conn.execute("INSERT INTO notes_fts (content, note_id) SELECT content, id FROM margin_notes", [])?;
+    }
+    Ok(())
+}
+
+/// Creates the `settings` key/value table if it doesn't exist.
+/// Values are stored as text; callers own their (de)serialization.
+pub fn migrate_add_settings_table(conn: &Connection) -> Result<(), Box<dyn std::error::Error>> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL,
+            updated_at INTEGER NOT NULL
+        );",
+    )?;
+    Ok(())
+}
@@ -13,6 +13,9 @@ pub struct Document {
     pub word_count: i64,
     pub last_opened_at: i64,
     pub created_at: i64,
+    /// How far through the document the reader got, 0.0–1.0.
+    #[serde(default)]
+    pub reading_progress: f64,
 }
 
 impl Document {
@@ -28,6 +31,7 @@ impl Document {
             word_count: row.get("word_count")?,
             last_opened_at: row.get("last_opened_at")?,
             created_at: row.get("created_at")?,
+            reading_progress: row.get("reading_progress")?,
         })
     }
 }
@@ -44,6 +48,9 @@ pub struct Highlight {
     pub suffix_context: Option<String>,
     pub created_at: i64,
     pub updated_at: i64,
+    /// Only filled in when the caller asks for tags.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
 }
 
 impl Highlight {
@@ -59,6 +66,7 @@ impl Highlight {
             suffix_context: row.get("suffix_context")?,
             created_at: row.get("created_at")?,
             updated_at: row.get("updated_at")?,
+            tags: None,
         })
     }
 }
@@ -67,6 +75,9 @@ impl Highlight {
 pub struct MarginNote {
     pub id: String,
     pub highlight_id: String,
+    /// The note this one replies to, on the same highlight; `None` for top-level notes.
+    #[serde(default)]
+    pub parent_note_id: Option<String>,
     pub content: String,
     pub created_at: i64,
     pub updated_at: i64,
@@ -77,6 +88,7 @@ impl MarginNote {
         Ok(MarginNote {
             id: row.get("id")?,
             highlight_id: row.get("highlight_id")?,
+            parent_note_id: row.get("parent_note_id")?,
             content: row.get("content")?,
             created_at: row.get("created_at")?,
             updated_at: row.get("updated_at")?,
@@ -84,6 +96,13 @@ impl MarginNote {
     }
 }
 
+/// A highlight together with its margin notes, for detail views.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HighlightWithNotes {
+    pub highlight: Highlight,
+    pub notes: Vec<MarginNote>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CorrectionInput {
     pub highlight_id: String,
@@ -95,5 +114,6 @@ pub struct CorrectionInput {
     pub highlight_color: String,
     pub writing_type: Option<String>,
     pub polarity: Option<String>,
+    pub category: Option<String>,
 }
 
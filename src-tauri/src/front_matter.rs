@@ -0,0 +1,177 @@
+//! YAML front matter at the top of markdown documents. Only the subset that
+//! notes actually use is understood: `key: value` scalars, `[a, b]` inline
+//! lists and `- item` block lists. Anything else means the block is not
+//! treated as front matter, so a leading `---` rule is left in the body.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct FrontMatter {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub tags: Vec<String>,
+    pub created: Option<String>,
+    /// Every other key; list values are joined with ", ".
+    pub extra: BTreeMap<String, String>,
+}
+
+enum Value {
+    Scalar(String),
+    List(Vec<String>),
+}
+
+impl Value {
+    fn into_scalar(self) -> Option<String> {
+        match self {
+            Value::Scalar(s) => Some(s),
+            Value::List(items) => items.into_iter().next(),
+        }
+    }
+
+    fn into_list(self) -> Vec<String> {
+        match self {
+            Value::Scalar(s) => s.split(',').map(unquote).filter(|t| !t.is_empty()).collect(),
+            Value::List(items) => items,
+        }
+    }
+}
+
+fn unquote(s: &str) -> String {
+    let s = s.trim();
+    let quoted = s.len() >= 2
+        && ((s.starts_with('"') && s.ends_with('"')) || (s.starts_with('\'') && s.ends_with('\'')));
+    if quoted {
+        s[1..s.len() - 1].to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+fn parse_value(raw: &str) -> Value {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        // `key:` alone introduces a block list
+        return Value::List(Vec::new());
+    }
+    match raw.strip_prefix('[').and_then(|r| r.strip_suffix(']')) {
+        Some(inner) => Value::List(inner.split(',').map(unquote).filter(|t| !t.is_empty()).collect()),
+        None => Value::Scalar(unquote(raw)),
+    }
+}
+
+/// Parses the lines between the `---` fences, or `None` if any line isn't
+/// one of the supported forms.
+fn parse_block(block: &str) -> Option<FrontMatter> {
+    let mut entries: Vec<(String, Value)> = Vec::new();
+    for line in block.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let item = if trimmed == "-" { Some("") } else { trimmed.strip_prefix("- ") };
+        if let Some(item) = item {
+            // A block list item belongs to the preceding `key:` with no value
+            match entries.last_mut() {
+                Some((_, Value::List(items))) => items.push(unquote(item)),
+                _ => return None,
+            }
+            continue;
+        }
+        let (key, raw) = line.split_once(':')?;
+        if key.starts_with(char::is_whitespace) || key.trim().is_empty() {
+            return None;
+        }
+        entries.push((key.trim().to_string(), parse_value(raw)));
+    }
+
+    let mut fm = FrontMatter::default();
+    for (key, value) in entries {
+        match key.to_lowercase().as_str() {
+            "title" => fm.title = value.into_scalar(),
+            "author" => fm.author = value.into_scalar(),
+            "created" => fm.created = value.into_scalar(),
+            "tags" => fm.tags = value.into_list(),
+            _ => {
+                let joined = match value {
+                    Value::Scalar(s) => s,
+                    Value::List(items) => items.join(", "),
+                };
+                fm.extra.insert(key, joined);
+            }
+        }
+    }
+    Some(fm)
+}
+
+/// Splits `content` into its front matter and the body after it. Content
+/// without a well-formed leading block comes back whole as the body.
+pub fn parse_front_matter(content: &str) -> (Option<FrontMatter>, &str) {
+    let text = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let Some(rest) = text.strip_prefix("---") else {
+        return (None, content);
+    };
+    let Some(rest) = rest.strip_prefix("\r\n").or_else(|| rest.strip_prefix('\n')) else {
+        return (None, content);
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        let fence = line.trim_end();
+        if fence == "---" || fence == "..." {
+            let body = &rest[offset + line.len()..];
+            return match parse_block(&rest[..offset]) {
+                Some(fm) => (Some(fm), body),
+                None => (None, content),
+            };
+        }
+        offset += line.len();
+    }
+    (None, content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_block_and_returns_body() {
+        let content = "---\ntitle: \"On Rhythm\"\nauthor: Ada\ntags: [prose, craft]\ncreated: 2024-05-01\nstatus: draft\n---\n# Heading\n\nBody text.";
+        let (fm, body) = parse_front_matter(content);
+        let fm = fm.unwrap();
+        assert_eq!(fm.title.as_deref(), Some("On Rhythm"));
+        assert_eq!(fm.author.as_deref(), Some("Ada"));
+        assert_eq!(fm.tags, vec!["prose", "craft"]);
+        assert_eq!(fm.created.as_deref(), Some("2024-05-01"));
+        assert_eq!(fm.extra.get("status").map(String::as_str), Some("draft"));
+        assert_eq!(body, "# Heading\n\nBody text.");
+    }
+
+    #[test]
+    fn block_lists_and_partial_fields() {
+        let content = "---\r\ntags:\r\n  - one\r\n  - 'two'\r\n---\r\nbody";
+        let (fm, body) = parse_front_matter(content);
+        let fm = fm.unwrap();
+        assert_eq!(fm.tags, vec!["one", "two"]);
+        assert_eq!(fm.title, None);
+        assert_eq!(fm.author, None);
+        assert_eq!(body, "body");
+    }
+
+    #[test]
+    fn content_without_block_is_all_body() {
+        let content = "# Just a note\n\n---\ntitle: not front matter\n---";
+        let (fm, body) = parse_front_matter(content);
+        assert!(fm.is_none());
+        assert_eq!(body, content);
+    }
+
+    #[test]
+    fn unclosed_or_non_yaml_block_is_left_alone() {
+        let unclosed = "---\ntitle: Draft\nno closing fence";
+        assert_eq!(parse_front_matter(unclosed), (None, unclosed));
+
+        let rule = "---\nJust a paragraph after a rule.\n---\nmore";
+        assert_eq!(parse_front_matter(rule), (None, rule));
+    }
+}
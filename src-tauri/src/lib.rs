@@ -1,5 +1,7 @@
+pub mod anchoring;
 pub mod commands;
 pub mod db;
+pub mod front_matter;
 pub mod watcher;
 
 use std::sync::Mutex;
@@ -10,10 +12,26 @@ use commands::keep_local::HttpClient;
 /// Stores file paths received before the frontend is ready.
 pub struct PendingOpenFiles(pub Mutex<Vec<String>>);
 
+impl PendingOpenFiles {
+    fn peek(&self) -> Vec<String> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn drain(&self) -> Vec<String> {
+        let mut pending = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        pending.drain(..).collect()
+    }
+}
+
 #[tauri::command]
 fn drain_pending_open_files(state: tauri::State<'_, PendingOpenFiles>) -> Vec<String> {
-    let mut pending = state.0.lock().unwrap_or_else(|e| e.into_inner());
-    pending.drain(..).collect()
+    state.drain()
+}
+
+/// Read-only view of the queue for diagnosing the startup open-file race.
+#[tauri::command]
+fn peek_pending_open_files(state: tauri::State<'_, PendingOpenFiles>) -> Vec<String> {
+    state.peek()
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -31,26 +49,65 @@ pub fn run() {
                 .expect("failed to build HTTP client"),
         ))
         .manage(Mutex::new(watcher::FileWatcher::new()))
+        .manage(watcher::SelfWrites::default())
         .manage(PendingOpenFiles(Mutex::new(Vec::new())))
         .invoke_handler(tauri::generate_handler![
             commands::search::index_all_documents,
             commands::files::open_file_dialog,
             commands::files::read_file,
+            commands::files::get_file_metadata,
             commands::files::save_file,
+            commands::files::list_backups,
+            commands::files::restore_backup,
             commands::files::list_markdown_files,
+            commands::files::set_workspace,
             commands::files::rename_file,
+            commands::files::move_file,
+            commands::files::create_file,
+            commands::files::delete_file,
+            commands::files::duplicate_document,
+            commands::files::duplicate_file,
+            commands::files::import_directory,
+            commands::files::list_attachments,
+            commands::files::find_broken_links,
             commands::documents::get_recent_documents,
+            commands::documents::get_document,
+            commands::documents::get_recently_created_documents,
+            commands::documents::set_document_pinned,
+            commands::documents::set_document_archived,
+            commands::documents::set_reading_progress,
+            commands::documents::set_document_title,
             commands::documents::upsert_document,
+            commands::documents::delete_document,
+            commands::documents::word_count,
+            commands::documents::recompute_word_counts,
             commands::annotations::create_highlight,
             commands::annotations::get_highlights,
+            commands::annotations::add_highlight_tag,
+            commands::annotations::remove_highlight_tag,
+            commands::annotations::get_highlights_by_tag,
+            commands::annotations::get_highlight_with_notes,
+            commands::annotations::get_all_highlights,
+            commands::annotations::get_document_annotation_counts,
             commands::annotations::update_highlight_color,
+            commands::annotations::recolor_highlights,
             commands::annotations::delete_highlight,
+            commands::annotations::restore_highlight,
+            commands::annotations::purge_deleted_highlights,
             commands::annotations::create_margin_note,
+            commands::annotations::create_margin_note_from_template,
             commands::annotations::get_margin_notes,
             commands::annotations::update_margin_note,
             commands::annotations::delete_margin_note,
+            commands::annotations::find_orphaned_notes,
+            commands::annotations::prune_orphaned_notes,
             commands::annotations::delete_all_highlights_for_document,
+            commands::annotations::export_annotations_by_color,
+            commands::annotations::export_annotations_obsidian,
+            commands::annotations::import_highlights_json,
             commands::annotations::update_highlight_positions,
+            commands::annotations::merge_overlapping_highlights,
+            commands::annotations::reanchor_highlights,
             commands::snapshots::save_content_snapshot,
             commands::snapshots::get_content_snapshot,
             commands::snapshots::delete_content_snapshot,
@@ -58,30 +115,63 @@ pub fn run() {
             commands::keep_local::keep_local_list_items,
             commands::keep_local::keep_local_get_item,
             commands::keep_local::keep_local_get_content,
+            commands::keep_local::import_keep_local_item,
+            commands::keep_local::clear_keep_local_cache,
             commands::search::index_document,
             commands::search::search_documents,
+            commands::search::search_documents_raw,
+            commands::search::search_in_document,
+            commands::search::search_notes,
+            commands::search::index_file,
+            commands::search::reindex_document,
+            commands::search::get_indexed_content,
             commands::search::remove_document_index,
             commands::search::search_files_on_disk,
+            commands::search::get_stale_documents,
+            commands::search::get_last_index_summary,
             commands::corrections::persist_corrections,
             commands::corrections::get_all_corrections,
             commands::corrections::get_corrections_count,
             commands::corrections::get_corrections_by_document,
+            commands::corrections::get_top_corrected_documents,
+            commands::corrections::get_corrections_stats,
+            commands::corrections::get_correction_context,
             commands::corrections::update_correction_writing_type,
             commands::corrections::delete_correction,
             commands::corrections::export_corrections_json,
+            commands::corrections::export_corrections_csv_range,
+            commands::corrections::export_document_corrections,
             commands::corrections::get_corrections_flat,
             commands::corrections::bulk_delete_corrections,
             commands::corrections::bulk_tag_corrections,
             commands::corrections::bulk_set_polarity_corrections,
             commands::corrections::get_voice_signals,
+            commands::corrections::link_correction_to_rule,
+            commands::corrections::get_rule_examples,
+            commands::corrections::get_exportable_corrections_count,
             commands::corrections::mark_corrections_synthesized,
             commands::corrections::mark_corrections_unsynthesized,
             commands::tabs::get_open_tabs,
+            commands::tabs::get_tab_summary,
             commands::tabs::save_open_tabs,
+            commands::tabs::move_tab,
+            commands::tabs::close_unpinned_tabs,
+            commands::documents::get_tag_counts,
+            commands::documents::add_document_tag,
+            commands::documents::remove_document_tag,
+            commands::documents::get_document_tags,
+            commands::documents::get_documents_by_tag,
+            commands::documents::normalize_all_tags,
             commands::writing_rules::get_writing_rules,
+            commands::writing_rules::get_writing_rule_facets,
             commands::writing_rules::export_writing_rules,
+            commands::writing_rules::export_writing_rules_json,
+            commands::writing_rules::import_writing_rules,
+            commands::writing_rules::create_writing_rule,
             commands::writing_rules::update_writing_rule,
+            commands::writing_rules::increment_writing_rule_signal,
             commands::writing_rules::delete_writing_rule,
+            commands::writing_rules::validate_writing_rule,
             commands::writing_rules::export_voice_profile,
             commands::writing_rules::mark_rules_reviewed,
             commands::writing_rules::mark_rules_unreviewed,
@@ -91,9 +181,25 @@ pub fn run() {
             commands::dashboard::get_dashboard_summary,
             commands::dashboard::get_test_run_detail,
             commands::dashboard::export_dashboard_markdown,
+            commands::diagnostics::self_check,
+            commands::diagnostics::verify_integrity,
+            commands::settings::get_scan_threads,
+            commands::settings::set_scan_threads,
+            commands::settings::get_index_read_timeout_ms,
+            commands::settings::set_index_read_timeout_ms,
+            commands::settings::get_busy_timeout_ms,
+            commands::settings::set_busy_timeout_ms,
+            commands::settings::get_note_templates,
+            commands::settings::set_note_template,
+            commands::settings::get_default_highlight_color,
+            commands::settings::set_default_highlight_color,
+            commands::settings::get_workspace,
             watcher::watch_file,
             watcher::unwatch_file,
+            watcher::watch_directory,
+            watcher::unwatch_directory,
             drain_pending_open_files,
+            peek_pending_open_files,
         ])
         .setup(|app| {
             let pool = db::migrations::init_db()?;
@@ -136,3 +242,19 @@ pub fn run() {
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peek_leaves_pending_files_for_drain() {
+        let pending = PendingOpenFiles(Mutex::new(vec!["/a.md".to_string(), "/b.md".to_string()]));
+
+        assert_eq!(pending.peek(), vec!["/a.md", "/b.md"]);
+        assert_eq!(pending.peek(), vec!["/a.md", "/b.md"]);
+        assert_eq!(pending.drain(), vec!["/a.md", "/b.md"]);
+        assert!(pending.peek().is_empty());
+        assert!(pending.drain().is_empty());
+    }
+}
@@ -1,17 +1,288 @@
+use crate::commands::search::index_file_from_disk;
+use crate::db::migrations::DbPool;
+use notify::event::{ModifyKind, RenameMode};
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-use std::path::PathBuf;
-use std::sync::Mutex;
-use tauri::{AppHandle, Emitter};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Quiet period a file must reach before an auto-reindex fires.
+const REINDEX_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Quiet period before a watched file's burst of events becomes one `file-changed`.
+const FILE_CHANGED_DEBOUNCE: Duration = Duration::from_millis(200);
 
 #[derive(Clone, serde::Serialize)]
 struct FileChangedPayload {
     path: String,
 }
 
+#[derive(Clone, serde::Serialize)]
+struct FileRenamedPayload {
+    from: String,
+    to: String,
+}
+
+fn content_hash(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Canonicalizes via the parent so a file that doesn't exist yet (first save)
+/// keys the same as the paths notify reports once it does.
+fn normalize_path(path: &Path) -> PathBuf {
+    match (path.parent().and_then(|p| std::fs::canonicalize(p).ok()), path.file_name()) {
+        (Some(parent), Some(name)) => parent.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// How long after a save its events can still be attributed to the app.
+const SELF_WRITE_GRACE: Duration = Duration::from_secs(2);
+
+struct SelfWrite {
+    hash: u64,
+    written_at: Instant,
+    /// Whether the file was already there, i.e. the write replaced it.
+    existed: bool,
+}
+
+/// Writes the app made itself, keyed by path with the hash of what was written.
+/// Shared between `save_file` and the watchers so a self-save doesn't come back
+/// as an external `file-changed` or `directory-changed`.
+#[derive(Default)]
+pub struct SelfWrites(Mutex<HashMap<PathBuf, SelfWrite>>);
+
+impl SelfWrites {
+    pub fn record(&self, path: &Path, content: &[u8]) {
+        let mut expected = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        expected.insert(
+            normalize_path(path),
+            SelfWrite {
+                hash: content_hash(content),
+                written_at: Instant::now(),
+                existed: path.exists(),
+            },
+        );
+    }
+
+    pub(crate) fn is_external_change(&self, path: &Path) -> bool {
+        self.is_external_change_at(path, Instant::now())
+    }
+
+    /// True when the file on disk differs from the app's last write. A save
+    /// can fire several modify events, so the expectation is kept while the
+    /// content still matches and cleared by the first event that doesn't, or
+    /// by any event after the grace window. Either way, doubt means notify.
+    fn is_external_change_at(&self, path: &Path, now: Instant) -> bool {
+        let key = normalize_path(path);
+        let mut expected = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(write) = expected.get(&key) else {
+            return true;
+        };
+        if now.saturating_duration_since(write.written_at) > SELF_WRITE_GRACE {
+            expected.remove(&key);
+            return true;
+        }
+        let hash = write.hash;
+        match std::fs::read(&key) {
+            Ok(bytes) if content_hash(&bytes) == hash => false,
+            _ => {
+                expected.remove(&key);
+                true
+            }
+        }
+    }
+
+    /// True when a tree event for `path` is the app's own atomic save
+    /// renaming its temp file over a file that was already there (see
+    /// `write_file_atomic`): the content changed but the listing didn't. A
+    /// save that creates the file still changes the tree.
+    pub(crate) fn is_replaced_in_place(&self, path: &Path) -> bool {
+        if self.is_external_change(path) {
+            return false;
+        }
+        let expected = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        expected.get(&normalize_path(path)).is_some_and(|write| write.existed)
+    }
+}
+
+fn is_external_change(handle: &AppHandle, path: &Path) -> bool {
+    handle
+        .try_state::<SelfWrites>()
+        .is_none_or(|writes| writes.is_external_change(path))
+}
+
+fn is_replaced_in_place(handle: &AppHandle, path: &Path) -> bool {
+    handle
+        .try_state::<SelfWrites>()
+        .is_some_and(|writes| writes.is_replaced_in_place(path))
+}
+
+/// Coalesces bursts of events per path: a path becomes ready once no new
+/// event for it has arrived within `window`.
+pub(crate) struct Debouncer {
+    window: Duration,
+    pending: HashMap<PathBuf, Instant>,
+}
+
+impl Debouncer {
+    pub(crate) fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn record(&mut self, path: PathBuf, at: Instant) {
+        self.pending.insert(path, at);
+    }
+
+    /// Time until the earliest pending path becomes ready, or `None` when idle.
+    pub(crate) fn next_ready_in(&self, now: Instant) -> Option<Duration> {
+        self.pending
+            .values()
+            .map(|last| (*last + self.window).saturating_duration_since(now))
+            .min()
+    }
+
+    pub(crate) fn drain_ready(&mut self, now: Instant) -> Vec<PathBuf> {
+        let ready: Vec<PathBuf> = self
+            .pending
+            .iter()
+            .filter(|(_, last)| now.saturating_duration_since(**last) >= self.window)
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in &ready {
+            self.pending.remove(path);
+        }
+        ready
+    }
+}
+
+fn is_markdown_path(path: &Path) -> bool {
+    let hidden = path
+        .file_name()
+        .is_some_and(|n| n.to_string_lossy().starts_with('.'));
+    let markdown = path.extension().is_some_and(|ext| {
+        let ext_lower = ext.to_string_lossy().to_lowercase();
+        ext_lower == "md" || ext_lower == "markdown"
+    });
+    markdown && !hidden
+}
+
+/// Markdown files the sidebar lists: visible, and not inside a hidden folder
+/// below the workspace root.
+fn is_sidebar_path(root: &Path, path: &Path) -> bool {
+    let hidden_dir = path
+        .strip_prefix(root)
+        .ok()
+        .and_then(|rel| rel.parent())
+        .is_some_and(|dirs| dirs.components().any(|c| c.as_os_str().to_string_lossy().starts_with('.')));
+    is_markdown_path(path) && !hidden_dir
+}
+
+/// Creates, removes, and renames change what the sidebar lists; content
+/// edits don't.
+fn changes_file_tree(kind: &EventKind) -> bool {
+    matches!(
+        kind,
+        EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(_))
+    )
+}
+
+/// Tracker ids of a watched file's rename in progress, for `renamed_to`.
+#[derive(Default)]
+struct RenameTracking {
+    /// Set by a `From` for the target, waiting for its `To`.
+    pending_from: Option<usize>,
+    /// Set once a `From` / `To` pair has been reported, so the `Both` that
+    /// inotify sends after the pair isn't reported again.
+    paired: Option<usize>,
+}
+
+/// Where a rename moved `target`, when notify can correlate both ends: either
+/// one event carrying `[from, to]`, or a `From` / `To` pair sharing a tracker
+/// id. Each rename is reported once, even though inotify sends `From`, `To`
+/// and then `Both`. Uncorrelated moves return `None` and surface as
+/// delete/create.
+fn renamed_to(event: &Event, target: &Path, tracking: &mut RenameTracking) -> Option<PathBuf> {
+    let EventKind::Modify(ModifyKind::Name(mode)) = event.kind else {
+        return None;
+    };
+    match mode {
+        RenameMode::Both if event.tracker().is_some() && event.tracker() == tracking.paired => {
+            tracking.paired = None;
+            None
+        }
+        RenameMode::Both if event.paths.len() == 2 && event.paths[0] == target => Some(event.paths[1].clone()),
+        RenameMode::From if event.paths.iter().any(|p| p == target) => {
+            tracking.pending_from = event.tracker();
+            None
+        }
+        RenameMode::To if tracking.pending_from.is_some() && event.tracker() == tracking.pending_from => {
+            tracking.paired = tracking.pending_from.take();
+            event.paths.first().cloned()
+        }
+        _ => None,
+    }
+}
+
+/// Event for a watched file whose burst of events has settled. Atomic saves
+/// remove and recreate the file, so a `Remove` only counts as a deletion if
+/// the file is still gone once things are quiet.
+fn settled_event_name(path: &Path) -> &'static str {
+    if path.exists() {
+        "file-changed"
+    } else {
+        "file-deleted"
+    }
+}
+
+/// Calls `on_ready` for each path once its events have been quiet for
+/// `window`. Runs until the sending side (owned by a watcher) is dropped.
+fn spawn_debounced_worker(window: Duration, rx: Receiver<PathBuf>, mut on_ready: impl FnMut(PathBuf) + Send + 'static) {
+    std::thread::spawn(move || {
+        let mut debouncer = Debouncer::new(window);
+        loop {
+            let received = match debouncer.next_ready_in(Instant::now()) {
+                Some(wait) => rx.recv_timeout(wait),
+                None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match received {
+                Ok(path) => debouncer.record(path, Instant::now()),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            for path in debouncer.drain_ready(Instant::now()) {
+                on_ready(path);
+            }
+        }
+    });
+}
+
+fn spawn_reindex_worker(handle: AppHandle, rx: Receiver<PathBuf>) {
+    spawn_debounced_worker(REINDEX_DEBOUNCE, rx, move |path| {
+        // Reads off the DB lock with the size/binary guard and read timeout
+        if let Err(e) = index_file_from_disk(&handle.state::<DbPool>(), &path.to_string_lossy()) {
+            eprintln!("[watcher] auto-reindex failed for {}: {e}", path.display());
+        }
+    });
+}
+
 #[derive(Default)]
 pub struct FileWatcher {
-    watcher: Option<RecommendedWatcher>,
-    watched_dir: Option<PathBuf>,
+    /// One watcher per watched file, keyed by the path the frontend passed in.
+    file_watchers: HashMap<String, RecommendedWatcher>,
+    workspace_watcher: Option<RecommendedWatcher>,
+    workspace_dir: Option<PathBuf>,
 }
 
 impl FileWatcher {
@@ -19,9 +290,11 @@ impl FileWatcher {
         Self::default()
     }
 
+    /// Watches a single file. Watching a path that is already watched is a no-op.
     pub fn watch(&mut self, path: &str, app_handle: &AppHandle) -> Result<(), String> {
-        // Stop watching previous path if any
-        self.unwatch()?;
+        if self.file_watchers.contains_key(path) {
+            return Ok(());
+        }
 
         let target = PathBuf::from(path);
         let parent = target
@@ -35,8 +308,30 @@ impl FileWatcher {
                 .ok_or_else(|| format!("Cannot determine filename for: {path}"))?,
         );
 
+        // Editors often save in several writes; the worker turns each burst
+        // into a single `file-changed` or `file-deleted`.
+        let (changed_tx, changed_rx) = mpsc::channel();
         let file_path = path.to_string();
+        let emit_handle = app_handle.clone();
+        // Set once `file-renamed` has gone out, so the old path vanishing
+        // isn't also reported as a deletion.
+        let renamed = Arc::new(AtomicBool::new(false));
+        let worker_renamed = Arc::clone(&renamed);
+        spawn_debounced_worker(FILE_CHANGED_DEBOUNCE, changed_rx, move |target| {
+            let event_name = settled_event_name(&target);
+            if event_name == "file-deleted" && worker_renamed.swap(false, Ordering::SeqCst) {
+                return;
+            }
+            let _ = emit_handle.emit(
+                event_name,
+                FileChangedPayload {
+                    path: file_path.clone(),
+                },
+            );
+        });
         let handle = app_handle.clone();
+        let renamed_from = path.to_string();
+        let mut rename_tracking = RenameTracking::default();
 
         let mut watcher = notify::recommended_watcher(move |res: Result<Event, notify::Error>| {
             match res {
@@ -48,18 +343,28 @@ impl FileWatcher {
                         return;
                     }
 
+                    if let Some(to) = renamed_to(&event, &target_full, &mut rename_tracking) {
+                        renamed.store(true, Ordering::SeqCst);
+                        let _ = handle.emit(
+                            "file-renamed",
+                            FileRenamedPayload {
+                                from: renamed_from.clone(),
+                                to: to.to_string_lossy().to_string(),
+                            },
+                        );
+                        return;
+                    }
+
                     // Only emit when the event involves our target file (full path match)
                     let is_target = event.paths.contains(&target_full);
                     if !is_target {
                         return;
                     }
+                    if matches!(event.kind, EventKind::Modify(_)) && !is_external_change(&handle, &target_full) {
+                        return;
+                    }
 
-                    let _ = handle.emit(
-                        "file-changed",
-                        FileChangedPayload {
-                            path: file_path.clone(),
-                        },
-                    );
+                    let _ = changed_tx.send(target_full.clone());
                 }
                 Err(e) => {
                     eprintln!("[watcher] notify error: {e}");
@@ -72,18 +377,101 @@ impl FileWatcher {
             .watch(&parent, RecursiveMode::NonRecursive)
             .map_err(|e| format!("Failed to watch directory: {e}"))?;
 
-        self.watched_dir = Some(parent);
-        self.watcher = Some(watcher);
+        self.file_watchers.insert(path.to_string(), watcher);
+
+        Ok(())
+    }
+
+    /// Stops watching `path`, or every watched file when `path` is `None`.
+    /// Dropping a watcher releases its OS watch.
+    pub fn unwatch(&mut self, path: Option<&str>) -> Result<(), String> {
+        match path {
+            Some(path) => {
+                self.file_watchers.remove(path);
+            }
+            None => self.file_watchers.clear(),
+        }
+        Ok(())
+    }
+
+    /// Watches a workspace recursively, emitting `file-changed` / `file-created`
+    /// for markdown files, plus `directory-changed` when one is created,
+    /// renamed, or removed. With `auto_reindex`, those files are also re-indexed
+    /// once their burst of events settles.
+    pub fn watch_directory(&mut self, dir: &str, auto_reindex: bool, app_handle: &AppHandle) -> Result<(), String> {
+        self.unwatch_directory()?;
+
+        let root = PathBuf::from(dir);
+        if !root.is_dir() {
+            return Err(format!("'{dir}' is not a directory"));
+        }
+
+        let reindex_tx = if auto_reindex {
+            let (tx, rx) = mpsc::channel();
+            spawn_reindex_worker(app_handle.clone(), rx);
+            Some(tx)
+        } else {
+            None
+        };
+        let handle = app_handle.clone();
+        let watch_root = root.clone();
+
+        let mut watcher = notify::recommended_watcher(move |res: Result<Event, notify::Error>| {
+            match res {
+                Ok(event) => {
+                    for path in event.paths.iter().filter(|p| is_sidebar_path(&watch_root, p)) {
+                        if changes_file_tree(&event.kind) && !is_replaced_in_place(&handle, path) {
+                            let _ = handle.emit(
+                                "directory-changed",
+                                FileChangedPayload {
+                                    path: path.to_string_lossy().to_string(),
+                                },
+                            );
+                        }
+
+                        let event_name = match event.kind {
+                            EventKind::Modify(_) => "file-changed",
+                            EventKind::Create(_) => "file-created",
+                            _ => continue,
+                        };
+                        if event_name == "file-changed" && !is_external_change(&handle, path) {
+                            continue;
+                        }
+                        let _ = handle.emit(
+                            event_name,
+                            FileChangedPayload {
+                                path: path.to_string_lossy().to_string(),
+                            },
+                        );
+                        if let Some(tx) = &reindex_tx {
+                            let _ = tx.send(path.clone());
+                        }
+                    }
+                }
+                Err(e) => {
+                    eprintln!("[watcher] notify error: {e}");
+                }
+            }
+        })
+        .map_err(|e| format!("Failed to create directory watcher: {e}"))?;
+
+        watcher
+            .watch(&root, RecursiveMode::Recursive)
+            .map_err(|e| format!("Failed to watch directory: {e}"))?;
+
+        self.workspace_dir = Some(root);
+        self.workspace_watcher = Some(watcher);
 
         Ok(())
     }
 
-    pub fn unwatch(&mut self) -> Result<(), String> {
-        if let (Some(mut watcher), Some(ref dir)) = (self.watcher.take(), &self.watched_dir) {
+    pub fn unwatch_directory(&mut self) -> Result<(), String> {
+        if let (Some(mut watcher), Some(ref dir)) = (self.workspace_watcher.take(), &self.workspace_dir) {
             let _ = watcher.unwatch(dir);
         }
-        self.watched_dir = None;
-        self.watcher = None;
+        // Dropping the watcher drops its reindex sender, which stops the worker
+        self.workspace_dir = None;
+        self.workspace_watcher = None;
         Ok(())
     }
 }
@@ -100,10 +488,234 @@ pub fn watch_file(
     watcher.watch(&path, &app_handle)
 }
 
+/// Stops watching `path`; without a path, stops watching every file.
 #[tauri::command]
-pub fn unwatch_file(state: tauri::State<'_, Mutex<FileWatcher>>) -> Result<(), String> {
+pub fn unwatch_file(path: Option<String>, state: tauri::State<'_, Mutex<FileWatcher>>) -> Result<(), String> {
     let mut watcher = state
         .lock()
         .map_err(|e| format!("Failed to lock watcher state: {e}"))?;
-    watcher.unwatch()
+    watcher.unwatch(path.as_deref())
+}
+
+#[tauri::command]
+pub fn watch_directory(
+    dir: String,
+    auto_reindex: Option<bool>,
+    state: tauri::State<'_, Mutex<FileWatcher>>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let mut watcher = state
+        .lock()
+        .map_err(|e| format!("Failed to lock watcher state: {e}"))?;
+    watcher.watch_directory(&dir, auto_reindex.unwrap_or(false), &app_handle)
+}
+
+#[tauri::command]
+pub fn unwatch_directory(state: tauri::State<'_, Mutex<FileWatcher>>) -> Result<(), String> {
+    let mut watcher = state
+        .lock()
+        .map_err(|e| format!("Failed to lock watcher state: {e}"))?;
+    watcher.unwatch_directory()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn burst_of_events_coalesces_into_one_reindex() {
+        let window = Duration::from_millis(500);
+        let mut debouncer = Debouncer::new(window);
+        let start = Instant::now();
+        let path = PathBuf::from("/ws/note.md");
+
+        for i in 0..5 {
+            debouncer.record(path.clone(), start + Duration::from_millis(i * 100));
+        }
+        // Still inside the quiet window of the last save
+        assert!(debouncer.drain_ready(start + Duration::from_millis(700)).is_empty());
+
+        let ready = debouncer.drain_ready(start + Duration::from_millis(900));
+        assert_eq!(ready, vec![path]);
+        assert!(debouncer.drain_ready(start + Duration::from_secs(5)).is_empty());
+    }
+
+    #[test]
+    fn debouncer_tracks_paths_independently() {
+        let mut debouncer = Debouncer::new(Duration::from_millis(500));
+        let start = Instant::now();
+        debouncer.record(PathBuf::from("/ws/a.md"), start);
+        debouncer.record(PathBuf::from("/ws/b.md"), start + Duration::from_millis(400));
+
+        let ready = debouncer.drain_ready(start + Duration::from_millis(600));
+        assert_eq!(ready, vec![PathBuf::from("/ws/a.md")]);
+        let ready = debouncer.drain_ready(start + Duration::from_millis(900));
+        assert_eq!(ready, vec![PathBuf::from("/ws/b.md")]);
+    }
+
+    #[test]
+    fn only_visible_markdown_paths_are_watched() {
+        assert!(is_markdown_path(Path::new("/ws/a.md")));
+        assert!(is_markdown_path(Path::new("/ws/B.MARKDOWN")));
+        assert!(!is_markdown_path(Path::new("/ws/.a.md")));
+        assert!(!is_markdown_path(Path::new("/ws/a.txt")));
+    }
+
+    #[test]
+    fn self_save_is_not_an_external_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("note.md");
+        let writes = SelfWrites::default();
+
+        writes.record(&path, b"saved by margin");
+        std::fs::write(&path, "saved by margin").unwrap();
+        // Both events of the save burst are suppressed
+        assert!(!writes.is_external_change(&path));
+        assert!(!writes.is_external_change(&path));
+
+        std::fs::write(&path, "edited elsewhere").unwrap();
+        assert!(writes.is_external_change(&path));
+        // Expectation is gone: later edits always count
+        std::fs::write(&path, "saved by margin").unwrap();
+        assert!(writes.is_external_change(&path));
+    }
+
+    #[test]
+    fn atomic_self_save_only_changes_the_tree_when_it_creates_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let existing = dir.path().join("note.md");
+        std::fs::write(&existing, "old").unwrap();
+        let created = dir.path().join("new.md");
+        let writes = SelfWrites::default();
+
+        writes.record(&existing, b"saved by margin");
+        std::fs::write(&existing, "saved by margin").unwrap();
+        assert!(writes.is_replaced_in_place(&existing));
+
+        writes.record(&created, b"first save");
+        std::fs::write(&created, "first save").unwrap();
+        assert!(!writes.is_replaced_in_place(&created));
+
+        std::fs::write(&existing, "edited elsewhere").unwrap();
+        assert!(!writes.is_replaced_in_place(&existing));
+    }
+
+    #[test]
+    fn untracked_paths_are_external_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("other.md");
+        std::fs::write(&path, "x").unwrap();
+        assert!(SelfWrites::default().is_external_change(&path));
+    }
+
+    #[test]
+    fn debouncer_reports_time_until_next_path_is_ready() {
+        let mut debouncer = Debouncer::new(FILE_CHANGED_DEBOUNCE);
+        let start = Instant::now();
+        assert_eq!(debouncer.next_ready_in(start), None);
+
+        debouncer.record(PathBuf::from("/ws/a.md"), start);
+        assert_eq!(debouncer.next_ready_in(start + Duration::from_millis(50)), Some(Duration::from_millis(150)));
+        // A lone save is ready as soon as the window passes
+        assert_eq!(debouncer.next_ready_in(start + Duration::from_millis(250)), Some(Duration::ZERO));
+        assert_eq!(debouncer.drain_ready(start + Duration::from_millis(250)), vec![PathBuf::from("/ws/a.md")]);
+        assert_eq!(debouncer.next_ready_in(start + Duration::from_millis(250)), None);
+    }
+
+    #[test]
+    fn removed_file_settles_as_deleted_but_atomic_save_as_changed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("note.md");
+        std::fs::write(&path, "v1").unwrap();
+        assert_eq!(settled_event_name(&path), "file-changed");
+
+        // Atomic save: remove then recreate within one burst
+        std::fs::remove_file(&path).unwrap();
+        std::fs::write(&path, "v2").unwrap();
+        assert_eq!(settled_event_name(&path), "file-changed");
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(settled_event_name(&path), "file-deleted");
+    }
+
+    #[test]
+    fn self_write_expectation_expires_after_grace_window() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("note.md");
+        let writes = SelfWrites::default();
+
+        writes.record(&path, b"saved by margin");
+        std::fs::write(&path, "saved by margin").unwrap();
+        let later = Instant::now() + SELF_WRITE_GRACE + Duration::from_millis(1);
+        assert!(writes.is_external_change_at(&path, later));
+        // The stale expectation is gone for good
+        assert!(writes.is_external_change(&path));
+    }
+
+    #[test]
+    fn sidebar_paths_skip_hidden_folders_and_tree_changes_skip_edits() {
+        let root = Path::new("/ws");
+        assert!(is_sidebar_path(root, Path::new("/ws/notes/a.md")));
+        assert!(!is_sidebar_path(root, Path::new("/ws/.obsidian/a.md")));
+        assert!(!is_sidebar_path(root, Path::new("/ws/notes/.drafts/a.md")));
+        // Only folders below the root count; the root itself may be hidden
+        assert!(is_sidebar_path(Path::new("/home/u/.notes"), Path::new("/home/u/.notes/a.md")));
+
+        use notify::event::{CreateKind, DataChange, RemoveKind};
+        assert!(changes_file_tree(&EventKind::Create(CreateKind::File)));
+        assert!(changes_file_tree(&EventKind::Remove(RemoveKind::File)));
+        assert!(changes_file_tree(&EventKind::Modify(ModifyKind::Name(RenameMode::Both))));
+        assert!(!changes_file_tree(&EventKind::Modify(ModifyKind::Data(DataChange::Content))));
+    }
+
+    #[test]
+    fn renames_are_correlated_when_notify_can_pair_them() {
+        let target = Path::new("/ws/a.md");
+        let rename = |mode| EventKind::Modify(ModifyKind::Name(mode));
+        let mut pending = RenameTracking::default();
+
+        let both = Event::new(rename(RenameMode::Both))
+            .add_path(PathBuf::from("/ws/a.md"))
+            .add_path(PathBuf::from("/ws/b.md"));
+        assert_eq!(renamed_to(&both, target, &mut pending), Some(PathBuf::from("/ws/b.md")));
+
+        let from = Event::new(rename(RenameMode::From)).add_path(PathBuf::from("/ws/a.md")).set_tracker(7);
+        assert_eq!(renamed_to(&from, target, &mut pending), None);
+        let unrelated = Event::new(rename(RenameMode::To)).add_path(PathBuf::from("/ws/x.md")).set_tracker(8);
+        assert_eq!(renamed_to(&unrelated, target, &mut pending), None);
+        let to = Event::new(rename(RenameMode::To)).add_path(PathBuf::from("/ws/c.md")).set_tracker(7);
+        assert_eq!(renamed_to(&to, target, &mut pending), Some(PathBuf::from("/ws/c.md")));
+
+        // Without a tracker the halves can't be paired: fall back to delete/create
+        let from = Event::new(rename(RenameMode::From)).add_path(PathBuf::from("/ws/a.md"));
+        assert_eq!(renamed_to(&from, target, &mut pending), None);
+        let to = Event::new(rename(RenameMode::To)).add_path(PathBuf::from("/ws/d.md"));
+        assert_eq!(renamed_to(&to, target, &mut pending), None);
+    }
+
+    #[test]
+    fn inotify_rename_sequence_is_reported_once() {
+        let target = Path::new("/ws/a.md");
+        let rename = |mode| EventKind::Modify(ModifyKind::Name(mode));
+        let mut tracking = RenameTracking::default();
+
+        // inotify: From, To (same cookie), then Both carrying both paths
+        let events = [
+            Event::new(rename(RenameMode::From)).add_path(PathBuf::from("/ws/a.md")).set_tracker(3),
+            Event::new(rename(RenameMode::To)).add_path(PathBuf::from("/ws/b.md")).set_tracker(3),
+            Event::new(rename(RenameMode::Both))
+                .add_path(PathBuf::from("/ws/a.md"))
+                .add_path(PathBuf::from("/ws/b.md"))
+                .set_tracker(3),
+        ];
+        let reported: Vec<PathBuf> = events.iter().filter_map(|e| renamed_to(e, target, &mut tracking)).collect();
+        assert_eq!(reported, vec![PathBuf::from("/ws/b.md")]);
+
+        // A later rename with a new cookie is reported again
+        let both = Event::new(rename(RenameMode::Both))
+            .add_path(PathBuf::from("/ws/a.md"))
+            .add_path(PathBuf::from("/ws/c.md"))
+            .set_tracker(4);
+        assert_eq!(renamed_to(&both, target, &mut tracking), Some(PathBuf::from("/ws/c.md")));
+    }
 }
@@ -43,6 +43,7 @@ const makeCorrection = (id: string): CorrectionDetail => ({
   highlightColor: "yellow",
   writingType: null,
   polarity: null,
+  category: null,
   synthesizedAt: null,
   documentTitle: null,
   createdAt: Date.now(),
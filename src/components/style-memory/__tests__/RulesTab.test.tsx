@@ -41,11 +41,12 @@ const baseRule = {
 beforeEach(() => {
   vi.clearAllMocks();
   vi.mocked(getWritingRules).mockResolvedValue([baseRule]);
-  vi.mocked(updateWritingRule).mockResolvedValue(undefined);
+  vi.mocked(updateWritingRule).mockResolvedValue(baseRule);
   vi.mocked(deleteWritingRule).mockResolvedValue(undefined);
   vi.mocked(exportWritingRules).mockResolvedValue({
     markdownPath: "~/.margin/writing-rules.md",
     hookPath: "~/.claude/hooks/writing_guard.py",
+    vscodePath: null,
     ruleCount: 1,
   });
 });
@@ -122,10 +122,32 @@ describe("useFileWatcher", () => {
       await flush();
     });
 
-    expect(mockInvoke).toHaveBeenCalledWith("unwatch_file");
+    expect(mockInvoke).toHaveBeenCalledWith("unwatch_file", {
+      path: "/tmp/test.md",
+    });
     expect(mockUnlisten).toHaveBeenCalled();
   });
 
+  it("ignores file-changed events for other watched files", async () => {
+    const onChanged = vi.fn();
+    renderHook(() => useFileWatcher("/tmp/test.md", onChanged));
+
+    await act(async () => {
+      await flush();
+    });
+
+    act(() => {
+      capturedListener!({ payload: { path: "/tmp/other.md" } });
+    });
+
+    await act(async () => {
+      vi.advanceTimersByTime(150);
+      await flush();
+    });
+
+    expect(onChanged).not.toHaveBeenCalled();
+  });
+
   it("cleanup on filePath change", async () => {
     const onChanged = vi.fn();
     const { rerender } = renderHook(
@@ -154,7 +176,9 @@ describe("useFileWatcher", () => {
     });
 
     // Should have cleaned up a.md
-    expect(mockInvoke).toHaveBeenCalledWith("unwatch_file");
+    expect(mockInvoke).toHaveBeenCalledWith("unwatch_file", {
+      path: "/tmp/a.md",
+    });
     expect(mockUnlisten).toHaveBeenCalled();
 
     // Should have set up b.md
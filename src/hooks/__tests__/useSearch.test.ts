@@ -23,6 +23,8 @@ const fakeSearchResult = (id: string) => ({
   title: `Doc ${id}`,
   snippet: `Snippet for ${id}`,
   rank: 1,
+  filePath: `/tmp/${id}.md`,
+  source: "file",
 });
 
 const fakeFileResult = (name: string) => ({
@@ -8,12 +8,12 @@ import type { PersistedTab } from "@/types/tab";
 // Mock tauri-commands
 const mockGetOpenTabs = vi.fn();
 const mockSaveOpenTabs = vi.fn();
-const mockGetRecentDocuments = vi.fn();
+const mockGetDocument = vi.fn();
 const mockReadFile = vi.fn();
 vi.mock("@/lib/tauri-commands", () => ({
   getOpenTabs: (...args: unknown[]) => mockGetOpenTabs(...args),
   saveOpenTabs: (...args: unknown[]) => mockSaveOpenTabs(...args),
-  getRecentDocuments: (...args: unknown[]) => mockGetRecentDocuments(...args),
+  getDocument: (...args: unknown[]) => mockGetDocument(...args),
   readFile: (...args: unknown[]) => mockReadFile(...args),
 }));
 
@@ -43,6 +43,15 @@ function makeDoc(id: string, title = "Doc"): Document {
   };
 }
 
+// getDocument resolves ids found in `docs` and rejects the rest
+function mockLibrary(docs: Document[]) {
+  mockGetDocument.mockImplementation(async (id: string) => {
+    const doc = docs.find((d) => d.id === id);
+    if (!doc) throw new Error(`Document not found: ${id}`);
+    return doc;
+  });
+}
+
 function makePersistedTab(
   id: string,
   docId: string,
@@ -87,7 +96,7 @@ describe("useTabs", () => {
     uuidCounter = 0;
     mockGetOpenTabs.mockResolvedValue([]);
     mockSaveOpenTabs.mockResolvedValue(undefined);
-    mockGetRecentDocuments.mockResolvedValue([]);
+    mockLibrary([]);
     mockReadFile.mockResolvedValue("");
   });
 
@@ -106,7 +115,7 @@ describe("useTabs", () => {
         makePersistedTab("t1", "a", 0, false),
         makePersistedTab("t2", "b", 1, true),
       ]);
-      mockGetRecentDocuments.mockResolvedValue([docA, docB]);
+      mockLibrary([docA, docB]);
 
       const { result } = renderHook(() => useTabs({ snapshotFn: makeSnapshotFn() }));
       await flushMount();
@@ -124,7 +133,7 @@ describe("useTabs", () => {
         makePersistedTab("t1", "a", 0, true),
         makePersistedTab("t2", "missing", 1, false),
       ]);
-      mockGetRecentDocuments.mockResolvedValue([docA]);
+      mockLibrary([docA]);
 
       const { result } = renderHook(() => useTabs({ snapshotFn: makeSnapshotFn() }));
       await flushMount();
@@ -149,7 +158,7 @@ describe("useTabs", () => {
       mockGetOpenTabs.mockResolvedValue([
         makePersistedTab("t1", "a", 0, true),
       ]);
-      mockGetRecentDocuments.mockResolvedValue([docA]);
+      mockLibrary([docA]);
       mockReadFile.mockResolvedValue("# Hello World");
 
       const { result } = renderHook(() => useTabs({ snapshotFn: makeSnapshotFn() }));
@@ -23,7 +23,7 @@ export interface UseAnnotationsReturn {
   }) => Promise<Highlight>;
   deleteHighlight: (id: string) => Promise<void>;
 
-  createMarginNote: (highlightId: string, content: string) => Promise<MarginNote>;
+  createMarginNote: (highlightId: string, content: string, parentNoteId?: string) => Promise<MarginNote>;
   updateMarginNote: (id: string, content: string) => Promise<void>;
   deleteMarginNote: (id: string) => Promise<void>;
 
@@ -101,10 +101,11 @@ export function useAnnotations(onMutate?: () => void): UseAnnotationsReturn {
   }, [onMutate]);
 
   const createMarginNote = useCallback(
-    async (highlightId: string, content: string): Promise<MarginNote> => {
+    async (highlightId: string, content: string, parentNoteId?: string): Promise<MarginNote> => {
       const note = await invoke<MarginNote>("create_margin_note", {
         highlightId,
         content,
+        parentNoteId: parentNoteId ?? null,
       });
       setMarginNotes((prev) => [...prev, note]);
       onMutate?.();
@@ -128,7 +129,17 @@ export function useAnnotations(onMutate?: () => void): UseAnnotationsReturn {
 
   const deleteMarginNote = useCallback(async (id: string) => {
     await invoke("delete_margin_note", { id });
-    setMarginNotes((prev) => prev.filter((n) => n.id !== id));
+    // Replies cascade with their parent; parents always precede replies
+    setMarginNotes((prev) => {
+      const removed = new Set([id]);
+      return prev.filter((n) => {
+        if (removed.has(n.id) || (n.parent_note_id && removed.has(n.parent_note_id))) {
+          removed.add(n.id);
+          return false;
+        }
+        return true;
+      });
+    });
     onMutate?.();
   }, [onMutate]);
 
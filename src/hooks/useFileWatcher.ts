@@ -18,6 +18,8 @@ export function useFileWatcher(
     // Listen FIRST so no events are lost during watcher setup
     const unlistenPromise = listen<{ path: string }>("file-changed", (event) => {
       if (cancelled) return;
+      // Other open tabs have their own watchers
+      if (event.payload.path !== filePath) return;
 
       // Debounce: coalesce rapid multi-event bursts from a single save
       if (debounceTimer) clearTimeout(debounceTimer);
@@ -32,7 +34,7 @@ export function useFileWatcher(
     return () => {
       cancelled = true;
       if (debounceTimer) clearTimeout(debounceTimer);
-      invoke("unwatch_file").catch(console.error);
+      invoke("unwatch_file", { path: filePath }).catch(console.error);
       void unlistenPromise.then((fn) => fn()).catch(console.error);
     };
   }, [filePath]);
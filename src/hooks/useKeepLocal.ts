@@ -1,6 +1,7 @@
 import { useState, useCallback, useEffect, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import type { KeepLocalItem, KeepLocalListResult } from "@/types/keep-local";
+import type { Document } from "@/types/document";
 
 const HEALTH_INTERVAL_MS = 30_000;
 const SEARCH_DEBOUNCE_MS = 300;
@@ -47,6 +48,16 @@ export function useKeepLocal() {
     return invoke<string>("keep_local_get_content", { itemId });
   }, []);
 
+  // Drops every cached article body; returns how many were removed
+  const clearCache = useCallback(async (): Promise<number> => {
+    return invoke<number>("clear_keep_local_cache");
+  }, []);
+
+  // Records the item as a Margin document (one per keep-local id) and indexes it
+  const importItem = useCallback(async (itemId: string): Promise<Document> => {
+    return invoke<Document>("import_keep_local_item", { itemId });
+  }, []);
+
   const search = useCallback(
     (q: string) => {
       setQuery(q);
@@ -81,5 +92,5 @@ export function useKeepLocal() {
     wasOnlineRef.current = isOnline;
   }, [isOnline, loadItems, query]);
 
-  return { items, isOnline, isLoading, query, search, getContent, checkHealth };
+  return { items, isOnline, isLoading, query, search, getContent, importItem, clearCache, checkHealth };
 }
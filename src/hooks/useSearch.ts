@@ -7,6 +7,8 @@ export interface SearchResult {
   title: string;
   snippet: string;
   rank: number;
+  filePath: string | null;
+  source: string | null;
 }
 
 export interface FileResult {
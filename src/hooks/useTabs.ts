@@ -2,7 +2,7 @@ import { useState, useCallback, useRef, useEffect } from "react";
 import type { Tab, TabCache, PersistedTab } from "@/types/tab";
 import type { Document } from "@/types/document";
 import type { Highlight, MarginNote } from "@/types/annotations";
-import { getOpenTabs, saveOpenTabs, getRecentDocuments, readFile } from "@/lib/tauri-commands";
+import { getOpenTabs, saveOpenTabs, getDocument, readFile } from "@/lib/tauri-commands";
 import { stat } from "@tauri-apps/plugin-fs";
 
 export interface SnapshotData {
@@ -62,6 +62,7 @@ export function useTabs({ snapshotFn, onFileMissing }: UseTabsOptions): UseTabsR
           tab_order: t.order,
           is_active: t.id === currentActiveId,
           created_at: Date.now(),
+          pinned: t.pinned ?? false,
         }));
       saveOpenTabs(persisted).catch(console.error);
     }, 500);
@@ -78,9 +79,13 @@ export function useTabs({ snapshotFn, onFileMissing }: UseTabsOptions): UseTabsR
           return;
         }
 
-        // Load document records to reconstruct tabs
-        const recentDocs = await getRecentDocuments(100);
-        const docMap = new Map(recentDocs.map((d) => [d.id, d]));
+        // Load each tab's document record; tabs whose document is gone are dropped
+        const docs = await Promise.all(
+          persisted.map((pt) => getDocument(pt.document_id).catch(() => null)),
+        );
+        const docMap = new Map(
+          docs.filter((d): d is Document => d !== null).map((d) => [d.id, d]),
+        );
 
         // Check file existence in parallel (non-file and active tabs skip stat)
         const candidates = persisted
@@ -118,6 +123,7 @@ export function useTabs({ snapshotFn, onFileMissing }: UseTabsOptions): UseTabsR
             title: doc.title ?? "Untitled",
             isDirty: false,
             order: pt.tab_order,
+            pinned: pt.pinned,
           };
           restoredTabs.push(tab);
           if (pt.is_active) activeId = pt.id;
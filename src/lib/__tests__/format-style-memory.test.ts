@@ -12,6 +12,7 @@ function correction(overrides: Partial<CorrectionRecord> = {}): CorrectionRecord
     createdAt: 1700000000000,
     writingType: null,
     polarity: null,
+    category: null,
     ...overrides,
   };
 }
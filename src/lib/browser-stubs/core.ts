@@ -56,6 +56,7 @@ type HighlightRow = {
 type MarginNoteRow = {
   id: string;
   highlight_id: string;
+  parent_note_id: string | null;
   content: string;
   created_at: number;
   updated_at: number;
@@ -76,6 +77,7 @@ function uid(): string {
 const handlers: Record<string, (args: Record<string, unknown>) => unknown> = {
   // --- Mount-time (must return correct shape or app crashes) ----------------
   get_recent_documents: () => [sampleDocument],
+  get_document: () => sampleDocument,
   get_open_tabs: () => [],
   index_all_documents: () => ({ indexed: 0, skipped: 0, errors: 0 }),
   drain_pending_open_files: () => [],
@@ -128,6 +130,7 @@ const handlers: Record<string, (args: Record<string, unknown>) => unknown> = {
     const row: MarginNoteRow = {
       id: uid(),
       highlight_id: a.highlightId as string,
+      parent_note_id: (a.parentNoteId as string | undefined) ?? null,
       content: a.content as string,
       created_at: Date.now(),
       updated_at: Date.now(),
@@ -145,7 +148,14 @@ const handlers: Record<string, (args: Record<string, unknown>) => unknown> = {
   },
 
   delete_margin_note: (a) => {
-    marginNotes = marginNotes.filter((n) => n.id !== a.id);
+    const removed = new Set([a.id as string]);
+    marginNotes = marginNotes.filter((n) => {
+      if (removed.has(n.id) || (n.parent_note_id && removed.has(n.parent_note_id))) {
+        removed.add(n.id);
+        return false;
+      }
+      return true;
+    });
   },
 
   // --- Search (empty) -------------------------------------------------------
@@ -156,8 +166,9 @@ const handlers: Record<string, (args: Record<string, unknown>) => unknown> = {
 
   // --- Keep-local (offline) -------------------------------------------------
   keep_local_health: () => ({ ok: false, now: Date.now() }),
-  keep_local_list_items: () => ({ items: [], count: 0 }),
+  keep_local_list_items: () => ({ items: [], count: 0, failedCount: 0 }),
   keep_local_get_content: () => "",
+  clear_keep_local_cache: () => 0,
 
   // --- File watcher (no-op) -------------------------------------------------
   watch_file: () => undefined,
@@ -186,12 +197,14 @@ const handlers: Record<string, (args: Record<string, unknown>) => unknown> = {
   }),
 
   get_writing_rules: () => [],
-  export_writing_rules: () => ({ markdownPath: "", hookPath: "", ruleCount: 0 }),
+  export_writing_rules: () => ({ markdownPath: "", hookPath: "", vscodePath: null, ruleCount: 0 }),
   update_writing_rule: () => undefined,
   delete_writing_rule: () => undefined,
 
   // --- Tabs -----------------------------------------------------------------
   save_open_tabs: () => undefined,
+  move_tab: () => undefined,
+  close_unpinned_tabs: () => 0,
 
   // --- Snapshots ------------------------------------------------------------
   save_content_snapshot: () => "mock-snapshot",
@@ -1,6 +1,13 @@
 import { invoke } from "@tauri-apps/api/core";
 import type { Document, FileEntry } from "@/types/document";
-import type { CorrectionInput, CorrectionRecord, CorrectionDetail, DocumentCorrections } from "@/types/annotations";
+import type {
+  CorrectionInput,
+  CorrectionRecord,
+  CorrectionDetail,
+  DocumentCorrections,
+  Highlight,
+  HighlightWithNotes,
+} from "@/types/annotations";
 import type { PersistedTab } from "@/types/tab";
 import type { WritingType } from "@/lib/writing-types";
 
@@ -12,26 +19,124 @@ export async function readFile(path: string): Promise<string> {
   return invoke<string>("read_file", { path });
 }
 
-export async function saveFile(path: string, content: string): Promise<void> {
-  return invoke<void>("save_file", { path, content });
+/** With `backup`, the current file is first copied to `~/.margin/backups`. */
+export async function saveFile(path: string, content: string, append?: boolean, backup?: boolean): Promise<void> {
+  return invoke<void>("save_file", {
+    path,
+    content,
+    ...(append === undefined ? {} : { append }),
+    ...(backup === undefined ? {} : { backup }),
+  });
+}
+
+/** Backup timestamps (ms) for the file, oldest first. */
+export async function listBackups(path: string): Promise<number[]> {
+  return invoke<number[]>("list_backups", { path });
+}
+
+/** Restores the backup taken at `timestamp`, returning the restored content. */
+export async function restoreBackup(path: string, timestamp: number): Promise<string> {
+  return invoke<string>("restore_backup", { path, timestamp });
 }
 
 export async function listMarkdownFiles(dir: string): Promise<FileEntry[]> {
   return invoke<FileEntry[]>("list_markdown_files", { dir });
 }
 
-export async function getRecentDocuments(limit?: number): Promise<Document[]> {
-  return invoke<Document[]>("get_recent_documents", { limit });
+export async function getDocument(documentId: string): Promise<Document> {
+  return invoke<Document>("get_document", { documentId });
+}
+
+export async function getRecentDocuments(limit?: number, includeArchived?: boolean): Promise<Document[]> {
+  return invoke<Document[]>("get_recent_documents", { limit, includeArchived });
+}
+
+/** Archived documents drop out of recents but stay searchable. */
+export async function setDocumentArchived(id: string, archived: boolean): Promise<void> {
+  return invoke<void>("set_document_archived", { id, archived });
+}
+
+/** Clamped to 0–1 by the backend; resolves with the stored value. */
+export async function setReadingProgress(id: string, progress: number): Promise<number> {
+  return invoke<number>("set_reading_progress", { id, progress });
 }
 
 export async function upsertDocument(doc: Document): Promise<Document> {
   return invoke<Document>("upsert_document", { doc });
 }
 
+export async function addDocumentTag(documentId: string, tag: string): Promise<void> {
+  return invoke<void>("add_document_tag", { documentId, tag });
+}
+
+export async function removeDocumentTag(documentId: string, tag: string): Promise<void> {
+  return invoke<void>("remove_document_tag", { documentId, tag });
+}
+
+export async function getDocumentTags(documentId: string): Promise<string[]> {
+  return invoke<string[]>("get_document_tags", { documentId });
+}
+
+export async function getDocumentsByTag(tag: string): Promise<Document[]> {
+  return invoke<Document[]>("get_documents_by_tag", { tag });
+}
+
+/** Removes the document from the library with its annotations; the file stays. */
+export async function deleteDocument(documentId: string): Promise<void> {
+  return invoke<void>("delete_document", { documentId });
+}
+
 export async function renameFile(oldPath: string, newName: string): Promise<Document> {
   return invoke<Document>("rename_file", { oldPath, newName });
 }
 
+export async function moveFile(oldPath: string, newDir: string): Promise<Document> {
+  return invoke<Document>("move_file", { oldPath, newDir });
+}
+
+export async function createFile(dir: string, name: string, initialContent?: string): Promise<Document> {
+  return invoke<Document>("create_file", { dir, name, initialContent });
+}
+
+/** Copies the file's text only; a taken `newName` becomes "name (copy).md". */
+export async function duplicateFile(path: string, newName: string): Promise<Document> {
+  return invoke<Document>("duplicate_file", { path, newName });
+}
+
+export interface ImportSummary {
+  imported: number;
+  skipped: number;
+}
+
+/** Adds every untracked markdown file under `dir` to the library and indexes it. */
+export async function importDirectory(dir: string): Promise<ImportSummary> {
+  return invoke<ImportSummary>("import_directory", { dir });
+}
+
+/**
+ * Moves the file to the system Trash / Recycle Bin and forgets its document,
+ * annotations and tabs. Rejects, leaving everything in place, if the file
+ * can't be trashed.
+ */
+export async function deleteFile(path: string): Promise<void> {
+  return invoke<void>("delete_file", { path });
+}
+
+/** Words of prose, skipping front matter and fenced code blocks. */
+export async function wordCount(content: string): Promise<number> {
+  return invoke<number>("word_count", { content });
+}
+
+export interface FileMetadata {
+  size_bytes: number;
+  modified_ms: number | null;
+  created_ms: number | null;
+}
+
+export async function getFileMetadata(path: string): Promise<FileMetadata> {
+  return invoke<FileMetadata>("get_file_metadata", { path });
+}
+
 export async function drainPendingOpenFiles(): Promise<string[]> {
   return invoke<string[]>("drain_pending_open_files");
 }
@@ -44,6 +149,16 @@ export async function saveOpenTabs(tabs: PersistedTab[]): Promise<void> {
   return invoke<void>("save_open_tabs", { tabs });
 }
 
+/** Moves one tab to display position `newOrder`, kept within its pinned or unpinned group. */
+export async function moveTab(tabId: string, newOrder: number): Promise<void> {
+  return invoke<void>("move_tab", { tabId, newOrder });
+}
+
+/** Closes every tab that isn't pinned; resolves with how many were closed. */
+export async function closeUnpinnedTabs(): Promise<number> {
+  return invoke<number>("close_unpinned_tabs");
+}
+
 export async function persistCorrections(
   corrections: CorrectionInput[],
   documentId: string,
@@ -62,22 +177,34 @@ export async function persistCorrections(
   });
 }
 
-export async function getAllCorrections(limit?: number): Promise<CorrectionRecord[]> {
-  return invoke<CorrectionRecord[]>(
-    "get_all_corrections",
-    limit === undefined ? {} : { limit },
-  );
+export async function getAllCorrections(
+  limit?: number,
+  category?: string,
+  since?: number,
+  until?: number,
+): Promise<CorrectionRecord[]> {
+  const args: Record<string, unknown> = {};
+  if (limit !== undefined) args.limit = limit;
+  if (category !== undefined) args.category = category;
+  if (since !== undefined) args.since = since;
+  if (until !== undefined) args.until = until;
+  return invoke<CorrectionRecord[]>("get_all_corrections", args);
 }
 
 export async function getCorrectionsCount(): Promise<number> {
   return invoke<number>("get_corrections_count");
 }
 
-export async function getCorrectionsByDocument(limit?: number): Promise<DocumentCorrections[]> {
-  return invoke<DocumentCorrections[]>(
-    "get_corrections_by_document",
-    limit === undefined ? {} : { limit },
-  );
+export async function getCorrectionsByDocument(
+  limit?: number,
+  since?: number,
+  until?: number,
+): Promise<DocumentCorrections[]> {
+  const args: Record<string, unknown> = {};
+  if (limit !== undefined) args.limit = limit;
+  if (since !== undefined) args.since = since;
+  if (until !== undefined) args.until = until;
+  return invoke<DocumentCorrections[]>("get_corrections_by_document", args);
 }
 
 export async function updateCorrectionWritingType(highlightId: string, writingType: WritingType): Promise<void> {
@@ -134,9 +261,12 @@ export interface WritingRule {
   updatedAt: number;
 }
 
+export type WritingRulesExportTarget = "markdown" | "hook";
+
 export interface WritingRulesExportResult {
-  markdownPath: string;
-  hookPath: string;
+  markdownPath: string | null;
+  hookPath: string | null;
+  vscodePath: string | null;
   ruleCount: number;
 }
 
@@ -147,8 +277,30 @@ export async function getWritingRules(writingType?: WritingType): Promise<Writin
   );
 }
 
-export async function exportWritingRules(): Promise<WritingRulesExportResult> {
-  return invoke<WritingRulesExportResult>("export_writing_rules");
+export interface WritingRuleFacets {
+  writingTypes: WritingType[];
+  categories: string[];
+  typeLabels: { value: WritingType; label: string }[];
+}
+
+export async function getWritingRuleFacets(): Promise<WritingRuleFacets> {
+  return invoke<WritingRuleFacets>("get_writing_rule_facets");
+}
+
+/**
+ * The guard keeps the last `minSeverity` given, including for CLI re-exports;
+ * omit it to keep that choice, or pass `""` to go back to the default guard.
+ */
+export async function exportWritingRules(
+  targets?: WritingRulesExportTarget[],
+  minSeverity?: WritingRuleSeverity | "",
+  vscodePath?: string,
+): Promise<WritingRulesExportResult> {
+  const args: Record<string, unknown> = {};
+  if (targets !== undefined) args.targets = targets;
+  if (minSeverity !== undefined) args.minSeverity = minSeverity;
+  if (vscodePath !== undefined) args.vscodePath = vscodePath;
+  return invoke<WritingRulesExportResult>("export_writing_rules", args);
 }
 
 export async function getCorrectionsFlat(limit?: number): Promise<CorrectionDetail[]> {
@@ -192,6 +344,23 @@ export async function exportVoiceProfile(): Promise<VoiceProfileExportResult> {
   return invoke<VoiceProfileExportResult>("export_voice_profile");
 }
 
+export interface NewWritingRule {
+  writingType: WritingType;
+  category: string;
+  ruleText: string;
+  whenToApply?: string;
+  why?: string;
+  severity?: WritingRuleSeverity;
+  exampleBefore?: string;
+  exampleAfter?: string;
+  source?: string;
+  notes?: string;
+}
+
+export async function createWritingRule(rule: NewWritingRule): Promise<WritingRule> {
+  return invoke<WritingRule>("create_writing_rule", { rule });
+}
+
 export async function updateWritingRule(
   id: string,
   updates: {
@@ -203,8 +372,8 @@ export async function updateWritingRule(
     exampleAfter?: string;
     notes?: string;
   },
-): Promise<void> {
-  return invoke<void>("update_writing_rule", {
+): Promise<WritingRule> {
+  return invoke<WritingRule>("update_writing_rule", {
     id,
     ruleText: updates.ruleText,
     severity: updates.severity,
@@ -216,6 +385,24 @@ export async function updateWritingRule(
   });
 }
 
+export async function incrementWritingRuleSignal(id: string): Promise<WritingRule> {
+  return invoke<WritingRule>("increment_writing_rule_signal", { id });
+}
+
+export async function exportWritingRulesJson(path: string): Promise<number> {
+  return invoke<number>("export_writing_rules_json", { path });
+}
+
+export interface RuleImportResult {
+  inserted: number;
+  updated: number;
+  skipped: number;
+}
+
+export async function importWritingRules(path: string): Promise<RuleImportResult> {
+  return invoke<RuleImportResult>("import_writing_rules", { path });
+}
+
 export async function deleteWritingRule(id: string): Promise<void> {
   return invoke<void>("delete_writing_rule", { id });
 }
@@ -250,6 +437,55 @@ export async function updateHighlightPositions(
   return invoke<void>("update_highlight_positions", { updates });
 }
 
+export interface AnnotationCounts {
+  highlightCount: number;
+  noteCount: number;
+}
+
+export async function getDocumentAnnotationCounts(
+  documentIds: string[],
+): Promise<Record<string, AnnotationCounts>> {
+  return invoke<Record<string, AnnotationCounts>>("get_document_annotation_counts", { documentIds });
+}
+
+export interface HighlightFeedItem {
+  highlight: Highlight;
+  documentTitle: string | null;
+  documentPath: string | null;
+  notes: string[];
+}
+
+export async function getAllHighlights(limit?: number, offset?: number): Promise<HighlightFeedItem[]> {
+  return invoke<HighlightFeedItem[]>("get_all_highlights", { limit, offset });
+}
+
+export async function restoreHighlight(id: string): Promise<HighlightWithNotes> {
+  return invoke<HighlightWithNotes>("restore_highlight", { id });
+}
+
+export async function purgeDeletedHighlights(olderThanMs: number): Promise<number> {
+  return invoke<number>("purge_deleted_highlights", { olderThanMs });
+}
+
+export async function recolorHighlights(documentId: string, fromColor: string, toColor: string): Promise<number> {
+  return invoke<number>("recolor_highlights", { documentId, fromColor, toColor });
+}
+
+export async function mergeOverlappingHighlights(documentId: string): Promise<number> {
+  return invoke<number>("merge_overlapping_highlights", { documentId });
+}
+
+export interface ReanchorResult {
+  relocated: number;
+  unchanged: number;
+  orphaned: number;
+  orphanedIds: string[];
+}
+
+export async function reanchorHighlights(documentId: string, newContent: string): Promise<ReanchorResult> {
+  return invoke<ReanchorResult>("reanchor_highlights", { documentId, newContent });
+}
+
 export async function markCorrectionsUnsynthesized(highlightIds: string[]): Promise<number> {
   return invoke<number>("mark_corrections_unsynthesized", { highlightIds });
 }
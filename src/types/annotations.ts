@@ -9,16 +9,24 @@ export interface Highlight {
   suffix_context: string | null;
   created_at: number;
   updated_at: number;
+  /** Present only when fetched with `includeTags`. */
+  tags?: string[];
 }
 
 export interface MarginNote {
   id: string;
   highlight_id: string;
+  parent_note_id?: string | null;
   content: string;
   created_at: number;
   updated_at: number;
 }
 
+export interface HighlightWithNotes {
+  highlight: Highlight;
+  notes: MarginNote[];
+}
+
 export interface CorrectionInput {
   highlight_id: string;
   original_text: string;
@@ -29,6 +37,7 @@ export interface CorrectionInput {
   highlight_color: string;
   writing_type: string | null;
   polarity: "positive" | "corrective" | null;
+  category?: string | null;
 }
 
 export interface CorrectionRecord {
@@ -40,6 +49,7 @@ export interface CorrectionRecord {
   createdAt: number;
   writingType: string | null;
   polarity: "positive" | "corrective" | null;
+  category: string | null;
 }
 
 export interface CorrectionDetail {
@@ -50,6 +60,7 @@ export interface CorrectionDetail {
   highlightColor: string;
   writingType: string | null;
   polarity: "positive" | "corrective" | null;
+  category: string | null;
   synthesizedAt: number | null;
   documentTitle: string | null;
   createdAt: number;
@@ -9,6 +9,8 @@ export interface Document {
   word_count: number;
   last_opened_at: number;
   created_at: number;
+  /** 0–1, how far the reader got; absent on documents not yet saved. */
+  reading_progress?: number;
 }
 
 export interface FileEntry {
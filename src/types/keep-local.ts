@@ -20,4 +20,6 @@ export interface KeepLocalHealth {
 export interface KeepLocalListResult {
   items: KeepLocalItem[];
   count: number;
+  /** Items the server returned that couldn't be parsed and were skipped. */
+  failedCount: number;
 }
@@ -7,6 +7,7 @@ export interface Tab {
   title: string;
   isDirty: boolean;
   order: number;
+  pinned?: boolean;
 }
 
 export interface TabCache {
@@ -25,4 +26,5 @@ export interface PersistedTab {
   tab_order: number;
   is_active: boolean;
   created_at: number;
+  pinned?: boolean;
 }